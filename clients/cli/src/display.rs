@@ -0,0 +1,107 @@
+//! Printing of decoded accounts.
+
+use solana_sdk::pubkey::Pubkey;
+
+use hh_client::hh_escrow::state::{GlobalState, Market};
+
+pub fn global_state(global_state: &GlobalState) {
+    println!("version:        {}", global_state.version);
+    println!("owner:          {}", global_state.owner);
+    println!("fee wallet:     {}", global_state.fee_wallet);
+    println!("fee cut bps:    {}", global_state.fee_cut_bps.get());
+    println!("crank tip bps:  {}", global_state.crank_tip_bps.get());
+    println!("referral bps:   {}", global_state.referral_bps.get());
+    let (min_window, max_window) = global_state.resolution_window_bounds();
+    println!("min window:     {}", min_window);
+    println!("max window:     {}", max_window);
+
+    let policy = &global_state.market_policy;
+    println!(
+        "close delay:    {}..{}",
+        policy.min_close_delay, policy.max_close_delay
+    );
+    println!("expiry gap:     ..{}", policy.max_expiry_gap);
+    println!(
+        "delay:          {}..{}",
+        policy.min_resolution_delay, policy.max_resolution_delay
+    );
+    println!("max uri len:    {}", policy.max_uri_len);
+    println!("creation bond:  {}", global_state.creation_bond_lamports);
+    println!("arbiter:        {}", global_state.arbiter);
+    println!("dispute bond:   {}", global_state.dispute_bond_lamports);
+    println!(
+        "template resolver: {}",
+        global_state.template_resolver_program
+    );
+}
+
+/// Prints a market on a single line.
+pub fn market_summary(key: &Pubkey, market: &Market) {
+    println!(
+        "{}  {:?}{}  {}/{} yes  {}/{} no  {}",
+        key,
+        market.outcome,
+        if market.finalized { " (final)" } else { "" },
+        market.yes_filled,
+        market.yes_amount,
+        market.no_filled,
+        market.no_amount,
+        market.metadata.title.as_str().unwrap_or("<invalid title>"),
+    );
+}
+
+pub fn market(key: &Pubkey, market: &Market) {
+    let category = market
+        .metadata
+        .category
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as char)
+        .collect::<String>();
+
+    println!("address:          {}", key);
+    println!("version:          {}", market.version);
+    println!(
+        "title:            {}",
+        market.metadata.title.as_str().unwrap_or("<invalid>")
+    );
+    println!(
+        "uri:              {}",
+        market.metadata.uri.as_str().unwrap_or("<invalid>")
+    );
+    println!("category:         {}", category);
+    println!("creator:          {}", market.creator);
+    println!("resolver:         {}", market.resolver);
+    println!("token mint:       {}", market.token_mint);
+    println!(
+        "yes:              {}/{}",
+        market.yes_filled, market.yes_amount
+    );
+    println!(
+        "no:               {}/{}",
+        market.no_filled, market.no_amount
+    );
+    println!("close ts:         {}", market.close_ts);
+    println!("expiry ts:        {}", market.expiry_ts);
+    println!("outcome:          {:?}", market.outcome);
+    println!("outcome ts:       {}", market.outcome_ts);
+    println!("resolution delay: {}", market.resolution_delay);
+    println!("early resolution: {}", market.allow_early_resolution);
+    println!("max window:       {}", market.status().resolution_window());
+    println!("finalized:        {}", market.finalized);
+    println!("acknowledged:     {}", market.acknowledged);
+    println!("accrued fees:     {}", market.accrued_fees);
+    let unit = if market.creation_bond_in_mint {
+        "tokens"
+    } else {
+        "lamports"
+    };
+    println!("creation bond:    {} {}", market.creation_bond, unit);
+    println!(
+        "resolver bond:    {}/{}",
+        market.resolver_bond_outstanding(),
+        market.resolver_bond
+    );
+    println!("disputed:         {}", market.disputed);
+    println!("slashed:          {}", market.resolver_slashed);
+}
@@ -0,0 +1,593 @@
+//! Admin CLI for the `hh_escrow` program.
+
+mod display;
+mod transaction;
+
+use std::path::PathBuf;
+
+use anchor_lang::Discriminator;
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcProgramAccountsConfig;
+use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_sdk::clock::Clock;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::sysvar;
+
+use hh_client::hh_escrow;
+use hh_client::hh_escrow::instructions::{
+    ArbitrateParams, InitializeGlobalStateParams, SetGlobalStateParams, SetMarketPolicyParams,
+    SetMintPolicyParams, UpdateStateParams,
+};
+use hh_client::hh_escrow::state::{Market, MarketPolicy, Outcome};
+use hh_client::{accounts, escrow, pda};
+
+use crate::transaction::Sender;
+
+#[derive(Parser)]
+#[clap(version, about = "Admin CLI for the P2P prediction programs")]
+struct Cli {
+    /// The JSON RPC URL of the cluster.
+    #[clap(long, short = 'u', default_value = "http://localhost:8899")]
+    url: String,
+    /// The keypair file signing and paying for transactions. Defaults to the
+    /// Solana CLI keypair.
+    #[clap(long, short = 'k')]
+    keypair: Option<PathBuf>,
+    /// Print transactions unsigned, encoded as base58, instead of sending
+    /// them.
+    #[clap(long)]
+    unsigned: bool,
+    /// The fee payer of unsigned transactions. Defaults to the keypair.
+    #[clap(long, requires = "unsigned")]
+    fee_payer: Option<Pubkey>,
+    /// The recent blockhash of unsigned transactions. Fetched from the cluster
+    /// if omitted.
+    #[clap(long, requires = "unsigned")]
+    blockhash: Option<Hash>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Inspect or update the global state.
+    #[clap(subcommand)]
+    Global(GlobalCommand),
+    /// Inspect or resolve markets.
+    #[clap(subcommand)]
+    Market(MarketCommand),
+}
+
+#[derive(Subcommand)]
+enum GlobalCommand {
+    /// Print the global state.
+    Show,
+    /// Initialize the global state, signed by the upgrade authority of the
+    /// program.
+    Init {
+        /// The owner of the global state.
+        #[clap(long)]
+        owner: Pubkey,
+        /// The wallet receiving the protocol fees.
+        #[clap(long)]
+        fee_wallet: Pubkey,
+        /// The protocol fee in basis points.
+        #[clap(long)]
+        protocol_fee_bps: u16,
+        /// The share of the protocol fee tipped to settlement crankers, in
+        /// basis points.
+        #[clap(long, default_value_t = 0)]
+        crank_tip_bps: u16,
+        /// The upgrade authority. Defaults to the keypair.
+        #[clap(long)]
+        authority: Option<Pubkey>,
+    },
+    /// Update the global state, signed by its owner. Omitted fields are kept.
+    Set {
+        /// The new owner of the global state.
+        #[clap(long)]
+        owner: Option<Pubkey>,
+        /// The new wallet receiving the protocol fees.
+        #[clap(long)]
+        fee_wallet: Option<Pubkey>,
+        /// The new protocol fee in basis points.
+        #[clap(long)]
+        fee_cut_bps: Option<u16>,
+        /// The new share of the protocol fee tipped to settlement crankers,
+        /// in basis points.
+        #[clap(long)]
+        crank_tip_bps: Option<u16>,
+        /// The new share of the protocol fee paid to referrers, in basis
+        /// points.
+        #[clap(long)]
+        referral_bps: Option<u16>,
+        /// The new minimum resolution window of a new market, in seconds.
+        #[clap(long)]
+        min_resolution_window: Option<u32>,
+        /// The new maximum resolution window of a new market, in seconds.
+        #[clap(long)]
+        max_resolution_window: Option<u32>,
+        /// The new creation bond of a new market, in lamports.
+        #[clap(long)]
+        creation_bond_lamports: Option<u64>,
+        /// The new arbiter ruling on disputed outcomes.
+        #[clap(long)]
+        arbiter: Option<Pubkey>,
+        /// The new bond a user posts to dispute an outcome, in lamports.
+        #[clap(long)]
+        dispute_bond_lamports: Option<u64>,
+        /// The new resolver program allowed for market templates.
+        #[clap(long)]
+        template_resolver_program: Option<Pubkey>,
+        /// The current owner. Defaults to the keypair.
+        #[clap(long)]
+        authority: Option<Pubkey>,
+    },
+    /// Update the limits on the parameters of new markets, signed by the
+    /// owner of the global state. Omitted fields are kept, and a maximum of 0
+    /// removes the limit.
+    SetMarketPolicy {
+        /// The minimum number of seconds until the close of a new market.
+        #[clap(long)]
+        min_close_delay: Option<u32>,
+        /// The maximum number of seconds until the close of a new market.
+        #[clap(long)]
+        max_close_delay: Option<u32>,
+        /// The maximum number of seconds between the close and the expiry.
+        #[clap(long)]
+        max_expiry_gap: Option<u32>,
+        /// The minimum resolution delay, in seconds.
+        #[clap(long)]
+        min_resolution_delay: Option<u32>,
+        /// The maximum resolution delay, in seconds.
+        #[clap(long)]
+        max_resolution_delay: Option<u32>,
+        /// The maximum length of a market URI.
+        #[clap(long)]
+        max_uri_len: Option<u16>,
+        /// The owner of the global state. Defaults to the keypair.
+        #[clap(long)]
+        authority: Option<Pubkey>,
+    },
+    /// Set the minimum fill and creation bond of new markets denominated in
+    /// a token mint, signed by the owner of the global state.
+    SetMintPolicy {
+        /// The token mint.
+        mint: Pubkey,
+        /// The minimum amount of tokens on either side of a new market.
+        #[clap(long)]
+        min_fill: u64,
+        /// The creation bond of a new market, in tokens. 0 falls back to the
+        /// bond in lamports of the global state.
+        #[clap(long, default_value_t = 0)]
+        creation_bond: u64,
+        /// The owner of the global state. Defaults to the keypair.
+        #[clap(long)]
+        authority: Option<Pubkey>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MarketCommand {
+    /// List the markets of the program.
+    List {
+        /// Only list the markets of this creator.
+        #[clap(long)]
+        creator: Option<Pubkey>,
+        /// Only list the markets denominated in this token mint.
+        #[clap(long)]
+        mint: Option<Pubkey>,
+    },
+    /// Print a market.
+    Show {
+        /// The market address.
+        market: Pubkey,
+    },
+    /// Set the outcome of a market, signed by its resolver.
+    Resolve {
+        /// The market address.
+        market: Pubkey,
+        /// The outcome to set.
+        #[clap(value_enum)]
+        outcome: OutcomeArg,
+        /// The resolver. Defaults to the keypair.
+        #[clap(long)]
+        resolver: Option<Pubkey>,
+    },
+    /// Finalize a market whose resolution delay has passed. Any signer may
+    /// finalize a market.
+    Finalize {
+        /// The market address.
+        market: Pubkey,
+    },
+    /// Collect the fees accrued on markets sharing a token mint, signed by
+    /// the fee wallet or the global state owner.
+    CollectFees {
+        /// The market addresses.
+        #[clap(required = true)]
+        markets: Vec<Pubkey>,
+        /// The collector. Defaults to the keypair.
+        #[clap(long)]
+        collector: Option<Pubkey>,
+    },
+    /// Settle the creation bond of a finalized market. Any signer may settle
+    /// a bond.
+    SettleBond {
+        /// The market address.
+        market: Pubkey,
+    },
+    /// Rule on the disputed outcome of a market, signed by the arbiter.
+    Arbitrate {
+        /// The market address.
+        market: Pubkey,
+        /// The final outcome.
+        #[clap(value_enum)]
+        outcome: OutcomeArg,
+        /// The arbiter. Defaults to the keypair.
+        #[clap(long)]
+        arbiter: Option<Pubkey>,
+    },
+    /// Refund the resolver bond of a finalized market. Any signer may refund
+    /// a bond.
+    SettleResolverBond {
+        /// The market address.
+        market: Pubkey,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum OutcomeArg {
+    Open,
+    Yes,
+    No,
+    Invalid,
+}
+
+impl From<OutcomeArg> for Outcome {
+    fn from(outcome: OutcomeArg) -> Self {
+        match outcome {
+            OutcomeArg::Open => Outcome::Open,
+            OutcomeArg::Yes => Outcome::Yes,
+            OutcomeArg::No => Outcome::No,
+            OutcomeArg::Invalid => Outcome::Invalid,
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let sender = Sender::new(
+        RpcClient::new(cli.url),
+        cli.keypair,
+        cli.unsigned,
+        cli.fee_payer,
+        cli.blockhash,
+    );
+
+    match cli.command {
+        Command::Global(command) => global(&sender, command),
+        Command::Market(command) => market(&sender, command),
+    }
+}
+
+fn global(sender: &Sender, command: GlobalCommand) -> Result<()> {
+    match command {
+        GlobalCommand::Show => {
+            let data = sender.rpc().get_account_data(&pda::global_state())?;
+            display::global_state(&accounts::global_state(&data)?);
+        }
+        GlobalCommand::Init {
+            owner,
+            fee_wallet,
+            protocol_fee_bps,
+            crank_tip_bps,
+            authority,
+        } => {
+            let authority = sender.signer(authority)?;
+            let ix = escrow::initialize_global_state(
+                &authority,
+                &sender.fee_payer()?,
+                &owner,
+                &fee_wallet,
+                InitializeGlobalStateParams {
+                    protocol_fee_bps,
+                    crank_tip_bps,
+                },
+            );
+            sender.send(&[ix], &authority)?;
+        }
+        GlobalCommand::Set {
+            owner,
+            fee_wallet,
+            fee_cut_bps,
+            crank_tip_bps,
+            referral_bps,
+            min_resolution_window,
+            max_resolution_window,
+            creation_bond_lamports,
+            arbiter,
+            dispute_bond_lamports,
+            template_resolver_program,
+            authority,
+        } => {
+            let data = sender.rpc().get_account_data(&pda::global_state())?;
+            let global_state = accounts::global_state(&data)?;
+
+            let authority = sender.signer(authority)?;
+            let ix = escrow::set_global_state(
+                &authority,
+                SetGlobalStateParams {
+                    new_owner: owner.unwrap_or(global_state.owner),
+                    new_fee_cut_bps: fee_cut_bps.unwrap_or_else(|| global_state.fee_cut_bps.get()),
+                    new_fee_wallet: fee_wallet.unwrap_or(global_state.fee_wallet),
+                    new_crank_tip_bps: crank_tip_bps,
+                    new_referral_bps: referral_bps,
+                    new_min_resolution_window: min_resolution_window,
+                    new_max_resolution_window: max_resolution_window,
+                    new_creation_bond_lamports: creation_bond_lamports,
+                    new_arbiter: arbiter,
+                    new_dispute_bond_lamports: dispute_bond_lamports,
+                    new_template_resolver_program: template_resolver_program,
+                },
+            );
+            sender.send(&[ix], &authority)?;
+        }
+        GlobalCommand::SetMarketPolicy {
+            min_close_delay,
+            max_close_delay,
+            max_expiry_gap,
+            min_resolution_delay,
+            max_resolution_delay,
+            max_uri_len,
+            authority,
+        } => {
+            let data = sender.rpc().get_account_data(&pda::global_state())?;
+            let policy = accounts::global_state(&data)?.market_policy;
+
+            let authority = sender.signer(authority)?;
+            let ix = escrow::set_market_policy(
+                &authority,
+                SetMarketPolicyParams {
+                    new_market_policy: MarketPolicy {
+                        min_close_delay: min_close_delay.unwrap_or(policy.min_close_delay),
+                        max_close_delay: max_close_delay.unwrap_or(policy.max_close_delay),
+                        max_expiry_gap: max_expiry_gap.unwrap_or(policy.max_expiry_gap),
+                        min_resolution_delay: min_resolution_delay
+                            .unwrap_or(policy.min_resolution_delay),
+                        max_resolution_delay: max_resolution_delay
+                            .unwrap_or(policy.max_resolution_delay),
+                        max_uri_len: max_uri_len.unwrap_or(policy.max_uri_len),
+                    },
+                },
+            );
+            sender.send(&[ix], &authority)?;
+        }
+        GlobalCommand::SetMintPolicy {
+            mint,
+            min_fill,
+            creation_bond,
+            authority,
+        } => {
+            let authority = sender.signer(authority)?;
+            let ix = escrow::set_mint_policy(
+                &authority,
+                &sender.fee_payer()?,
+                &mint,
+                SetMintPolicyParams {
+                    new_min_fill: min_fill,
+                    new_creation_bond: creation_bond,
+                },
+            );
+            sender.send(&[ix], &authority)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn market(sender: &Sender, command: MarketCommand) -> Result<()> {
+    match command {
+        MarketCommand::List { creator, mint } => {
+            let filter = RpcFilterType::Memcmp(Memcmp {
+                offset: 0,
+                bytes: MemcmpEncodedBytes::Base58(
+                    bs58::encode(Market::discriminator()).into_string(),
+                ),
+                encoding: None,
+            });
+            let config = RpcProgramAccountsConfig {
+                filters: Some(vec![filter]),
+                ..RpcProgramAccountsConfig::default()
+            };
+
+            for (key, account) in sender
+                .rpc()
+                .get_program_accounts_with_config(&hh_escrow::ID, config)?
+            {
+                // Markets in the unversioned layout fail to decode until they
+                // are migrated, and are told apart from corrupt markets.
+                let market = match accounts::market(&account.data) {
+                    Ok(market) => market,
+                    Err(_) if accounts::is_unmigrated_market(&account.data) => {
+                        eprintln!("{}: not migrated yet", key);
+                        continue;
+                    }
+                    Err(err) => {
+                        eprintln!("{}: could not decode: {}", key, err);
+                        continue;
+                    }
+                };
+                if creator.map_or(false, |creator| creator != market.creator)
+                    || mint.map_or(false, |mint| mint != market.token_mint)
+                {
+                    continue;
+                }
+                display::market_summary(&key, &market);
+            }
+        }
+        MarketCommand::Show { market } => {
+            let data = sender.rpc().get_account_data(&market)?;
+            display::market(&market, &accounts::market(&data)?);
+        }
+        MarketCommand::Resolve {
+            market,
+            outcome,
+            resolver,
+        } => {
+            let resolver = sender.signer(resolver)?;
+            let ix = escrow::update_state(
+                &market,
+                &resolver,
+                UpdateStateParams {
+                    outcome: outcome.into(),
+                },
+            );
+            sender.send(&[ix], &resolver)?;
+        }
+        MarketCommand::Finalize { market } => {
+            let data = sender.rpc().get_account_data(&market)?;
+            let info = accounts::market(&data)?;
+            if info.finalized {
+                bail!("market {} is already finalized", market);
+            }
+
+            // Only a market that would finalize at the cluster time can be
+            // finalized by any signer; otherwise `update_state` would try to
+            // set the current outcome and fail on the resolver check.
+            let data = sender.rpc().get_account_data(&sysvar::clock::id())?;
+            let clock: Clock = bincode::deserialize(&data)?;
+            let now = u64::try_from(clock.unix_timestamp)?;
+            if info.status().finalized_outcome(now)?.is_none() {
+                bail!("market {} cannot be finalized yet", market);
+            }
+
+            // The outcome is ignored once the market would finalize, as it
+            // finalizes before the signer is checked.
+            let signer = sender.signer(None)?;
+            let ix = escrow::update_state(
+                &market,
+                &signer,
+                UpdateStateParams {
+                    outcome: info.outcome,
+                },
+            );
+            sender.send(&[ix], &signer)?;
+        }
+        MarketCommand::CollectFees { markets, collector } => {
+            let data = sender.rpc().get_account_data(&pda::global_state())?;
+            let global_state = accounts::global_state(&data)?;
+
+            let mut token_mint = None;
+            let mut collected = Vec::with_capacity(markets.len());
+            for market in markets {
+                let data = sender.rpc().get_account_data(&market)?;
+                let info = accounts::market(&data)?;
+                if *token_mint.get_or_insert(info.token_mint) != info.token_mint {
+                    bail!("market {} has a different token mint", market);
+                }
+                if info.accrued_fees == 0 {
+                    eprintln!("{}: no fees accrued, skipping", market);
+                    continue;
+                }
+                collected.push((market, info.outcome));
+            }
+            if collected.is_empty() {
+                bail!("no fees to collect");
+            }
+
+            let collector = sender.signer(collector)?;
+            let ix = escrow::collect_fees(
+                &collector,
+                &global_state.fee_wallet,
+                &token_mint.unwrap(),
+                &collected,
+            );
+            sender.send(&[ix], &collector)?;
+        }
+        MarketCommand::SettleBond { market } => {
+            let data = sender.rpc().get_account_data(&pda::global_state())?;
+            let global_state = accounts::global_state(&data)?;
+            let data = sender.rpc().get_account_data(&market)?;
+            let info = accounts::market(&data)?;
+            if info.creation_bond == 0 {
+                bail!("market {} has no creation bond", market);
+            }
+
+            let signer = sender.signer(None)?;
+            let ix = escrow::settle_creation_bond(
+                &market,
+                &info.token_mint,
+                &info.creation_bond_payer,
+                &global_state.fee_wallet,
+            );
+            sender.send(&[ix], &signer)?;
+        }
+        MarketCommand::Arbitrate {
+            market,
+            outcome,
+            arbiter,
+        } => {
+            let arbiter = sender.signer(arbiter)?;
+            let ix = escrow::arbitrate(
+                &arbiter,
+                &market,
+                ArbitrateParams {
+                    outcome: outcome.into(),
+                },
+            );
+            sender.send(&[ix], &arbiter)?;
+        }
+        MarketCommand::SettleResolverBond { market } => {
+            let data = sender.rpc().get_account_data(&market)?;
+            let info = accounts::market(&data)?;
+            if info.resolver_bond_outstanding() == 0 {
+                bail!("market {} has no resolver bond", market);
+            }
+            if info.resolver_slashed {
+                bail!("market {} slashed its resolver bond", market);
+            }
+
+            let signer = sender.signer(None)?;
+            let ix =
+                escrow::settle_resolver_bond(&market, &info.token_mint, &info.resolver_bond_payer);
+            sender.send(&[ix], &signer)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+
+    // Test that the argument definitions are consistent.
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    // Test that unsigned transaction options require `--unsigned`.
+    #[test]
+    fn test_unsigned_options() {
+        let fee_payer = Pubkey::new_unique().to_string();
+
+        assert!(
+            Cli::try_parse_from(["hh-admin", "--fee-payer", &fee_payer, "global", "show"]).is_err()
+        );
+        assert!(Cli::try_parse_from([
+            "hh-admin",
+            "--unsigned",
+            "--fee-payer",
+            &fee_payer,
+            "global",
+            "show"
+        ])
+        .is_ok());
+    }
+}
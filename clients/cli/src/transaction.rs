@@ -0,0 +1,109 @@
+//! Signing and sending of transactions, or printing them unsigned.
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use solana_sdk::transaction::Transaction;
+
+/// Sends transactions signed by a keypair file, or prints them unsigned for
+/// offline or multisig signing.
+pub struct Sender {
+    rpc: RpcClient,
+    keypair: Option<PathBuf>,
+    unsigned: bool,
+    fee_payer: Option<Pubkey>,
+    blockhash: Option<Hash>,
+}
+
+impl Sender {
+    pub fn new(
+        rpc: RpcClient,
+        keypair: Option<PathBuf>,
+        unsigned: bool,
+        fee_payer: Option<Pubkey>,
+        blockhash: Option<Hash>,
+    ) -> Self {
+        Sender {
+            rpc,
+            keypair,
+            unsigned,
+            fee_payer,
+            blockhash,
+        }
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// Reads the keypair file, defaulting to the Solana CLI keypair.
+    fn keypair(&self) -> Result<Keypair> {
+        let path = match &self.keypair {
+            Some(path) => path.clone(),
+            None => {
+                let home = std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set"))?;
+                PathBuf::from(home).join(".config/solana/id.json")
+            }
+        };
+
+        read_keypair_file(&path)
+            .map_err(|err| anyhow!("could not read keypair {}: {}", path.display(), err))
+    }
+
+    /// Returns the given signer, defaulting to the keypair.
+    pub fn signer(&self, signer: Option<Pubkey>) -> Result<Pubkey> {
+        match signer {
+            Some(signer) => Ok(signer),
+            None => Ok(self.keypair()?.pubkey()),
+        }
+    }
+
+    /// Returns the fee payer, defaulting to the keypair.
+    pub fn fee_payer(&self) -> Result<Pubkey> {
+        self.signer(self.fee_payer)
+    }
+
+    /// Sends the instructions in a transaction signed by the keypair, or
+    /// prints the transaction unsigned. The keypair must be `signer` when the
+    /// transaction is sent.
+    pub fn send(&self, instructions: &[Instruction], signer: &Pubkey) -> Result<()> {
+        if self.unsigned {
+            let blockhash = match self.blockhash {
+                Some(blockhash) => blockhash,
+                None => self.rpc.get_latest_blockhash()?,
+            };
+            let mut message = Message::new(instructions, Some(&self.fee_payer()?));
+            message.recent_blockhash = blockhash;
+
+            let tx = Transaction::new_unsigned(message);
+            println!("{}", bs58::encode(bincode::serialize(&tx)?).into_string());
+            return Ok(());
+        }
+
+        let keypair = self.keypair()?;
+        if keypair.pubkey() != *signer {
+            bail!(
+                "{} must sign, but the keypair is {}; use --unsigned to sign elsewhere",
+                signer,
+                keypair.pubkey()
+            );
+        }
+
+        let tx = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            self.rpc.get_latest_blockhash()?,
+        );
+        let signature = self.rpc.send_and_confirm_transaction(&tx)?;
+        println!("{}", signature);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+//! Decoders for the accounts of the programs.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use hh_escrow::state::{
+    GlobalState, Market, MarketCounter, MarketRegistry, MarketRegistryPage, MarketTemplate,
+    MarketV0, MintPolicy, UserPosition,
+};
+use hyperspace_resolver::state::NftFloor;
+
+/// Decodes the data of an account, checking its discriminator.
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Decodes a [`Market`].
+pub fn market(data: &[u8]) -> Result<Market> {
+    decode(data)
+}
+
+/// Checks whether the data is a market in the layout used before versioning,
+/// which only decodes once migrated.
+pub fn is_unmigrated_market(data: &[u8]) -> bool {
+    data.len() == 8 + MarketV0::LEN && data[..8] == Market::discriminator()
+}
+
+/// Decodes a [`UserPosition`].
+pub fn user_position(data: &[u8]) -> Result<UserPosition> {
+    decode(data)
+}
+
+/// Decodes the [`GlobalState`].
+pub fn global_state(data: &[u8]) -> Result<GlobalState> {
+    decode(data)
+}
+
+/// Decodes a [`MarketCounter`].
+pub fn market_counter(data: &[u8]) -> Result<MarketCounter> {
+    decode(data)
+}
+
+/// Decodes a [`MarketTemplate`].
+pub fn market_template(data: &[u8]) -> Result<MarketTemplate> {
+    decode(data)
+}
+
+/// Decodes a [`MarketRegistry`].
+pub fn market_registry(data: &[u8]) -> Result<MarketRegistry> {
+    decode(data)
+}
+
+/// Decodes a [`MarketRegistryPage`].
+pub fn market_registry_page(data: &[u8]) -> Result<MarketRegistryPage> {
+    decode(data)
+}
+
+/// Decodes a [`MintPolicy`].
+pub fn mint_policy(data: &[u8]) -> Result<MintPolicy> {
+    decode(data)
+}
+
+/// Decodes an [`NftFloor`] resolver.
+pub fn nft_floor(data: &[u8]) -> Result<NftFloor> {
+    decode(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that an account round trips, and that the discriminator of another
+    // account type is refused.
+    #[test]
+    fn test_decode() {
+        let counter = MarketCounter {
+            next_nonce: 5,
+            ..MarketCounter::default()
+        };
+        let mut data = Vec::new();
+        counter.try_serialize(&mut data).unwrap();
+
+        assert_eq!(market_counter(&data).unwrap().next_nonce, 5);
+        assert!(market_registry(&data).is_err());
+    }
+
+    // Test that markets in the unversioned layout are told apart from data
+    // that decodes as neither layout.
+    #[test]
+    fn test_is_unmigrated_market() {
+        let mut data = Market::discriminator().to_vec();
+        data.resize(8 + MarketV0::LEN, 0);
+        assert!(market(&data).is_err());
+        assert!(is_unmigrated_market(&data));
+
+        data.truncate(data.len() - 1);
+        assert!(!is_unmigrated_market(&data));
+
+        let mut data = Vec::new();
+        Market::default().try_serialize(&mut data).unwrap();
+        assert!(market(&data).is_ok());
+        assert!(!is_unmigrated_market(&data));
+    }
+}
@@ -0,0 +1,760 @@
+//! Builders for the instructions of the `hh_escrow` program.
+//!
+//! Every builder derives the PDAs of the instruction, so only the accounts
+//! chosen by the caller have to be provided.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{system_program, InstructionData};
+use anchor_spl::associated_token::{self, get_associated_token_address};
+use anchor_spl::token;
+
+use hh_escrow::instructions::{
+    ArbitrateParams, DepositParams, InitializeGlobalStateParams, InitializeMarketParams,
+    InitializeMarketTemplateParams, MigrateMarketParams, SetAllowlistRootParams,
+    SetGlobalStateParams, SetMarketPolicyParams, SetMintPolicyParams, SetResolverParams,
+    UpdateStateParams,
+};
+use hh_escrow::state::Outcome;
+
+use crate::pda;
+
+/// The tail pages of the registries a new market is appended to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RegistryPages {
+    /// The tail page of the creator's registry.
+    pub creator: u32,
+    /// The tail page of the token mint's registry.
+    pub mint: u32,
+}
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: hh_escrow::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// The registry accounts of a new market, in the order expected by the
+/// market creation instructions.
+fn registry_accounts(
+    creator: &Pubkey,
+    token_mint: &Pubkey,
+    pages: RegistryPages,
+) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
+    let creator_registry = pda::creator_registry(creator);
+    let mint_registry = pda::mint_registry(token_mint);
+
+    (
+        creator_registry,
+        pda::registry_page(&creator_registry, pages.creator),
+        mint_registry,
+        pda::registry_page(&mint_registry, pages.mint),
+    )
+}
+
+/// Initializes the global state, signed by the upgrade authority of the
+/// program.
+pub fn initialize_global_state(
+    authority: &Pubkey,
+    payer: &Pubkey,
+    global_state_owner: &Pubkey,
+    fee_wallet: &Pubkey,
+    params: InitializeGlobalStateParams,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::InitializeGlobalState {
+            global_state: pda::global_state(),
+            authority: *authority,
+            global_state_owner: *global_state_owner,
+            fee_wallet: *fee_wallet,
+            escrow_program: hh_escrow::ID,
+            program_data: pda::program_data(),
+            system_program: system_program::ID,
+            payer: *payer,
+        },
+        hh_escrow::instruction::InitializeGlobalState { params },
+    )
+}
+
+/// Updates the global state, signed by its owner.
+pub fn set_global_state(owner: &Pubkey, params: SetGlobalStateParams) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SetGlobalState {
+            global_state: pda::global_state(),
+            owner: *owner,
+        },
+        hh_escrow::instruction::SetGlobalState { params },
+    )
+}
+
+/// Sets the limits on the parameters of new markets, signed by the owner of
+/// the global state.
+pub fn set_market_policy(owner: &Pubkey, params: SetMarketPolicyParams) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SetMarketPolicy {
+            global_state: pda::global_state(),
+            owner: *owner,
+        },
+        hh_escrow::instruction::SetMarketPolicy { params },
+    )
+}
+
+/// Sets the limits on new markets denominated in `token_mint`, signed by the
+/// owner of the global state.
+pub fn set_mint_policy(
+    owner: &Pubkey,
+    payer: &Pubkey,
+    token_mint: &Pubkey,
+    params: SetMintPolicyParams,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SetMintPolicy {
+            global_state: pda::global_state(),
+            owner: *owner,
+            token_mint: *token_mint,
+            mint_policy: pda::mint_policy(token_mint),
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        hh_escrow::instruction::SetMintPolicy { params },
+    )
+}
+
+/// Initializes a market at the address of the `market` keypair.
+pub fn initialize_market(
+    market: &Pubkey,
+    creator: &Pubkey,
+    token_mint: &Pubkey,
+    pages: RegistryPages,
+    params: InitializeMarketParams,
+) -> Instruction {
+    let (creator_registry, creator_registry_page, mint_registry, mint_registry_page) =
+        registry_accounts(creator, token_mint, pages);
+
+    instruction(
+        hh_escrow::accounts::InitializeMarket {
+            global_state: pda::global_state(),
+            market: *market,
+            authority: pda::authority(market),
+            creator: *creator,
+            token_mint: *token_mint,
+            mint_policy: pda::mint_policy(token_mint),
+            yes_token_account: pda::yes_token_account(market),
+            no_token_account: pda::no_token_account(market),
+            bond_token_account: pda::bond_token_account(market),
+            creator_token_account: get_associated_token_address(creator, token_mint),
+            creator_registry,
+            creator_registry_page,
+            mint_registry,
+            mint_registry_page,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+        },
+        hh_escrow::instruction::InitializeMarket { params },
+    )
+}
+
+/// Initializes a market at the PDA of the creator's next nonce.
+pub fn initialize_market_pda(
+    creator: &Pubkey,
+    nonce: u64,
+    token_mint: &Pubkey,
+    pages: RegistryPages,
+    params: InitializeMarketParams,
+) -> Instruction {
+    let market = pda::market(creator, nonce);
+    let (creator_registry, creator_registry_page, mint_registry, mint_registry_page) =
+        registry_accounts(creator, token_mint, pages);
+
+    instruction(
+        hh_escrow::accounts::InitializeMarketPda {
+            global_state: pda::global_state(),
+            market_counter: pda::market_counter(creator),
+            market,
+            authority: pda::authority(&market),
+            creator: *creator,
+            token_mint: *token_mint,
+            mint_policy: pda::mint_policy(token_mint),
+            yes_token_account: pda::yes_token_account(&market),
+            no_token_account: pda::no_token_account(&market),
+            bond_token_account: pda::bond_token_account(&market),
+            creator_token_account: get_associated_token_address(creator, token_mint),
+            creator_registry,
+            creator_registry_page,
+            mint_registry,
+            mint_registry_page,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+        },
+        hh_escrow::instruction::InitializeMarketPda { params },
+    )
+}
+
+/// Initializes a market template at the address of the `template` keypair.
+pub fn initialize_market_template(
+    template: &Pubkey,
+    creator: &Pubkey,
+    token_mint: &Pubkey,
+    params: InitializeMarketTemplateParams,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::InitializeMarketTemplate {
+            global_state: pda::global_state(),
+            template: *template,
+            creator: *creator,
+            token_mint: *token_mint,
+            mint_policy: pda::mint_policy(token_mint),
+            system_program: system_program::ID,
+        },
+        hh_escrow::instruction::InitializeMarketTemplate { params },
+    )
+}
+
+/// Spawns the market at `index` from a template, where `index` is the number
+/// of markets spawned so far.
+///
+/// If the template has a resolver program, `resolver` holds the resolver
+/// account of the new market and the resolver program. The resolver account
+/// must be funded beforehand, as the payer is not forwarded to the resolver
+/// program.
+pub fn spawn_market_from_template(
+    template: &Pubkey,
+    index: u64,
+    creator: &Pubkey,
+    token_mint: &Pubkey,
+    payer: &Pubkey,
+    pages: RegistryPages,
+    resolver: Option<(Pubkey, Pubkey)>,
+) -> Instruction {
+    let market = pda::market(template, index);
+    let (creator_registry, creator_registry_page, mint_registry, mint_registry_page) =
+        registry_accounts(creator, token_mint, pages);
+
+    let mut ix = instruction(
+        hh_escrow::accounts::SpawnMarketFromTemplate {
+            global_state: pda::global_state(),
+            template: *template,
+            market,
+            authority: pda::authority(&market),
+            spawn_authority: pda::spawn_authority(&market),
+            payer: *payer,
+            token_mint: *token_mint,
+            mint_policy: pda::mint_policy(token_mint),
+            yes_token_account: pda::yes_token_account(&market),
+            no_token_account: pda::no_token_account(&market),
+            bond_token_account: pda::bond_token_account(&market),
+            payer_token_account: get_associated_token_address(payer, token_mint),
+            creator_registry,
+            creator_registry_page,
+            mint_registry,
+            mint_registry_page,
+            system_program: system_program::ID,
+            token_program: token::ID,
+            rent: sysvar::rent::ID,
+        },
+        hh_escrow::instruction::SpawnMarketFromTemplate,
+    );
+    if let Some((resolver, resolver_program)) = resolver {
+        ix.accounts.push(AccountMeta::new(resolver, false));
+        ix.accounts
+            .push(AccountMeta::new_readonly(resolver_program, false));
+    }
+
+    ix
+}
+
+/// Closes a finalized market, given the registry pages holding it and the fee
+/// wallet receiving the tokens left in its escrow accounts.
+pub fn close_market(
+    market: &Pubkey,
+    creator: &Pubkey,
+    token_mint: &Pubkey,
+    fee_wallet: &Pubkey,
+    pages: RegistryPages,
+) -> Instruction {
+    let (creator_registry, creator_registry_page, mint_registry, mint_registry_page) =
+        registry_accounts(creator, token_mint, pages);
+
+    instruction(
+        hh_escrow::accounts::CloseMarket {
+            market: *market,
+            authority: pda::authority(market),
+            creator: *creator,
+            yes_token_account: pda::yes_token_account(market),
+            no_token_account: pda::no_token_account(market),
+            bond_token_account: pda::bond_token_account(market),
+            resolver_bond_token_account: pda::resolver_bond_token_account(market),
+            creator_registry,
+            creator_registry_page,
+            mint_registry,
+            mint_registry_page,
+            global_state: pda::global_state(),
+            fee_account: get_associated_token_address(fee_wallet, token_mint),
+            token_program: token::ID,
+        },
+        hh_escrow::instruction::CloseMarket,
+    )
+}
+
+/// Closes the paid out position of a user in a finalized market.
+pub fn close_user_position(user: &Pubkey, market: &Pubkey) -> Instruction {
+    instruction(
+        hh_escrow::accounts::CloseUserPosition {
+            user: *user,
+            market: *market,
+            user_position: pda::user_position(user, market),
+        },
+        hh_escrow::instruction::CloseUserPosition,
+    )
+}
+
+/// Initializes the position of a user in a market.
+pub fn initialize_user_position(user: &Pubkey, payer: &Pubkey, market: &Pubkey) -> Instruction {
+    instruction(
+        hh_escrow::accounts::InitializeUserPosition {
+            user: *user,
+            payer: *payer,
+            market: *market,
+            user_position: pda::user_position(user, market),
+            system_program: system_program::ID,
+        },
+        hh_escrow::instruction::InitializeUserPosition,
+    )
+}
+
+/// Deposits tokens from `user_token_account` into a market.
+pub fn deposit(
+    user: &Pubkey,
+    market: &Pubkey,
+    user_token_account: &Pubkey,
+    params: DepositParams,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::Deposit {
+            user: *user,
+            market: *market,
+            yes_token_account: pda::yes_token_account(market),
+            no_token_account: pda::no_token_account(market),
+            user_token_account: *user_token_account,
+            user_position: pda::user_position(user, market),
+            token_program: token::ID,
+        },
+        hh_escrow::instruction::Deposit { params },
+    )
+}
+
+/// Withdraws the deposits of a user from an invalid market, into the
+/// associated token account of `destination`.
+pub fn withdraw(
+    user: &Pubkey,
+    market: &Pubkey,
+    token_mint: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::Withdraw {
+            user: *user,
+            yes_token_account: pda::yes_token_account(market),
+            no_token_account: pda::no_token_account(market),
+            destination: *destination,
+            user_token_account: get_associated_token_address(destination, token_mint),
+            token_mint: *token_mint,
+            authority: pda::authority(market),
+            market: *market,
+            user_position: pda::user_position(user, market),
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+        },
+        hh_escrow::instruction::Withdraw,
+    )
+}
+
+/// Claims the winnings of a user from a resolved market, into the associated
+/// token account of `destination`.
+///
+/// `referrer` must be the referrer recorded on the position of the user, if
+/// any.
+pub fn claim(
+    user: &Pubkey,
+    market: &Pubkey,
+    token_mint: &Pubkey,
+    destination: &Pubkey,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let user_token_account = get_associated_token_address(destination, token_mint);
+
+    instruction(
+        hh_escrow::accounts::Claim {
+            global_state: pda::global_state(),
+            destination: *destination,
+            user_token_account,
+            referrer_token_account: referrer.map_or(user_token_account, |referrer| {
+                get_associated_token_address(referrer, token_mint)
+            }),
+            token_mint: *token_mint,
+            yes_token_account: pda::yes_token_account(market),
+            no_token_account: pda::no_token_account(market),
+            user_position: pda::user_position(user, market),
+            market: *market,
+            authority: pda::authority(market),
+            token_program: token::ID,
+            associated_token_program: associated_token::ID,
+            system_program: system_program::ID,
+            rent: sysvar::rent::ID,
+            user: *user,
+        },
+        hh_escrow::instruction::Claim,
+    )
+}
+
+/// Settles the position of a user on their behalf, paying the tip to
+/// `cranker_token_account`.
+///
+/// `referrer` must be the referrer recorded on the position of the user, if
+/// any.
+pub fn settle_position(
+    cranker: &Pubkey,
+    cranker_token_account: &Pubkey,
+    user: &Pubkey,
+    market: &Pubkey,
+    token_mint: &Pubkey,
+    referrer: Option<&Pubkey>,
+) -> Instruction {
+    let user_token_account = get_associated_token_address(user, token_mint);
+
+    instruction(
+        hh_escrow::accounts::SettlePosition {
+            global_state: pda::global_state(),
+            user: *user,
+            user_token_account,
+            cranker_token_account: *cranker_token_account,
+            referrer_token_account: referrer.map_or(user_token_account, |referrer| {
+                get_associated_token_address(referrer, token_mint)
+            }),
+            yes_token_account: pda::yes_token_account(market),
+            no_token_account: pda::no_token_account(market),
+            user_position: pda::user_position(user, market),
+            market: *market,
+            authority: pda::authority(market),
+            token_program: token::ID,
+            cranker: *cranker,
+        },
+        hh_escrow::instruction::SettlePosition,
+    )
+}
+
+/// Settles the creation bond of a finalized market, refunding it to
+/// `bond_payer` or forfeiting it to `fee_wallet`.
+pub fn settle_creation_bond(
+    market: &Pubkey,
+    token_mint: &Pubkey,
+    bond_payer: &Pubkey,
+    fee_wallet: &Pubkey,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SettleCreationBond {
+            global_state: pda::global_state(),
+            market: *market,
+            authority: pda::authority(market),
+            bond_token_account: pda::bond_token_account(market),
+            bond_payer: *bond_payer,
+            bond_payer_token_account: get_associated_token_address(bond_payer, token_mint),
+            fee_wallet: *fee_wallet,
+            fee_account: get_associated_token_address(fee_wallet, token_mint),
+            token_program: token::ID,
+        },
+        hh_escrow::instruction::SettleCreationBond,
+    )
+}
+
+/// Collects the fees accrued on markets sharing `token_mint`, signed by the
+/// fee wallet or the global state owner.
+///
+/// Each market is given with the outcome it resolved to, which determines the
+/// escrow holding its fees.
+pub fn collect_fees(
+    collector: &Pubkey,
+    fee_wallet: &Pubkey,
+    token_mint: &Pubkey,
+    markets: &[(Pubkey, Outcome)],
+) -> Instruction {
+    let mut ix = instruction(
+        hh_escrow::accounts::CollectFees {
+            global_state: pda::global_state(),
+            fee_wallet: *fee_wallet,
+            fee_account: get_associated_token_address(fee_wallet, token_mint),
+            token_mint: *token_mint,
+            collector: *collector,
+            token_program: token::ID,
+        },
+        hh_escrow::instruction::CollectFees,
+    );
+    for (market, outcome) in markets {
+        let escrow = match outcome {
+            Outcome::No => pda::yes_token_account(market),
+            _ => pda::no_token_account(market),
+        };
+        ix.accounts.push(AccountMeta::new(*market, false));
+        ix.accounts.push(AccountMeta::new(escrow, false));
+        ix.accounts
+            .push(AccountMeta::new_readonly(pda::authority(market), false));
+    }
+
+    ix
+}
+
+/// Updates the outcome of a market, signed by its resolver.
+pub fn update_state(market: &Pubkey, resolver: &Pubkey, params: UpdateStateParams) -> Instruction {
+    instruction(
+        hh_escrow::accounts::UpdateState {
+            market: *market,
+            resolver: *resolver,
+        },
+        hh_escrow::instruction::UpdateState { params },
+    )
+}
+
+/// Acknowledges a market, signed by its resolver.
+///
+/// If the market requires a resolver bond, the [`resolver_bond_accounts`]
+/// are appended to the instruction.
+pub fn resolver_acknowledge(market: &Pubkey, resolver: &Pubkey) -> Instruction {
+    instruction(
+        hh_escrow::accounts::ResolverAcknowledge {
+            market: *market,
+            resolver: *resolver,
+        },
+        hh_escrow::instruction::ResolverAcknowledge,
+    )
+}
+
+/// The accounts posting the resolver bond of a market when it is
+/// acknowledged, with `bond_payer` signing and paying the bond from their
+/// associated token account.
+///
+/// These are appended to [`resolver_acknowledge`], or to the instruction of
+/// a resolver program acknowledging the market.
+pub fn resolver_bond_accounts(
+    market: &Pubkey,
+    bond_payer: &Pubkey,
+    token_mint: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(pda::authority(market), false),
+        AccountMeta::new_readonly(*token_mint, false),
+        AccountMeta::new(pda::resolver_bond_token_account(market), false),
+        AccountMeta::new(*bond_payer, true),
+        AccountMeta::new(get_associated_token_address(bond_payer, token_mint), false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(token::ID, false),
+        AccountMeta::new_readonly(sysvar::rent::ID, false),
+    ]
+}
+
+/// Disputes the outcome of a market, signed by a user with deposits in it,
+/// who pays the dispute bond.
+pub fn dispute_outcome(user: &Pubkey, market: &Pubkey) -> Instruction {
+    instruction(
+        hh_escrow::accounts::DisputeOutcome {
+            global_state: pda::global_state(),
+            market: *market,
+            user_position: pda::user_position(user, market),
+            user: *user,
+            system_program: system_program::ID,
+        },
+        hh_escrow::instruction::DisputeOutcome,
+    )
+}
+
+/// Rules on the disputed outcome of a market, signed by the arbiter.
+pub fn arbitrate(arbiter: &Pubkey, market: &Pubkey, params: ArbitrateParams) -> Instruction {
+    instruction(
+        hh_escrow::accounts::Arbitrate {
+            global_state: pda::global_state(),
+            market: *market,
+            arbiter: *arbiter,
+        },
+        hh_escrow::instruction::Arbitrate { params },
+    )
+}
+
+/// Refunds the resolver bond of a finalized market to `bond_payer`.
+pub fn settle_resolver_bond(
+    market: &Pubkey,
+    token_mint: &Pubkey,
+    bond_payer: &Pubkey,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SettleResolverBond {
+            market: *market,
+            authority: pda::authority(market),
+            resolver_bond_token_account: pda::resolver_bond_token_account(market),
+            bond_payer_token_account: get_associated_token_address(bond_payer, token_mint),
+            token_program: token::ID,
+        },
+        hh_escrow::instruction::SettleResolverBond,
+    )
+}
+
+/// Pays a disputing user their share of the slashed resolver bond of a
+/// market.
+pub fn claim_slashed_bond(user: &Pubkey, market: &Pubkey, token_mint: &Pubkey) -> Instruction {
+    instruction(
+        hh_escrow::accounts::ClaimSlashedBond {
+            market: *market,
+            authority: pda::authority(market),
+            resolver_bond_token_account: pda::resolver_bond_token_account(market),
+            user_position: pda::user_position(user, market),
+            user: *user,
+            user_token_account: get_associated_token_address(user, token_mint),
+            token_program: token::ID,
+        },
+        hh_escrow::instruction::ClaimSlashedBond,
+    )
+}
+
+/// Settles the dispute bond of `user` in a finalized market, refunding it to
+/// the user or forfeiting it to `fee_wallet` if the arbiter upheld the outcome.
+pub fn settle_dispute_bond(user: &Pubkey, market: &Pubkey, fee_wallet: &Pubkey) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SettleDisputeBond {
+            global_state: pda::global_state(),
+            market: *market,
+            user_position: pda::user_position(user, market),
+            user: *user,
+            fee_wallet: *fee_wallet,
+        },
+        hh_escrow::instruction::SettleDisputeBond,
+    )
+}
+
+/// Sets the allowlist root of a market, signed by its creator.
+pub fn set_allowlist_root(
+    market: &Pubkey,
+    creator: &Pubkey,
+    params: SetAllowlistRootParams,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SetAllowlistRoot {
+            market: *market,
+            creator: *creator,
+        },
+        hh_escrow::instruction::SetAllowlistRoot { params },
+    )
+}
+
+/// Replaces the resolver of a market, signed by its creator.
+pub fn set_resolver(market: &Pubkey, creator: &Pubkey, params: SetResolverParams) -> Instruction {
+    instruction(
+        hh_escrow::accounts::SetResolver {
+            market: *market,
+            creator_position: pda::user_position(creator, market),
+            creator: *creator,
+        },
+        hh_escrow::instruction::SetResolver { params },
+    )
+}
+
+/// Migrates a market with `positions` unversioned user positions to the
+/// current account layout.
+pub fn migrate_market(
+    market: &Pubkey,
+    owner: &Pubkey,
+    payer: &Pubkey,
+    positions: u64,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::MigrateMarket {
+            market: *market,
+            global_state: pda::global_state(),
+            owner: *owner,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        hh_escrow::instruction::MigrateMarket {
+            params: MigrateMarketParams { positions },
+        },
+    )
+}
+
+/// Migrates a user position in `market` to the current account layout.
+pub fn migrate_user_position(
+    user_position: &Pubkey,
+    market: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    instruction(
+        hh_escrow::accounts::MigrateUserPosition {
+            user_position: *user_position,
+            market: *market,
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        hh_escrow::instruction::MigrateUserPosition,
+    )
+}
+
+/// Migrates the global state to the current account layout.
+pub fn migrate_global_state(payer: &Pubkey) -> Instruction {
+    instruction(
+        hh_escrow::accounts::MigrateGlobalState {
+            global_state: pda::global_state(),
+            payer: *payer,
+            system_program: system_program::ID,
+        },
+        hh_escrow::instruction::MigrateGlobalState,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::solana_program::hash::hash;
+
+    use super::*;
+
+    // Test that the signers of a withdrawal are marked as such, and that the
+    // data starts with the instruction discriminator.
+    #[test]
+    fn test_withdraw() {
+        let user = Pubkey::new_unique();
+        let market = Pubkey::new_unique();
+        let ix = withdraw(&user, &market, &Pubkey::new_unique(), &user);
+
+        assert_eq!(ix.program_id, hh_escrow::ID);
+        assert_eq!(ix.accounts[0], AccountMeta::new(user, true));
+        assert_eq!(ix.accounts[8].pubkey, pda::user_position(&user, &market));
+        assert_eq!(ix.data, hash(b"global:withdraw").to_bytes()[..8]);
+    }
+
+    // Test that the resolver accounts of a spawned market are appended after
+    // the instruction accounts.
+    #[test]
+    fn test_spawn_market_from_template() {
+        let template = Pubkey::new_unique();
+        let resolver = Pubkey::new_unique();
+        let ix = spawn_market_from_template(
+            &template,
+            3,
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            &Pubkey::new_unique(),
+            RegistryPages::default(),
+            Some((resolver, hyperspace_resolver::ID)),
+        );
+
+        assert_eq!(ix.accounts[2].pubkey, pda::market(&template, 3));
+        assert_eq!(ix.accounts.len(), 21);
+        assert_eq!(ix.accounts[19], AccountMeta::new(resolver, false));
+        assert_eq!(
+            ix.accounts[20],
+            AccountMeta::new_readonly(hyperspace_resolver::ID, false)
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Client library for building instructions to, and decoding accounts of, the
+//! `hh_escrow` and `hyperspace_resolver` programs.
+
+pub mod accounts;
+pub mod escrow;
+pub mod pda;
+pub mod resolver;
+
+pub use hh_escrow;
+pub use hyperspace_resolver;
@@ -0,0 +1,136 @@
+//! Derivation of the program derived addresses used by the programs.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+
+use hh_escrow::state::{CREATOR_REGISTRY_SEED, MINT_REGISTRY_SEED};
+use hyperspace_resolver::state::NFT_FLOOR_SEED;
+
+fn find(seeds: &[&[u8]], program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(seeds, program_id).0
+}
+
+/// The global state of the escrow program.
+pub fn global_state() -> Pubkey {
+    find(&[b"global"], &hh_escrow::ID)
+}
+
+/// The program data account of the escrow program.
+pub fn program_data() -> Pubkey {
+    find(&[hh_escrow::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+/// The authority owning the escrow accounts of a market.
+pub fn authority(market: &Pubkey) -> Pubkey {
+    find(&[b"authority", market.as_ref()], &hh_escrow::ID)
+}
+
+/// The signer proving to a resolver program that a market is spawned from a
+/// template.
+pub fn spawn_authority(market: &Pubkey) -> Pubkey {
+    find(&[b"spawn", market.as_ref()], &hh_escrow::ID)
+}
+
+/// The escrow for tokens on the yes side of a market.
+pub fn yes_token_account(market: &Pubkey) -> Pubkey {
+    find(&[b"yes", market.as_ref()], &hh_escrow::ID)
+}
+
+/// The escrow for tokens on the no side of a market.
+pub fn no_token_account(market: &Pubkey) -> Pubkey {
+    find(&[b"no", market.as_ref()], &hh_escrow::ID)
+}
+
+/// The escrow for the creation bond of a market.
+pub fn bond_token_account(market: &Pubkey) -> Pubkey {
+    find(&[b"bond", market.as_ref()], &hh_escrow::ID)
+}
+
+/// The escrow for the resolver bond of a market.
+pub fn resolver_bond_token_account(market: &Pubkey) -> Pubkey {
+    find(&[b"resolver_bond", market.as_ref()], &hh_escrow::ID)
+}
+
+/// The position of a user in a market.
+pub fn user_position(user: &Pubkey, market: &Pubkey) -> Pubkey {
+    find(&[b"user", user.as_ref(), market.as_ref()], &hh_escrow::ID)
+}
+
+/// The policy of a token mint.
+pub fn mint_policy(token_mint: &Pubkey) -> Pubkey {
+    find(&[b"mint_policy", token_mint.as_ref()], &hh_escrow::ID)
+}
+
+/// The counter handing out the nonces of a creator's PDA markets.
+pub fn market_counter(creator: &Pubkey) -> Pubkey {
+    find(&[b"counter", creator.as_ref()], &hh_escrow::ID)
+}
+
+/// The market created at a PDA, where `key` is either the creator and its
+/// nonce, or a market template and the index of the spawned market.
+pub fn market(key: &Pubkey, nonce: u64) -> Pubkey {
+    find(
+        &[b"market", key.as_ref(), &nonce.to_le_bytes()],
+        &hh_escrow::ID,
+    )
+}
+
+/// The registry of the markets of a creator.
+pub fn creator_registry(creator: &Pubkey) -> Pubkey {
+    find(
+        &[b"registry", CREATOR_REGISTRY_SEED, creator.as_ref()],
+        &hh_escrow::ID,
+    )
+}
+
+/// The registry of the markets denominated in a token mint.
+pub fn mint_registry(token_mint: &Pubkey) -> Pubkey {
+    find(
+        &[b"registry", MINT_REGISTRY_SEED, token_mint.as_ref()],
+        &hh_escrow::ID,
+    )
+}
+
+/// A page of a market registry.
+pub fn registry_page(registry: &Pubkey, page: u32) -> Pubkey {
+    find(
+        &[b"registry_page", registry.as_ref(), &page.to_le_bytes()],
+        &hh_escrow::ID,
+    )
+}
+
+/// The NFT floor resolver of a market.
+pub fn nft_floor(market: &Pubkey) -> Pubkey {
+    find(&[NFT_FLOOR_SEED, market.as_ref()], &hyperspace_resolver::ID)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test that a market spawned from a template and a creator's PDA market
+    // share the same derivation.
+    #[test]
+    fn test_market_derivation() {
+        let key = Pubkey::new_unique();
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"market", key.as_ref(), &7u64.to_le_bytes()],
+            &hh_escrow::ID,
+        );
+
+        assert_eq!(market(&key, 7), expected);
+        assert_ne!(market(&key, 7), market(&key, 8));
+    }
+
+    // Test that the creator and mint registries of the same key differ.
+    #[test]
+    fn test_registry_derivation() {
+        let key = Pubkey::new_unique();
+
+        assert_ne!(creator_registry(&key), mint_registry(&key));
+        assert_ne!(
+            registry_page(&creator_registry(&key), 0),
+            registry_page(&creator_registry(&key), 1)
+        );
+    }
+}
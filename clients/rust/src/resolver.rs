@@ -0,0 +1,126 @@
+//! Builders for the instructions of the `hyperspace_resolver` program.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_instruction;
+use anchor_lang::{system_program, InstructionData};
+
+use hyperspace_resolver::instructions::{InitializeNftFloorParams, ResolveNftFloorParams};
+use hyperspace_resolver::state::NftFloor;
+
+use crate::pda;
+
+fn instruction(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: hyperspace_resolver::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+/// Initializes the NFT floor resolver of a market, signed by the market
+/// creator.
+///
+/// If the market requires a resolver bond, the
+/// [`resolver_bond_accounts`](crate::escrow::resolver_bond_accounts) are
+/// appended to the instruction.
+pub fn initialize_nft_floor(
+    market: &Pubkey,
+    creator: &Pubkey,
+    params: InitializeNftFloorParams,
+) -> Instruction {
+    instruction(
+        hyperspace_resolver::accounts::InitializeNftFloor {
+            resolver: pda::nft_floor(market),
+            market: *market,
+            creator: *creator,
+            escrow_program: hh_escrow::ID,
+            system_program: system_program::ID,
+        },
+        hyperspace_resolver::instruction::InitializeNftFloor { params },
+    )
+}
+
+/// Initializes the NFT floor resolver of a market spawned from a template.
+///
+/// This instruction is only invoked by the escrow program, so clients
+/// normally only need its data, see [`templated_nft_floor_data`].
+pub fn initialize_templated_nft_floor(
+    market: &Pubkey,
+    params: InitializeNftFloorParams,
+) -> Instruction {
+    instruction(
+        hyperspace_resolver::accounts::InitializeTemplatedNftFloor {
+            resolver: pda::nft_floor(market),
+            market: *market,
+            spawn_authority: pda::spawn_authority(market),
+            system_program: system_program::ID,
+        },
+        hyperspace_resolver::instruction::InitializeTemplatedNftFloor { params },
+    )
+}
+
+/// Funds the NFT floor resolver of a market about to be spawned from a
+/// template, whose resolver is initialized without a payer.
+pub fn fund_templated_nft_floor(market: &Pubkey, payer: &Pubkey, project_id: &str) -> Instruction {
+    let space = 8 + NftFloor::account_size(project_id);
+
+    system_instruction::transfer(
+        payer,
+        &pda::nft_floor(market),
+        Rent::default().minimum_balance(space),
+    )
+}
+
+/// The resolver data of a market template whose markets are resolved by NFT
+/// floor resolvers.
+pub fn templated_nft_floor_data(params: InitializeNftFloorParams) -> Vec<u8> {
+    hyperspace_resolver::instruction::InitializeTemplatedNftFloor { params }.data()
+}
+
+/// Acknowledges an NFT floor resolver, signed by its authority.
+pub fn acknowledge_nft_floor(market: &Pubkey, authority: &Pubkey) -> Instruction {
+    instruction(
+        hyperspace_resolver::accounts::AcknowledgeNftFloor {
+            resolver: pda::nft_floor(market),
+            authority: *authority,
+        },
+        hyperspace_resolver::instruction::AcknowledgeNftFloor,
+    )
+}
+
+/// Acknowledges the NFT floor resolver of a market spawned from a template,
+/// and the market itself, signed by the resolver authority.
+///
+/// If the market requires a resolver bond, the
+/// [`resolver_bond_accounts`](crate::escrow::resolver_bond_accounts) are
+/// appended to the instruction.
+pub fn acknowledge_templated_nft_floor(market: &Pubkey, authority: &Pubkey) -> Instruction {
+    instruction(
+        hyperspace_resolver::accounts::AcknowledgeTemplatedNftFloor {
+            resolver: pda::nft_floor(market),
+            market: *market,
+            authority: *authority,
+            escrow_program: hh_escrow::ID,
+        },
+        hyperspace_resolver::instruction::AcknowledgeTemplatedNftFloor,
+    )
+}
+
+/// Resolves a market from the current NFT floor price, signed by the
+/// resolver authority.
+pub fn resolve_nft_floor(
+    market: &Pubkey,
+    authority: &Pubkey,
+    params: ResolveNftFloorParams,
+) -> Instruction {
+    instruction(
+        hyperspace_resolver::accounts::ResolveNftFloor {
+            resolver: pda::nft_floor(market),
+            market: *market,
+            authority: *authority,
+            escrow_program: hh_escrow::ID,
+        },
+        hyperspace_resolver::instruction::ResolveNftFloor { params },
+    )
+}
@@ -9,5 +9,6 @@ pub mod macros;
 
 pub mod bps;
 pub mod math;
+pub mod merkle;
 pub mod sys;
 pub mod traits;
@@ -0,0 +1,110 @@
+use solana_program::keccak::hashv;
+
+/// Hashes a leaf of a Merkle tree.
+#[must_use]
+#[inline]
+pub fn hash_leaf(leaf: &[u8]) -> [u8; 32] {
+    hashv(&[leaf]).0
+}
+
+/// Hashes two nodes of a Merkle tree.
+///
+/// The nodes are hashed in sorted order, so that proofs do not need to encode
+/// whether each sibling is on the left or the right.
+#[must_use]
+#[inline]
+pub fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[a, b]).0
+    } else {
+        hashv(&[b, a]).0
+    }
+}
+
+/// Verifies that `leaf` is in the Merkle tree with the given `root`, where
+/// `leaf` is the hash of the leaf as returned by [`hash_leaf`].
+#[must_use]
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let node = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(&node, sibling));
+
+    node == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck::TestResult;
+    use quickcheck_macros::quickcheck;
+
+    use super::*;
+
+    /// Builds every level of a Merkle tree, from the leaves to the root.
+    fn build(leaves: &[Vec<u8>]) -> Vec<Vec<[u8; 32]>> {
+        let mut levels = vec![leaves
+            .iter()
+            .map(|leaf| hash_leaf(leaf))
+            .collect::<Vec<_>>()];
+
+        while levels.last().unwrap().len() > 1 {
+            let level = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    [a] => *a,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(level);
+        }
+
+        levels
+    }
+
+    /// Returns the proof for the leaf at the given index.
+    fn proof(levels: &[Vec<[u8; 32]>], mut index: usize) -> Vec<[u8; 32]> {
+        let mut proof = Vec::new();
+
+        for level in &levels[..levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(*sibling);
+            }
+            index /= 2;
+        }
+
+        proof
+    }
+
+    #[quickcheck]
+    fn verifies_all_leaves(leaves: Vec<Vec<u8>>) -> TestResult {
+        if leaves.is_empty() {
+            return TestResult::discard();
+        }
+
+        let levels = build(&leaves);
+        let root = levels.last().unwrap()[0];
+
+        TestResult::from_bool(
+            leaves
+                .iter()
+                .enumerate()
+                .all(|(i, leaf)| verify(&proof(&levels, i), &root, hash_leaf(leaf))),
+        )
+    }
+
+    #[quickcheck]
+    fn rejects_missing_leaf(leaves: Vec<Vec<u8>>, leaf: Vec<u8>) -> TestResult {
+        if leaves.is_empty() || leaves.contains(&leaf) {
+            return TestResult::discard();
+        }
+
+        let levels = build(&leaves);
+        let root = levels.last().unwrap()[0];
+
+        TestResult::from_bool(
+            (0..leaves.len()).all(|i| !verify(&proof(&levels, i), &root, hash_leaf(&leaf))),
+        )
+    }
+}
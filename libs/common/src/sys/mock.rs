@@ -3,7 +3,7 @@ use std::cell::Cell;
 use solana_program::clock::Clock;
 
 thread_local! {
-    static TIMESTAMP: Cell<i64> = Cell::new(0);
+    static TIMESTAMP: Cell<i64> = const { Cell::new(0) };
 }
 
 struct MockSyscall {}
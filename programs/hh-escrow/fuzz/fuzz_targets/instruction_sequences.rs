@@ -0,0 +1,405 @@
+//! Runs random sequences of market instructions against the program in
+//! `solana-program-test`, moving the clock forward between them, and checks
+//! the escrow invariants after every instruction.
+//!
+//! Instructions are expected to fail often; only the invariants are asserted.
+
+#![no_main]
+
+#[path = "../../tests/utils/mod.rs"]
+mod utils;
+
+use std::collections::HashMap;
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use solana_program_test::tokio;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::get_associated_token_address;
+
+use hh_client::{escrow, pda};
+use hh_escrow::instructions::{DepositParams, InitializeMarketParams};
+use hh_escrow::state::{GlobalState, Market, Outcome, UserPosition};
+
+use crate::utils::{TestEnv, START_TS};
+
+const MAX_ACTIONS: usize = 64;
+const MAX_MARKETS: usize = 4;
+const USERS: usize = 3;
+const USER_BALANCE: u64 = 1 << 40;
+
+#[derive(Arbitrary, Debug)]
+enum Action {
+    InitializeMarket {
+        yes_amount: u16,
+        no_amount: u16,
+        close_in: u16,
+        expiry_after_close: u16,
+        resolution_delay: u16,
+    },
+    InitializeUserPosition {
+        user: u8,
+        market: u8,
+    },
+    Deposit {
+        user: u8,
+        market: u8,
+        yes_amount: u16,
+        no_amount: u16,
+        allow_partial: bool,
+    },
+    UpdateState {
+        market: u8,
+        outcome: u8,
+    },
+    Withdraw {
+        user: u8,
+        market: u8,
+    },
+    Claim {
+        user: u8,
+        market: u8,
+    },
+    CollectFees {
+        market: u8,
+    },
+    Warp {
+        seconds: u32,
+    },
+}
+
+struct Harness {
+    env: TestEnv,
+    now: u64,
+    resolver: Keypair,
+    users: Vec<(Keypair, Pubkey)>,
+    markets: Vec<Pubkey>,
+    /// The outcome of each market when it was first seen finalized.
+    finalized: HashMap<Pubkey, Outcome>,
+}
+
+impl Harness {
+    async fn new() -> Harness {
+        let mut env = TestEnv::new().await;
+        let mut users = Vec::with_capacity(USERS);
+        for _ in 0..USERS {
+            users.push(env.user(USER_BALANCE).await);
+        }
+
+        Harness {
+            env,
+            now: START_TS,
+            resolver: Keypair::new(),
+            users,
+            markets: Vec::new(),
+            finalized: HashMap::new(),
+        }
+    }
+
+    fn user(&self, index: u8) -> (Keypair, Pubkey) {
+        let (user, token_account) = &self.users[index as usize % USERS];
+
+        (
+            Keypair::from_bytes(&user.to_bytes()).unwrap(),
+            *token_account,
+        )
+    }
+
+    fn market(&self, index: u8) -> Option<Pubkey> {
+        if self.markets.is_empty() {
+            return None;
+        }
+
+        Some(self.markets[index as usize % self.markets.len()])
+    }
+
+    fn fee_account(&self) -> Pubkey {
+        get_associated_token_address(&self.env.fee_wallet, &self.env.mint.pubkey())
+    }
+
+    async fn user_position(&mut self, user: &Pubkey, market: &Pubkey) -> Option<UserPosition> {
+        let key = pda::user_position(user, market);
+        self.env.account(&key).await?;
+
+        Some(self.env.decode(&key).await)
+    }
+
+    async fn run(&mut self, action: Action) {
+        match action {
+            Action::InitializeMarket {
+                yes_amount,
+                no_amount,
+                close_in,
+                expiry_after_close,
+                resolution_delay,
+            } => {
+                if self.markets.len() == MAX_MARKETS {
+                    return;
+                }
+
+                let market = Keypair::new();
+                let close_ts = self.now + close_in as u64;
+                let params = InitializeMarketParams {
+                    close_ts,
+                    expiry_ts: close_ts + expiry_after_close as u64,
+                    resolution_delay: resolution_delay as u32,
+                    yes_amount: yes_amount as u64,
+                    no_amount: no_amount as u64,
+                    ..self.env.market_params(&self.resolver.pubkey())
+                };
+                if self.env.try_init_market(&market, params).await.is_ok() {
+                    self.markets.push(market.pubkey());
+                }
+            }
+            Action::InitializeUserPosition { user, market } => {
+                let (user, market) = match (self.user(user), self.market(market)) {
+                    ((user, _), Some(market)) => (user, market),
+                    _ => return,
+                };
+                let ix =
+                    escrow::initialize_user_position(&user.pubkey(), &self.env.creator(), &market);
+                let _ = self.env.process(&[ix], &[&user]).await;
+            }
+            Action::Deposit {
+                user,
+                market,
+                yes_amount,
+                no_amount,
+                allow_partial,
+            } => {
+                let ((user, token_account), market) = match self.market(market) {
+                    Some(market) => (self.user(user), market),
+                    None => return,
+                };
+                let ix = escrow::deposit(
+                    &user.pubkey(),
+                    &market,
+                    &token_account,
+                    DepositParams {
+                        yes_amount: yes_amount as u64,
+                        no_amount: no_amount as u64,
+                        allow_partial,
+                        proof: vec![],
+                        referrer: None,
+                    },
+                );
+                let _ = self.env.process(&[ix], &[&user]).await;
+            }
+            Action::UpdateState { market, outcome } => {
+                let market = match self.market(market) {
+                    Some(market) => market,
+                    None => return,
+                };
+                let outcome = match outcome % 4 {
+                    0 => Outcome::Open,
+                    1 => Outcome::Yes,
+                    2 => Outcome::No,
+                    _ => Outcome::Invalid,
+                };
+                let resolver = Keypair::from_bytes(&self.resolver.to_bytes()).unwrap();
+                let _ = self.env.update_state(&market, &resolver, outcome).await;
+            }
+            Action::Withdraw { user, market } => {
+                let ((user, token_account), market) = match self.market(market) {
+                    Some(market) => (self.user(user), market),
+                    None => return,
+                };
+                let before = self.env.balance(&token_account).await;
+                let ix = escrow::withdraw(
+                    &user.pubkey(),
+                    &market,
+                    &self.env.mint.pubkey(),
+                    &user.pubkey(),
+                );
+                if self.env.process(&[ix], &[&user]).await.is_err() {
+                    return;
+                }
+
+                let received = self.env.balance(&token_account).await - before;
+                let info = self.env.market(&market).await;
+                assert!(
+                    received <= info.yes_filled + info.no_filled,
+                    "withdrew {} from a pool of {}",
+                    received,
+                    info.yes_filled + info.no_filled,
+                );
+            }
+            Action::Claim { user, market } => {
+                let ((user, token_account), market) = match self.market(market) {
+                    Some(market) => (self.user(user), market),
+                    None => return,
+                };
+                let position = match self.user_position(&user.pubkey(), &market).await {
+                    Some(position) => position,
+                    None => return,
+                };
+                let user_before = self.env.balance(&token_account).await;
+                let fee_before = self.env.market(&market).await.accrued_fees;
+
+                let ix = escrow::claim(
+                    &user.pubkey(),
+                    &market,
+                    &self.env.mint.pubkey(),
+                    &user.pubkey(),
+                    None,
+                );
+                if self.env.process(&[ix], &[&user]).await.is_err() {
+                    return;
+                }
+
+                let received = self.env.balance(&token_account).await - user_before;
+                let info = self.env.market(&market).await;
+                let fee = info.accrued_fees - fee_before;
+                let global_state: GlobalState = self.env.decode(&pda::global_state()).await;
+
+                let pool = info.yes_filled + info.no_filled;
+                assert!(
+                    received + fee <= pool,
+                    "claimed {} with a fee of {} from a pool of {}",
+                    received,
+                    fee,
+                    pool,
+                );
+
+                let (_, winnings) = info.winnings(&position);
+                assert!(fee <= winnings, "fee {} exceeds winnings {}", fee, winnings);
+                assert!(
+                    fee <= global_state.fee_cut_bps.fee(winnings),
+                    "fee {} exceeds {} bps of {}",
+                    fee,
+                    global_state.fee_cut_bps.get(),
+                    winnings,
+                );
+            }
+            Action::CollectFees { market } => {
+                let market = match self.market(market) {
+                    Some(market) => market,
+                    None => return,
+                };
+                let fee_account = self.fee_account();
+                let before = self.env.balance(&fee_account).await;
+                let info = self.env.market(&market).await;
+
+                let owner = Keypair::from_bytes(&self.env.owner.to_bytes()).unwrap();
+                let ix = escrow::collect_fees(
+                    &owner.pubkey(),
+                    &self.env.fee_wallet,
+                    &self.env.mint.pubkey(),
+                    &[(market, info.outcome)],
+                );
+                if self.env.process(&[ix], &[&owner]).await.is_err() {
+                    return;
+                }
+
+                let collected = self.env.balance(&fee_account).await - before;
+                assert_eq!(
+                    collected, info.accrued_fees,
+                    "collected {} of {} accrued fees",
+                    collected, info.accrued_fees,
+                );
+                assert_eq!(self.env.market(&market).await.accrued_fees, 0);
+            }
+            Action::Warp { seconds } => {
+                self.now += seconds as u64;
+                self.env.set_time(self.now).await;
+            }
+        }
+    }
+
+    /// Checks the invariants of every market.
+    async fn check(&mut self) {
+        for market in self.markets.clone() {
+            let info = self.env.market(&market).await;
+            self.check_outcome(&market, &info);
+
+            let mut positions = Vec::with_capacity(USERS);
+            for index in 0..USERS {
+                let user = self.users[index].0.pubkey();
+                if let Some(position) = self.user_position(&user, &market).await {
+                    positions.push(position);
+                }
+            }
+            let yes_balance = self.env.balance(&pda::yes_token_account(&market)).await;
+            let no_balance = self.env.balance(&pda::no_token_account(&market)).await;
+
+            check_escrows(&info, &positions, yes_balance, no_balance);
+        }
+    }
+
+    /// Checks that a finalized market keeps its outcome.
+    fn check_outcome(&mut self, market: &Pubkey, info: &Market) {
+        if let Some(outcome) = self.finalized.get(market) {
+            assert!(info.finalized, "market {} was unfinalized", market);
+            assert_eq!(
+                info.outcome, *outcome,
+                "finalized market {} changed outcome",
+                market
+            );
+        } else if info.finalized {
+            self.finalized.insert(*market, info.outcome);
+        }
+    }
+}
+
+/// Checks that the escrows of a market cover what its positions are owed.
+fn check_escrows(info: &Market, positions: &[UserPosition], yes_balance: u64, no_balance: u64) {
+    let sum = |f: &dyn Fn(&UserPosition) -> u64| positions.iter().map(f).sum::<u64>();
+
+    match info.outcome {
+        Outcome::Yes | Outcome::No if info.finalized => {
+            let (winning_balance, losing_balance) = match info.outcome {
+                Outcome::Yes => (yes_balance, no_balance),
+                _ => (no_balance, yes_balance),
+            };
+            let stakes = sum(&|position| info.winnings(position).0);
+            let winnings = sum(&|position| info.winnings(position).1);
+
+            assert!(
+                winning_balance >= stakes,
+                "winning escrow {} does not cover stakes {}",
+                winning_balance,
+                stakes,
+            );
+            assert!(
+                losing_balance >= winnings + info.accrued_fees,
+                "losing escrow {} does not cover winnings {} and fees {}",
+                losing_balance,
+                winnings,
+                info.accrued_fees,
+            );
+        }
+        _ => {
+            let yes_amount = sum(&|position| position.yes_amount);
+            let no_amount = sum(&|position| position.no_amount);
+
+            assert!(
+                yes_balance >= yes_amount,
+                "yes escrow {} does not cover positions {}",
+                yes_balance,
+                yes_amount,
+            );
+            assert!(
+                no_balance >= no_amount,
+                "no escrow {} does not cover positions {}",
+                no_balance,
+                no_amount,
+            );
+        }
+    }
+}
+
+fuzz_target!(|actions: Vec<Action>| {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    runtime.block_on(async {
+        let mut harness = Harness::new().await;
+        for action in actions.into_iter().take(MAX_ACTIONS) {
+            harness.run(action).await;
+            harness.check().await;
+        }
+    });
+});
@@ -50,4 +50,144 @@ pub enum ErrorCode {
     AssociatedTokenAccountRequired,
     #[msg("Cannot have nonzero amounts.")]
     CannotHaveNonzeroAmounts, // FIXME: This is incorrectly named, this error is caused by having zero fill tokens.
+    #[msg("Must use the associated token account of the user to settle a position.")]
+    UserAccountNotAssociated,
+    #[msg("The cranker token account must be owned by the signing cranker.")]
+    CrankerAccountIncorrectOwner,
+    #[msg("The deposit limits can never be satisfied.")]
+    InvalidDepositLimits,
+    #[msg("The deposit is smaller than the minimum deposit.")]
+    BelowMinimumDeposit,
+    #[msg("Attempted to deposit more than the allowed amount per user.")]
+    OverUserLimit,
+    #[msg("The user is not on the market allowlist.")]
+    NotAllowlisted,
+    #[msg("Creator does not match the market creator.")]
+    IncorrectCreator,
+    #[msg("The resolver has not acknowledged the market.")]
+    NotAcknowledged,
+    #[msg("The resolver has already acknowledged the market.")]
+    AlreadyAcknowledged,
+    #[msg("Other users have already deposited into the market.")]
+    DepositsExist,
+    #[msg("Market title must be non-empty and at most 64 bytes.")]
+    InvalidMarketTitle,
+    #[msg("Market category must be ASCII padded with zeroes.")]
+    InvalidMarketCategory,
+    #[msg("The account has already been migrated.")]
+    AlreadyMigrated,
+    #[msg("The account does not have a known layout.")]
+    UnknownAccountLayout,
+    #[msg("The template cadence must be nonzero.")]
+    InvalidTemplateCadence,
+    #[msg("The last market spawned from the template has not closed yet.")]
+    TemplateNotDue,
+    #[msg("The resolver instruction data is too long.")]
+    InvalidResolverData,
+    #[msg("The resolver account must be passed to spawn a market with a resolver program.")]
+    MissingResolverAccount,
+    #[msg("The resolver program does not match the template resolver program.")]
+    IncorrectResolverProgram,
+    #[msg("The market registry page is full.")]
+    RegistryPageFull,
+    #[msg("The market is not in the market registry page.")]
+    MarketNotRegistered,
+    #[msg("The market registry page does not belong to the market registry.")]
+    IncorrectRegistryPage,
+    #[msg("Token mint does not match the market token mint.")]
+    IncorrectTokenMint,
+    #[msg("Fee wallet does not match the global state fee wallet.")]
+    IncorrectFeeWallet,
+    #[msg("Signer must be the fee wallet or the global state owner.")]
+    IncorrectFeeCollector,
+    #[msg("Each market must be followed by its losing escrow and its authority.")]
+    InvalidFeeCollectionAccounts,
+    #[msg("Escrow does not hold the losing side of the market.")]
+    IncorrectFeeEscrow,
+    #[msg("Authority does not match the market authority.")]
+    IncorrectMarketAuthority,
+    #[msg("Referrer must not be the default public key or the user.")]
+    InvalidReferrer,
+    #[msg("The referrer cannot change after the first deposit.")]
+    ReferrerImmutable,
+    #[msg("Referrer token account must be the associated token account of the referrer.")]
+    ReferrerAccountNotAssociated,
+    #[msg("Minimum resolution window cannot exceed the maximum, which must be nonzero.")]
+    InvalidResolutionWindowBounds,
+    #[msg("Resolution window is outside the bounds set by the global state.")]
+    InvalidResolutionWindow,
+    #[msg("Resolution delay cannot exceed the resolution window.")]
+    ResolutionDelayTooLong,
+    #[msg("Market policy minimums cannot exceed their maximums.")]
+    InvalidMarketPolicy,
+    #[msg("Close timestamp is sooner than the market policy allows.")]
+    CloseTooSoon,
+    #[msg("Close timestamp is later than the market policy allows.")]
+    CloseTooLate,
+    #[msg("Expiry timestamp is further from the close than the market policy allows.")]
+    ExpiryTooLate,
+    #[msg("Resolution delay is shorter than the market policy allows.")]
+    ResolutionDelayTooShort,
+    #[msg("Resolution delay is longer than the market policy allows.")]
+    ResolutionDelayAbovePolicy,
+    #[msg("Side amount is below the minimum fill of the token mint.")]
+    FillTooSmall,
+    #[msg("Market URI is longer than the market policy allows.")]
+    UriTooLong,
+    #[msg("The market has no creation bond left to settle.")]
+    NoCreationBond,
+    #[msg("The creation bond must be settled to close the market.")]
+    CreationBondNotSettled,
+    #[msg("Bond payer does not match the market bond payer.")]
+    IncorrectBondPayer,
+    #[msg("A resolver bond requires the resolver to acknowledge the market.")]
+    ResolverBondRequiresAcknowledgement,
+    #[msg("No arbiter is set to rule on disputes.")]
+    NoArbiter,
+    #[msg("Arbiter does not match the global state arbiter.")]
+    IncorrectArbiter,
+    #[msg("The market has no outcome to dispute.")]
+    OutcomeNotSet,
+    #[msg("Only positions with deposits can dispute the outcome.")]
+    EmptyPosition,
+    #[msg("The position already disputed the outcome.")]
+    AlreadyDisputed,
+    #[msg("The outcome is disputed and awaits the arbiter.")]
+    MarketDisputed,
+    #[msg("The outcome of the market is not disputed.")]
+    NotDisputed,
+    #[msg("The arbiter must rule on a final outcome.")]
+    InvalidRuling,
+    #[msg("The market has no resolver bond left to settle.")]
+    NoResolverBond,
+    #[msg("The resolver bond was slashed and is paid to disputing users.")]
+    ResolverSlashed,
+    #[msg("The resolver bond was not slashed.")]
+    ResolverNotSlashed,
+    #[msg("The resolver bond must be settled to close the market.")]
+    ResolverBondNotSettled,
+    #[msg("Every user position must be closed to close the market.")]
+    OpenPositions,
+    #[msg("The position must be paid out to be closed.")]
+    PositionNotEmpty,
+    #[msg("The position must claim its share of the slashed resolver bond.")]
+    SlashedBondNotClaimed,
+    #[msg("Market does not match the market of the user position.")]
+    IncorrectMarket,
+    #[msg("The resolver bond accounts are missing or the bond payer did not sign.")]
+    InvalidResolverBondAccounts,
+    #[msg("Resolver bond escrow does not match the market.")]
+    IncorrectResolverBondEscrow,
+    #[msg("The position has no dispute bond left to settle.")]
+    NoDisputeBond,
+    #[msg("The slashed dispute bond must be settled to close the position.")]
+    DisputeBondNotSettled,
+    #[msg("The resolver program is not allowed for market templates.")]
+    ResolverProgramNotAllowed,
+    #[msg("The URI is too long for the migrated layout.")]
+    UriTooLongToMigrate,
+    #[msg("The resolver was not initialized by the resolver program.")]
+    ResolverNotInitialized,
+    #[msg("Every user position of the market was already migrated.")]
+    NoUnmigratedPositions,
 }
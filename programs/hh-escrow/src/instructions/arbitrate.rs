@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, Market, Outcome};
+
+/// Parameters for the [Arbitrate] instruction.
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct ArbitrateParams {
+    /// The final outcome of the market.
+    pub outcome: Outcome,
+}
+
+/// Allows the arbiter of the global state to rule on a disputed outcome,
+/// which finalizes the market immediately.
+///
+/// If the ruling differs from the outcome set by the resolver, the resolver
+/// bond is slashed. Otherwise, the dispute bonds of the disputing positions
+/// are.
+#[derive(Accounts)]
+#[instruction(params: ArbitrateParams)]
+pub struct Arbitrate<'info> {
+    /// The global state account.
+    #[account(
+        seeds = [b"global"],
+        bump,
+        has_one = arbiter @ ErrorCode::IncorrectArbiter,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    /// The market whose outcome is disputed.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// The arbiter of the global state.
+    pub arbiter: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<Arbitrate>, params: ArbitrateParams) -> ProgramResult {
+    let ArbitrateParams { outcome } = params;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    let market = &mut ctx.accounts.market;
+    market.set_and_check_finalize(now)?;
+    if !market.disputed {
+        return Err(error!(ErrorCode::NotDisputed).into());
+    }
+    if outcome == Outcome::Open {
+        return Err(error!(ErrorCode::InvalidRuling).into());
+    }
+
+    market.resolver_slashed = outcome != market.outcome;
+    market.dispute_upheld = outcome == market.outcome;
+    market.outcome = outcome;
+    market.outcome_ts = now;
+    market.disputed = false;
+    market.finalized = true;
+
+    Ok(())
+}
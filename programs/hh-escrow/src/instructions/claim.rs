@@ -1,33 +1,48 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 use solana_program::entrypoint::ProgramResult;
-use spl_associated_token_account::get_associated_token_address;
 
 use common::traits::KeyRef;
 
 use crate::error::ErrorCode;
-use crate::state::{GlobalState, Market, Outcome, UserPosition};
-use crate::utils::signer_transfer;
-
-/// Allows users to claim their winnings.
+use crate::state::{GlobalState, MarketZc, Outcome, UserPosition};
+use crate::utils::{referral, signer_transfer};
+
+/// Allows users to claim their winnings, into the associated token account
+/// of any wallet.
+///
+/// The protocol fee is left in the losing side's escrow, and accrued on the
+/// market until it is swept by [CollectFees](crate::instructions::CollectFees).
+/// If the position has a referrer, their share of the fee is paid out
+/// directly.
 #[derive(Accounts)]
 pub struct Claim<'info> {
     /// The global state account.
     #[account(seeds = [b"global"], bump)]
     pub global_state: Account<'info, GlobalState>,
-    /// The fee account that receive protocol fees.
+    /// The wallet receiving the payout, which may be the user.
+    ///
+    /// CHECK: We only need the public key from this account.
+    pub destination: UncheckedAccount<'info>,
+    /// The associated token account of the destination wallet, created with
+    /// the user as payer if it does not exist yet.
     #[account(
-        mut,
-        constraint = fee_account.owner == global_state.fee_wallet @ ErrorCode::AccountNotOwnedByFeeWallet,
+        init_if_needed,
+        payer = user,
+        associated_token::mint = token_mint,
+        associated_token::authority = destination,
     )]
-    pub fee_account: Account<'info, TokenAccount>,
-    /// The user's token account. We explicitly check the owner for this
-    /// account.
-    #[account(mut,
-        constraint = user_token_account.key_ref() != yes_token_account.key_ref() && user_token_account.key_ref() != no_token_account.key_ref() @ ErrorCode::UserAccountCannotBeMarketAccount,
-        constraint = user_token_account.owner == *user.key_ref() @ ErrorCode::UserAccountIncorrectOwner
-    )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+    /// The associated token account of the referrer of the position. Ignored
+    /// if the position has no referrer.
+    ///
+    /// CHECK: The address is checked against the referrer of the position if
+    /// there is one. Writes only occur via the token program.
+    #[account(mut)]
+    pub referrer_token_account: UncheckedAccount<'info>,
+    /// The token mint of the market.
+    pub token_mint: Box<Account<'info, Mint>>,
     /// Escrow for tokens on the yes side of the market.
     ///
     /// CHECK: We do not read any data from this account. The correctness of the
@@ -51,13 +66,15 @@ pub struct Claim<'info> {
         bump
     )]
     pub user_position: Account<'info, UserPosition>,
-    /// The [Market] to claim winnings for.
+    /// The [Market](crate::state::Market) to claim winnings for, loaded
+    /// without deserializing it onto the stack.
     #[account(
         mut,
         has_one = yes_token_account @ ErrorCode::IncorrectYesEscrow,
         has_one = no_token_account @ ErrorCode::IncorrectNoEscrow,
+        has_one = token_mint @ ErrorCode::IncorrectTokenMint,
     )]
-    pub market: Box<Account<'info, Market>>,
+    pub market: AccountLoader<'info, MarketZc>,
     /// The authority for the market token accounts.
     ///
     /// CHECK: We do not read/write any data from this account.
@@ -65,26 +82,29 @@ pub struct Claim<'info> {
     pub authority: AccountInfo<'info>,
     /// The SPL Token program.
     pub token_program: Program<'info, Token>,
-    /// The user claiming winnings.
+    /// The SPL Associated Token Account program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+    /// The Sysvar rent.
+    pub rent: Sysvar<'info, Rent>,
+    /// The user claiming winnings, paying for the destination token account if
+    /// needed.
+    #[account(mut)]
     pub user: Signer<'info>,
 }
 
 impl Claim<'_> {
     pub fn can_claim(&mut self) -> Result<()> {
-        // Check that the provided fee token account is the associated token
-        // account of the fee wallet.
-        let key =
-            get_associated_token_address(&self.global_state.fee_wallet, &self.market.token_mint);
-        if key != *self.fee_account.key_ref() {
-            return Err(error!(ErrorCode::AssociatedTokenAccountRequired));
-        }
+        let mut market = MarketZc::load_mut_checked(&self.market)?;
 
         let now = Clock::get()?.unix_timestamp as u64;
-        if !self.market.finalize(now)? {
+        if !market.finalize(now)? {
             return Err(error!(ErrorCode::NotFinalized));
         }
 
-        if self.market.outcome == Outcome::Invalid || self.market.outcome == Outcome::Open {
+        let outcome = market.outcome()?;
+        if outcome == Outcome::Invalid || outcome == Outcome::Open {
             return Err(error!(ErrorCode::CannotClaim));
         }
 
@@ -106,28 +126,31 @@ impl Claim<'_> {
 pub fn handler(ctx: Context<Claim>) -> ProgramResult {
     ctx.accounts.can_claim()?;
 
-    let user_position = &ctx.accounts.user_position;
-    let market = &ctx.accounts.market;
-
-    // Compute the winnings.
-    let (winning_num, winning_denom, pool, winning_side_holdings, losing_side_holdings) =
-        match ctx.accounts.market.outcome {
-            Outcome::Yes => (
-                user_position.yes_amount,
-                market.yes_amount,
-                market.no_amount,
-                &ctx.accounts.yes_token_account,
-                &ctx.accounts.no_token_account,
-            ),
-            Outcome::No => (
-                user_position.no_amount,
-                market.no_amount,
-                market.yes_amount,
-                &ctx.accounts.no_token_account,
-                &ctx.accounts.yes_token_account,
-            ),
-            _ => unreachable!(),
-        };
+    // Compute the winnings, recording them as paid out and the fee, less the
+    // referral, as accrued.
+    let mut market = MarketZc::load_mut_checked(&ctx.accounts.market)?;
+    let (winning_num, winnings) = market.pay_out(&ctx.accounts.user_position)?;
+    let fee = ctx.accounts.global_state.fee_cut_bps.fee(winnings);
+    let referral = referral(
+        &ctx.accounts.global_state,
+        &ctx.accounts.user_position,
+        ctx.accounts.token_mint.key_ref(),
+        &ctx.accounts.referrer_token_account,
+        fee,
+    )?;
+    market.accrue_fee(fee - referral)?;
+    let (winning_side_holdings, losing_side_holdings) = match market.outcome()? {
+        Outcome::Yes => (
+            &ctx.accounts.yes_token_account,
+            &ctx.accounts.no_token_account,
+        ),
+        Outcome::No => (
+            &ctx.accounts.no_token_account,
+            &ctx.accounts.yes_token_account,
+        ),
+        _ => unreachable!(),
+    };
+    drop(market);
 
     // Reset the user position.
     let user_position = &mut ctx.accounts.user_position;
@@ -139,19 +162,8 @@ pub fn handler(ctx: Context<Claim>) -> ProgramResult {
         return Ok(());
     }
 
-    // Both numbers are u64, so this should not overflow. Morever, num / denom *
-    // pool <= pool, so the cast to u64 should not lose information beyond any
-    // fractional portion of the division.
-    let winnings = (((winning_num as u128) * (pool as u128)) / (winning_denom as u128)) as u64;
-
-    // Fees.
-    let fee = ctx.accounts.global_state.fee_cut_bps.fee(winnings);
-
     // Clip remaining winnings to 0.
-    let remaining_winnings = match winnings.checked_sub(fee) {
-        Some(x) => x,
-        None => 0,
-    };
+    let remaining_winnings = winnings.saturating_sub(fee);
 
     // Transfer.
     let bump_seed = *ctx
@@ -161,20 +173,22 @@ pub fn handler(ctx: Context<Claim>) -> ProgramResult {
 
     ctx.accounts.with_signer_seeds(
         |signer| {
-            // Fee to the fee wallet.
-            signer_transfer(
-                &ctx.accounts.token_program,
-                &losing_side_holdings,
-                &ctx.accounts.fee_account.to_account_info(),
-                &ctx.accounts.authority,
-                &[signer],
-                fee,
-            )?;
+            // Referral to the referrer's wallet.
+            if referral > 0 {
+                signer_transfer(
+                    &ctx.accounts.token_program,
+                    losing_side_holdings,
+                    &ctx.accounts.referrer_token_account,
+                    &ctx.accounts.authority,
+                    &[signer],
+                    referral,
+                )?;
+            }
 
             // Winnings to the user's wallet.
             signer_transfer(
                 &ctx.accounts.token_program,
-                &losing_side_holdings,
+                losing_side_holdings,
                 &ctx.accounts.user_token_account.to_account_info(),
                 &ctx.accounts.authority,
                 &[signer],
@@ -184,7 +198,7 @@ pub fn handler(ctx: Context<Claim>) -> ProgramResult {
             // Original position to the user's wallet.
             signer_transfer(
                 &ctx.accounts.token_program,
-                &winning_side_holdings,
+                winning_side_holdings,
                 &ctx.accounts.user_token_account.to_account_info(),
                 &ctx.accounts.authority,
                 &[signer],
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use solana_program::entrypoint::ProgramResult;
+use spl_associated_token_account::get_associated_token_address;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{Market, UserPosition};
+use crate::utils::signer_transfer;
+
+/// Permissionless crank that pays a disputing user their share of a slashed
+/// resolver bond, into their associated token account.
+#[derive(Accounts)]
+pub struct ClaimSlashedBond<'info> {
+    /// The market whose resolver bond was slashed.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// The authority for the market token accounts.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"authority", market.key_ref().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    /// Escrow for the resolver bond.
+    ///
+    /// CHECK: The address is checked by the seeds. Writes only occur via the
+    /// token program.
+    #[account(mut, seeds = [b"resolver_bond", market.key_ref().as_ref()], bump)]
+    pub resolver_bond_token_account: UncheckedAccount<'info>,
+    /// The disputing user's [UserPosition] account.
+    #[account(
+        mut,
+        seeds = [b"user", user.key_ref().as_ref(), market.key_ref().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// The user who disputed the outcome.
+    ///
+    /// CHECK: We only need the public key from this account.
+    pub user: UncheckedAccount<'info>,
+    /// The associated token account of the user.
+    ///
+    /// CHECK: The address is checked against the user. Writes only occur via
+    /// the token program.
+    #[account(mut)]
+    pub user_token_account: UncheckedAccount<'info>,
+    /// The SPL Token program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl ClaimSlashedBond<'_> {
+    /// Checks that the resolver bond was slashed, and that the position
+    /// disputed the outcome without being paid yet.
+    fn can_claim(&self) -> Result<()> {
+        if !self.market.resolver_slashed {
+            return Err(error!(ErrorCode::ResolverNotSlashed));
+        }
+        if self.market.resolver_bond_outstanding() == 0 {
+            return Err(error!(ErrorCode::NoResolverBond));
+        }
+        if self.user_position.dispute_stake == 0 {
+            return Err(error!(ErrorCode::NotDisputed));
+        }
+
+        let key = get_associated_token_address(self.user.key_ref(), &self.market.token_mint);
+        if key != *self.user_token_account.key_ref() {
+            return Err(error!(ErrorCode::AssociatedTokenAccountRequired));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<ClaimSlashedBond>) -> ProgramResult {
+    ctx.accounts.can_claim()?;
+
+    let stake = ctx.accounts.user_position.dispute_stake;
+    let share = ctx.accounts.market.pay_out_slashed_bond(stake)?;
+    ctx.accounts.user_position.dispute_stake = 0;
+
+    if share == 0 {
+        return Ok(());
+    }
+
+    let bump = *ctx
+        .bumps
+        .get("authority")
+        .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
+    let market_key = ctx.accounts.market.key();
+    let seeds = [b"authority", market_key.as_ref(), &[bump]];
+    signer_transfer(
+        &ctx.accounts.token_program,
+        &ctx.accounts.resolver_bond_token_account,
+        &ctx.accounts.user_token_account,
+        &ctx.accounts.authority,
+        &[&seeds],
+        share,
+    )?;
+
+    Ok(())
+}
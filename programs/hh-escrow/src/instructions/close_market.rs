@@ -0,0 +1,196 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Token, TokenAccount};
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, Market, CREATOR_REGISTRY_SEED, MINT_REGISTRY_SEED};
+use crate::utils::{signer_transfer, unregister_market};
+
+/// Closes a finalized [`Market`] whose bonds have been settled and whose user
+/// positions have all been closed, returning the rent to the creator and
+/// removing the market from the registries of its creator and token mint.
+///
+/// Tokens left in the escrow accounts, such as uncollected fees, rounding
+/// remainders or tokens sent to an escrow directly, belong to no position and
+/// are swept to the fee wallet.
+#[derive(Accounts)]
+pub struct CloseMarket<'info> {
+    /// The market to close.
+    #[account(
+        mut,
+        close = creator,
+        has_one = creator @ ErrorCode::IncorrectCreator,
+        has_one = yes_token_account @ ErrorCode::IncorrectYesEscrow,
+        has_one = no_token_account @ ErrorCode::IncorrectNoEscrow,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// The authority for the two token accounts.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"authority", market.key_ref().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    /// The creator of the market, who receives the rent.
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    /// Escrow for tokens on the yes side of the market.
+    #[account(mut)]
+    pub yes_token_account: Box<Account<'info, TokenAccount>>,
+    /// Escrow for tokens on the no side of the market.
+    #[account(mut)]
+    pub no_token_account: Box<Account<'info, TokenAccount>>,
+    /// Escrow for the creation bond. Markets created before creation bonds
+    /// were introduced do not have one.
+    ///
+    /// CHECK: The address is checked by the seeds, and the account is only
+    /// closed via the token program if it exists.
+    #[account(mut, seeds = [b"bond", market.key_ref().as_ref()], bump)]
+    pub bond_token_account: UncheckedAccount<'info>,
+    /// Escrow for the resolver bond, which only exists once the resolver
+    /// acknowledged the market.
+    ///
+    /// CHECK: The address is checked by the seeds, and the account is only
+    /// closed via the token program if it exists.
+    #[account(mut, seeds = [b"resolver_bond", market.key_ref().as_ref()], bump)]
+    pub resolver_bond_token_account: UncheckedAccount<'info>,
+    /// The registry of the creator's markets. Only used if the market was
+    /// registered, as markets created before registries were not.
+    ///
+    /// CHECK: The address is checked by the seeds, and the account is
+    /// deserialized if the market was registered.
+    #[account(
+        mut,
+        seeds = [b"registry", CREATOR_REGISTRY_SEED, market.creator.as_ref()],
+        bump,
+    )]
+    pub creator_registry: UncheckedAccount<'info>,
+    /// The page of the creator's registry holding the market.
+    ///
+    /// CHECK: The account is deserialized and checked against the registry if
+    /// the market was registered.
+    #[account(mut)]
+    pub creator_registry_page: UncheckedAccount<'info>,
+    /// The registry of the token mint's markets. Only used if the market was
+    /// registered.
+    ///
+    /// CHECK: The address is checked by the seeds, and the account is
+    /// deserialized if the market was registered.
+    #[account(
+        mut,
+        seeds = [b"registry", MINT_REGISTRY_SEED, market.token_mint.as_ref()],
+        bump,
+    )]
+    pub mint_registry: UncheckedAccount<'info>,
+    /// The page of the token mint's registry holding the market.
+    ///
+    /// CHECK: The account is deserialized and checked against the registry if
+    /// the market was registered.
+    #[account(mut)]
+    pub mint_registry_page: UncheckedAccount<'info>,
+    /// The global state account.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    /// The associated token account of the fee wallet, which receives the
+    /// tokens left in the escrow accounts.
+    #[account(
+        mut,
+        associated_token::mint = market.token_mint,
+        associated_token::authority = global_state.fee_wallet,
+    )]
+    pub fee_account: Box<Account<'info, TokenAccount>>,
+    /// The SPL Token Program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CloseMarket<'info> {
+    /// Checks that the market is finalized, that both bonds have been paid
+    /// out, and that no user position is left, including those that were not
+    /// migrated yet.
+    fn can_close(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp as u64;
+        if !self.market.finalize(now)? {
+            return Err(error!(ErrorCode::NotFinalized));
+        }
+        if self.market.creation_bond != 0 {
+            return Err(error!(ErrorCode::CreationBondNotSettled));
+        }
+        if self.market.resolver_bond_outstanding() != 0 {
+            return Err(error!(ErrorCode::ResolverBondNotSettled));
+        }
+        if self.market.open_positions != 0 || self.market.unmigrated_positions != 0 {
+            return Err(error!(ErrorCode::OpenPositions));
+        }
+
+        Ok(())
+    }
+
+    /// Sweeps the tokens left in an escrow account to the fee wallet, then
+    /// closes it, returning its rent to the creator.
+    fn close_escrow(&self, escrow: &AccountInfo<'info>, bump: u8) -> Result<()> {
+        let market_key = self.market.key_ref();
+        let seeds = [b"authority", market_key.as_ref(), &[bump]];
+        let signer = &[&seeds[..]];
+
+        let amount = Account::<TokenAccount>::try_from(escrow)?.amount;
+        if amount != 0 {
+            signer_transfer(
+                &self.token_program,
+                escrow,
+                &self.fee_account.to_account_info(),
+                &self.authority,
+                signer,
+                amount,
+            )?;
+        }
+
+        let ctx = CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: escrow.clone(),
+                destination: self.creator.to_account_info(),
+                authority: self.authority.clone(),
+            },
+            signer,
+        );
+
+        token::close_account(ctx)
+    }
+}
+
+pub fn handler(ctx: Context<CloseMarket>) -> ProgramResult {
+    ctx.accounts.can_close()?;
+
+    let bump = *ctx
+        .bumps
+        .get("authority")
+        .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
+    ctx.accounts
+        .close_escrow(&ctx.accounts.yes_token_account.to_account_info(), bump)?;
+    ctx.accounts
+        .close_escrow(&ctx.accounts.no_token_account.to_account_info(), bump)?;
+    for escrow in [
+        &ctx.accounts.bond_token_account,
+        &ctx.accounts.resolver_bond_token_account,
+    ] {
+        if *escrow.owner == token::ID && !escrow.data_is_empty() {
+            ctx.accounts.close_escrow(escrow, bump)?;
+        }
+    }
+
+    if ctx.accounts.market.registered {
+        let market = ctx.accounts.market.key();
+        unregister_market(
+            &ctx.accounts.creator_registry,
+            &ctx.accounts.creator_registry_page,
+            &market,
+        )?;
+        unregister_market(
+            &ctx.accounts.mint_registry,
+            &ctx.accounts.mint_registry_page,
+            &market,
+        )?;
+    }
+
+    Ok(())
+}
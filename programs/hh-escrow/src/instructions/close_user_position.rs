@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{Market, UserPosition};
+
+/// Closes a paid out [UserPosition] of a finalized market, returning the rent
+/// to the user.
+///
+/// Positions must be closed before the market itself, so that a closed market
+/// can't be initialized again with stale positions.
+#[derive(Accounts)]
+pub struct CloseUserPosition<'info> {
+    /// The user owning the position, who receives the rent.
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// The [Market] of the position.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// The user's [UserPosition] account.
+    #[account(
+        mut,
+        close = user,
+        seeds = [b"user", user.key_ref().as_ref(), market.key_ref().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+}
+
+impl CloseUserPosition<'_> {
+    /// Checks that the market is finalized, and that the position has been
+    /// paid out, including its share of a slashed resolver bond. A dispute
+    /// bond is refunded with the rent, unless it was slashed, in which case it
+    /// must be settled first.
+    fn can_close(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp as u64;
+        if !self.market.finalize(now)? {
+            return Err(error!(ErrorCode::NotFinalized));
+        }
+        if self.user_position.yes_amount != 0 || self.user_position.no_amount != 0 {
+            return Err(error!(ErrorCode::PositionNotEmpty));
+        }
+        if self.market.resolver_slashed && self.user_position.dispute_stake != 0 {
+            return Err(error!(ErrorCode::SlashedBondNotClaimed));
+        }
+        if self.market.dispute_upheld && self.user_position.dispute_bond != 0 {
+            return Err(error!(ErrorCode::DisputeBondNotSettled));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<CloseUserPosition>) -> ProgramResult {
+    ctx.accounts.can_close()?;
+    ctx.accounts.market.close_position()?;
+
+    Ok(())
+}
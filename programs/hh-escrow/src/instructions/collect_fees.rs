@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, MarketZc};
+use crate::utils::signer_transfer;
+
+/// Sweeps the protocol fees accrued by claims on one or many markets into the
+/// associated token account of the fee wallet.
+///
+/// Each market is passed in the remaining accounts, followed by the escrow of
+/// its losing side and its authority. All markets must share the token mint.
+#[derive(Accounts)]
+pub struct CollectFees<'info> {
+    /// The global state account.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// The wallet receiving protocol fees.
+    ///
+    /// CHECK: We only need the public key from this account.
+    #[account(address = global_state.fee_wallet @ ErrorCode::IncorrectFeeWallet)]
+    pub fee_wallet: UncheckedAccount<'info>,
+    /// The associated token account of the fee wallet.
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = fee_wallet,
+    )]
+    pub fee_account: Box<Account<'info, TokenAccount>>,
+    /// The token mint of the markets.
+    pub token_mint: Box<Account<'info, Mint>>,
+    /// The fee wallet or the owner of the global state.
+    #[account(
+        constraint = *collector.key_ref() == global_state.fee_wallet
+            || *collector.key_ref() == global_state.owner
+            @ ErrorCode::IncorrectFeeCollector,
+    )]
+    pub collector: Signer<'info>,
+    /// The SPL Token program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CollectFees<'info> {
+    /// Transfers the fees accrued on a market from the escrow of its losing
+    /// side, returning the amount collected.
+    fn collect(
+        &self,
+        market_info: &AccountInfo<'info>,
+        escrow: &AccountInfo<'info>,
+        authority: &AccountInfo<'info>,
+    ) -> Result<u64> {
+        let loader = AccountLoader::<MarketZc>::try_from(market_info)?;
+        let mut market = MarketZc::load_mut_checked(&loader)?;
+        if market.token_mint != *self.token_mint.key_ref() {
+            return Err(error!(ErrorCode::IncorrectTokenMint));
+        }
+
+        let fee = market.accrued_fees;
+        if fee == 0 {
+            return Ok(0);
+        }
+
+        // Fees are only accrued once the market has resolved to a side, and
+        // are held by the escrow of the other side.
+        if market.losing_token_account()? != Some(*escrow.key) {
+            return Err(error!(ErrorCode::IncorrectFeeEscrow));
+        }
+
+        let (key, bump) =
+            Pubkey::find_program_address(&[b"authority", market_info.key.as_ref()], &crate::ID);
+        if key != *authority.key {
+            return Err(error!(ErrorCode::IncorrectMarketAuthority));
+        }
+
+        market.accrued_fees = 0;
+        drop(market);
+
+        let seeds = [b"authority", market_info.key.as_ref(), &[bump]];
+        signer_transfer(
+            &self.token_program,
+            escrow,
+            &self.fee_account.to_account_info(),
+            authority,
+            &[&seeds],
+            fee,
+        )?;
+
+        Ok(fee)
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CollectFees<'info>>) -> ProgramResult {
+    if ctx.remaining_accounts.is_empty() || ctx.remaining_accounts.len() % 3 != 0 {
+        return Err(error!(ErrorCode::InvalidFeeCollectionAccounts).into());
+    }
+
+    for accounts in ctx.remaining_accounts.chunks(3) {
+        ctx.accounts
+            .collect(&accounts[0], &accounts[1], &accounts[2])?;
+    }
+
+    Ok(())
+}
@@ -12,12 +12,18 @@ use crate::utils::non_signer_transfer;
 #[derive(Clone, AnchorDeserialize, AnchorSerialize)]
 pub struct DepositParams {
     /// The amount to deposit on the yes side.
-    yes_amount: u64,
+    pub yes_amount: u64,
     /// The amount to deposit on the no side.
-    no_amount: u64,
+    pub no_amount: u64,
     /// If true, the instruction will not fail if the user attempts to fill a
     /// side above the specified amount, but rather fill that side to the max.
-    allow_partial: bool,
+    pub allow_partial: bool,
+    /// Merkle proof that the user is on the market allowlist. Ignored if the
+    /// market is not private.
+    pub proof: Vec<[u8; 32]>,
+    /// The wallet that referred the user. Only recorded on the first deposit
+    /// into the position.
+    pub referrer: Option<Pubkey>,
 }
 
 /// Allows a user to deposit into a given market.
@@ -69,6 +75,7 @@ impl Deposit<'_> {
         yes_amount: u64,
         no_amount: u64,
         allow_partial: bool,
+        proof: &[[u8; 32]],
     ) -> Result<(u64, u64)> {
         let now = Clock::get()?.unix_timestamp as u64;
         if self.market.close_ts <= now {
@@ -77,26 +84,40 @@ impl Deposit<'_> {
 
         self.market.set_and_check_finalize(now)?;
 
+        if !self.market.is_acknowledged() {
+            return Err(error!(ErrorCode::NotAcknowledged));
+        }
+
+        if !self.market.is_allowed(self.user.key_ref(), proof) {
+            return Err(error!(ErrorCode::NotAllowlisted));
+        }
+
         // These subtractions should be safe.
         let yes_left = self
             .market
             .yes_amount
             .checked_sub(self.market.yes_filled)
             .ok_or_else(|| error!(ErrorCode::Overflow))?;
-        if yes_left < yes_amount && !allow_partial {
-            return Err(error!(ErrorCode::OverAllowedAmount));
-        }
+        let yes_amount = self.market.yes_limits.deposit_amount(
+            self.user_position.yes_amount,
+            yes_left,
+            yes_amount,
+            allow_partial,
+        )?;
 
         let no_left = self
             .market
             .no_amount
             .checked_sub(self.market.no_filled)
             .ok_or_else(|| error!(ErrorCode::Overflow))?;
-        if no_left < no_amount && !allow_partial {
-            return Err(error!(ErrorCode::OverAllowedAmount));
-        }
+        let no_amount = self.market.no_limits.deposit_amount(
+            self.user_position.no_amount,
+            no_left,
+            no_amount,
+            allow_partial,
+        )?;
 
-        Ok((yes_left.min(yes_amount), no_left.min(no_amount)))
+        Ok((yes_amount, no_amount))
     }
 }
 
@@ -105,16 +126,26 @@ pub fn handler(ctx: Context<Deposit>, params: DepositParams) -> ProgramResult {
         yes_amount,
         no_amount,
         allow_partial,
+        proof,
+        referrer,
     } = params;
 
     let (yes_to_deposit, no_to_deposit) =
         ctx.accounts
-            .can_deposit(yes_amount, no_amount, allow_partial)?;
+            .can_deposit(yes_amount, no_amount, allow_partial, &proof)?;
 
     // Update the state.
     let user_position = &mut ctx.accounts.user_position;
     let market = &mut ctx.accounts.market;
 
+    // Record the referrer, which must be set before any deposit.
+    if let Some(referrer) = referrer {
+        if referrer == Pubkey::default() || referrer == *ctx.accounts.user.key_ref() {
+            return Err(error!(ErrorCode::InvalidReferrer).into());
+        }
+        user_position.set_referrer(referrer)?;
+    }
+
     // All of these additions should be safe.
     user_position.yes_amount = user_position
         .yes_amount
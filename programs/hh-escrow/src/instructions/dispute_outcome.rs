@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, Market, Outcome, UserPosition};
+
+/// Allows a user with deposits in a [`Market`] to dispute the outcome set by
+/// its resolver, before the outcome is finalized.
+///
+/// A disputed outcome is no longer finalized by the resolution delay, but
+/// only by the arbiter of the global state, or at the end of the resolution
+/// window if the arbiter does not rule. If the arbiter overturns the outcome,
+/// the resolver bond is shared between the disputing users, pro rata to
+/// their stake.
+///
+/// The user posts the dispute bond of the global state in lamports, held by
+/// the position until [SettleDisputeBond](crate::instructions::SettleDisputeBond)
+/// refunds it, or forfeits it to the fee wallet if the arbiter upholds the
+/// outcome.
+#[derive(Accounts)]
+pub struct DisputeOutcome<'info> {
+    /// The global state account, holding the arbiter.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// The market whose outcome is disputed.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// The user's [UserPosition] account.
+    #[account(
+        mut,
+        seeds = [b"user", user.key_ref().as_ref(), market.key_ref().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// The user disputing the outcome, who posts the dispute bond.
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+}
+
+impl DisputeOutcome<'_> {
+    /// Checks that an arbiter can rule on the outcome, and returns the stake
+    /// of the position.
+    fn can_dispute(&mut self) -> Result<u64> {
+        if self.global_state.arbiter == Pubkey::default() {
+            return Err(error!(ErrorCode::NoArbiter));
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        self.market.set_and_check_finalize(now)?;
+        if self.market.outcome == Outcome::Open {
+            return Err(error!(ErrorCode::OutcomeNotSet));
+        }
+
+        if self.user_position.dispute_stake != 0 {
+            return Err(error!(ErrorCode::AlreadyDisputed));
+        }
+        let stake = self
+            .user_position
+            .yes_amount
+            .checked_add(self.user_position.no_amount)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+        if stake == 0 {
+            return Err(error!(ErrorCode::EmptyPosition));
+        }
+
+        Ok(stake)
+    }
+}
+
+pub fn handler(ctx: Context<DisputeOutcome>) -> ProgramResult {
+    let stake = ctx.accounts.can_dispute()?;
+
+    let market = &mut ctx.accounts.market;
+    market.disputed = true;
+    market.disputed_stake = market
+        .disputed_stake
+        .checked_add(stake)
+        .ok_or_else(|| error!(ErrorCode::Overflow))?;
+    ctx.accounts.user_position.dispute_stake = stake;
+
+    let bond = ctx.accounts.global_state.dispute_bond_lamports;
+    if bond > 0 {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.user.to_account_info(),
+                to: ctx.accounts.user_position.to_account_info(),
+            },
+        );
+        system_program::transfer(cpi_ctx, bond)?;
+        ctx.accounts.user_position.dispute_bond = bond;
+    }
+
+    Ok(())
+}
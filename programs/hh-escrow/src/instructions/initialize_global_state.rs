@@ -8,7 +8,8 @@ use crate::HhEscrow;
 /// Parameters for initializing the global state.
 #[derive(Clone, AnchorDeserialize, AnchorSerialize)]
 pub struct InitializeGlobalStateParams {
-    protocol_fee_bps: u16,
+    pub protocol_fee_bps: u16,
+    pub crank_tip_bps: u16,
 }
 
 /// Initializes a global state that holds protocol fee parameters.
@@ -48,8 +49,11 @@ pub fn handler(
 ) -> ProgramResult {
     let global_state = &mut ctx.accounts.global_state;
     
+    global_state.version = GlobalState::VERSION;
     global_state.fee_cut_bps =
         Bps::new(params.protocol_fee_bps).ok_or_else(|| error!(ErrorCode::FeeTooHigh))?;
+    global_state.crank_tip_bps =
+        Bps::new(params.crank_tip_bps).ok_or_else(|| error!(ErrorCode::FeeTooHigh))?;
     global_state.owner = ctx.accounts.global_state_owner.key();
     global_state.fee_wallet = ctx.accounts.fee_wallet.key();
 
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use solana_program::entrypoint::ProgramResult;
@@ -5,25 +7,58 @@ use solana_program::entrypoint::ProgramResult;
 use common::traits::KeyRef;
 
 use crate::error::ErrorCode;
-use crate::state::{Market, UriResource};
+use crate::state::{
+    DepositLimits, GlobalState, Market, MarketMetadata, MarketRegistry, MarketRegistryPage,
+    MarketTemplate, MintPolicy, CATEGORY_LEN, CREATOR_REGISTRY_SEED, MINT_REGISTRY_SEED,
+};
+use crate::utils::{post_creation_bond, register_market};
 
 /// Parameters for initializing a market.
 #[derive(Clone, AnchorDeserialize, AnchorSerialize)]
 pub struct InitializeMarketParams {
     /// Set the close time for this market.
-    close_ts: u64,
+    pub close_ts: u64,
     /// Sets the expiry time.
-    expiry_ts: u64,
+    pub expiry_ts: u64,
     /// Amount of seconds to wait until a resolution is final.
-    resolution_delay: u32,
+    pub resolution_delay: u32,
     /// The amount of tokens to put on the yes side of the market.
-    yes_amount: u64,
+    pub yes_amount: u64,
     /// The amount of tokens to put on the no side of the market.
-    no_amount: u64,
+    pub no_amount: u64,
     /// The resolver for this market.
-    resolver: Pubkey,
+    pub resolver: Pubkey,
     /// The URI that leads to the market info.
-    uri: String,
+    pub uri: String,
+    /// A short title for the market.
+    pub title: String,
+    /// The category tag of the market, as ASCII padded with trailing zeroes.
+    pub category: [u8; CATEGORY_LEN],
+    /// The hash of the off-chain JSON document found at `uri`.
+    pub content_hash: [u8; 32],
+    /// Limits on deposits into the yes side of the market.
+    pub yes_limits: DepositLimits,
+    /// Limits on deposits into the no side of the market.
+    pub no_limits: DepositLimits,
+    /// The Merkle root of the public keys allowed to deposit. If not set,
+    /// anyone can deposit.
+    pub allowlist_root: Option<[u8; 32]>,
+    /// If true, deposits are refused until the resolver acknowledges the
+    /// market, and the market is invalid if it is not acknowledged by the
+    /// close time.
+    pub require_acknowledgement: bool,
+    /// If true, the resolver may set the outcome to a side before the expiry
+    /// time, which closes deposits immediately. The resolution delay still
+    /// applies.
+    pub allow_early_resolution: bool,
+    /// The number of seconds after the expiry after which the outcome is
+    /// final, or the market invalid if no outcome was set. Must be within the
+    /// bounds set by the global state, and at least the resolution delay.
+    pub max_resolution_window: u32,
+    /// The bond the resolver must post in the token mint when acknowledging
+    /// the market, slashed if the arbiter overturns its outcome. Requires
+    /// `require_acknowledgement`.
+    pub resolver_bond: u64,
 }
 
 /// Initializes a [`Market`].
@@ -32,6 +67,9 @@ pub struct InitializeMarketParams {
 #[derive(Accounts)]
 #[instruction(params: InitializeMarketParams)]
 pub struct InitializeMarket<'info> {
+    /// The global state account, holding the market policy.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
     /// The market account to initialize.
     #[account(init, payer = creator, space = 8 + Market::LEN)]
     pub market: Account<'info, Market>,
@@ -45,6 +83,12 @@ pub struct InitializeMarket<'info> {
     pub creator: Signer<'info>,
     /// The token that this market is denominated in.
     pub token_mint: Box<Account<'info, Mint>>,
+    /// The minimum fill policy of the token mint.
+    ///
+    /// CHECK: Only read if it was initialized by this program, otherwise the
+    /// token mint has no minimum fill.
+    #[account(seeds = [b"mint_policy", token_mint.key_ref().as_ref()], bump)]
+    pub mint_policy: UncheckedAccount<'info>,
     /// Escrow for tokens on the yes side of the market.
     #[account(
         init,
@@ -65,6 +109,64 @@ pub struct InitializeMarket<'info> {
         bump,
     )]
     pub no_token_account: Box<Account<'info, TokenAccount>>,
+    /// Escrow for the creation bond, only created if the bond is denominated
+    /// in the token mint.
+    ///
+    /// CHECK: The address is checked by the seeds, and the account is created
+    /// as a token account if the bond is in the token mint.
+    #[account(mut, seeds = [b"bond", market.key_ref().as_ref()], bump)]
+    pub bond_token_account: UncheckedAccount<'info>,
+    /// The creator's token account paying the creation bond, if it is
+    /// denominated in the token mint.
+    ///
+    /// CHECK: Only used as the source of a transfer signed by the creator,
+    /// which the token program checks.
+    #[account(mut)]
+    pub creator_token_account: UncheckedAccount<'info>,
+    /// The registry of the creator's markets.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistry::LEN,
+        seeds = [b"registry", CREATOR_REGISTRY_SEED, creator.key_ref().as_ref()],
+        bump,
+    )]
+    pub creator_registry: Box<Account<'info, MarketRegistry>>,
+    /// The tail page of the creator's registry.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistryPage::LEN,
+        seeds = [
+            b"registry_page",
+            creator_registry.key_ref().as_ref(),
+            &creator_registry.tail_page.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub creator_registry_page: Box<Account<'info, MarketRegistryPage>>,
+    /// The registry of the token mint's markets.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistry::LEN,
+        seeds = [b"registry", MINT_REGISTRY_SEED, token_mint.key_ref().as_ref()],
+        bump,
+    )]
+    pub mint_registry: Box<Account<'info, MarketRegistry>>,
+    /// The tail page of the token mint's registry.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistryPage::LEN,
+        seeds = [
+            b"registry_page",
+            mint_registry.key_ref().as_ref(),
+            &mint_registry.tail_page.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub mint_registry_page: Box<Account<'info, MarketRegistryPage>>,
     /// The Solana System Program.
     pub system_program: Program<'info, System>,
     /// The SPL Token Program.
@@ -73,67 +175,283 @@ pub struct InitializeMarket<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
-impl InitializeMarket<'_> {
-    pub fn validate_params(&self, yes_amount: u64, no_amount: u64) -> Result<()> {
-        if yes_amount == 0 || no_amount == 0 {
+impl InitializeMarketParams {
+    /// Returns the parameters of the market spawned from a template at the
+    /// given index.
+    pub(crate) fn from_template(
+        template: &MarketTemplate,
+        index: u64,
+        (close_ts, expiry_ts): (u64, u64),
+        resolver: Pubkey,
+    ) -> Result<Self> {
+        let uri = template
+            .uri(index)
+            .ok_or_else(|| error!(ErrorCode::InvalidMarketResource))?;
+        let title = template
+            .title
+            .as_str()
+            .ok_or_else(|| error!(ErrorCode::InvalidMarketTitle))?
+            .to_string();
+
+        Ok(InitializeMarketParams {
+            close_ts,
+            expiry_ts,
+            resolution_delay: template.resolution_delay,
+            yes_amount: template.yes_amount,
+            no_amount: template.no_amount,
+            resolver,
+            uri,
+            title,
+            category: template.category,
+            content_hash: [0; 32],
+            yes_limits: template.yes_limits,
+            no_limits: template.no_limits,
+            allowlist_root: None,
+            require_acknowledgement: template.require_acknowledgement,
+            allow_early_resolution: template.allow_early_resolution,
+            max_resolution_window: template.max_resolution_window,
+            resolver_bond: template.resolver_bond,
+        })
+    }
+
+    pub fn validate_params(&self) -> Result<()> {
+        if self.yes_amount == 0 || self.no_amount == 0 {
             return Err(error!(ErrorCode::CannotHaveNonzeroAmounts));
         }
 
+        self.yes_limits.validate(self.yes_amount)?;
+        self.no_limits.validate(self.no_amount)?;
+
+        // A resolver that never acknowledges would never post its bond.
+        if self.resolver_bond > 0 && !self.require_acknowledgement {
+            return Err(error!(ErrorCode::ResolverBondRequiresAcknowledgement));
+        }
+
         Ok(())
     }
 
-    pub fn validate_ts(&self, close_ts: u64, expiry_ts: u64) -> Result<()> {
+    pub fn validate_ts(&self) -> Result<()> {
         let now = Clock::get()?.unix_timestamp as u64;
-        if close_ts < now {
+        if self.close_ts < now {
             return Err(error!(ErrorCode::InvalidCloseTimestamp));
         }
-        if expiry_ts < close_ts {
+        if self.expiry_ts < self.close_ts {
             return Err(error!(ErrorCode::InvalidExpiryTimestamp));
         }
 
         Ok(())
     }
+
+    /// Validates the parameters against the policy set by the global state,
+    /// and the minimum fill of the token mint.
+    pub fn validate_policy(&self, global_state: &GlobalState, min_fill: u64) -> Result<()> {
+        let (min_window, max_window) = global_state.resolution_window_bounds();
+        if self.max_resolution_window == 0
+            || self.max_resolution_window < min_window
+            || self.max_resolution_window > max_window
+        {
+            return Err(error!(ErrorCode::InvalidResolutionWindow));
+        }
+        if self.resolution_delay > self.max_resolution_window {
+            return Err(error!(ErrorCode::ResolutionDelayTooLong));
+        }
+
+        let policy = &global_state.market_policy;
+        let now = Clock::get()?.unix_timestamp as u64;
+        policy.check_schedule(now, self.close_ts, self.expiry_ts)?;
+        policy.check_resolution_delay(self.resolution_delay)?;
+        policy.check_uri(&self.uri)?;
+        if self.yes_amount < min_fill || self.no_amount < min_fill {
+            return Err(error!(ErrorCode::FillTooSmall));
+        }
+
+        Ok(())
+    }
+
+    pub fn validate_metadata(&self) -> Result<MarketMetadata> {
+        MarketMetadata::validate(&self.uri, &self.title, self.category, self.content_hash)
+    }
+
+    /// Validates the parameters and writes them to a newly created market.
+    #[allow(clippy::too_many_arguments)]
+    fn init_market(
+        self,
+        global_state: &GlobalState,
+        min_fill: u64,
+        market: &mut Market,
+        creator: Pubkey,
+        token_mint: Pubkey,
+        (yes_token_account, yes_account_bump): (Pubkey, u8),
+        (no_token_account, no_account_bump): (Pubkey, u8),
+    ) -> Result<()> {
+        // Exit early if timestamps or parameters are invalid.
+        self.validate_params()?;
+        self.validate_ts()?;
+        self.validate_policy(global_state, min_fill)?;
+
+        // Exit early if info is invalid.
+        market.metadata = self.validate_metadata()?;
+
+        let InitializeMarketParams {
+            close_ts,
+            expiry_ts,
+            resolution_delay,
+            yes_amount,
+            no_amount,
+            resolver,
+            yes_limits,
+            no_limits,
+            allowlist_root,
+            require_acknowledgement,
+            allow_early_resolution,
+            max_resolution_window,
+            resolver_bond,
+            ..
+        } = self;
+
+        market.version = Market::VERSION;
+        market.creator = creator;
+        market.resolver = resolver;
+        market.token_mint = token_mint;
+        market.yes_token_account = yes_token_account;
+        market.no_token_account = no_token_account;
+        market.yes_amount = yes_amount;
+        market.no_amount = no_amount;
+        market.close_ts = close_ts;
+        market.expiry_ts = expiry_ts;
+        market.outcome_ts = 0;
+        market.resolution_delay = resolution_delay;
+        market.yes_limits = yes_limits;
+        market.no_limits = no_limits;
+        market.allowlist_root = Market::allowlist_root(allowlist_root);
+        market.require_acknowledgement = require_acknowledgement;
+        market.allow_early_resolution = allow_early_resolution;
+        market.max_resolution_window = max_resolution_window;
+        market.resolver_bond = resolver_bond;
+        market.yes_account_bump = yes_account_bump;
+        market.no_account_bump = no_account_bump;
+
+        Ok(())
+    }
+}
+
+/// The accounts of a market being created, borrowed from the accounts of
+/// every instruction that creates a market.
+///
+/// Those instructions derive the market address differently, so each repeats
+/// the account constraints of [`InitializeMarket`] on its own accounts, and
+/// shares the creation itself through [`NewMarket::create`].
+pub(crate) struct NewMarket<'a, 'info> {
+    pub global_state: &'a GlobalState,
+    pub market: &'a mut Account<'info, Market>,
+    pub authority: &'a AccountInfo<'info>,
+    pub payer: &'a Signer<'info>,
+    pub payer_token_account: &'a AccountInfo<'info>,
+    pub token_mint: &'a Account<'info, Mint>,
+    pub mint_policy: &'a AccountInfo<'info>,
+    pub yes_token_account: &'a Account<'info, TokenAccount>,
+    pub no_token_account: &'a Account<'info, TokenAccount>,
+    pub bond_token_account: &'a AccountInfo<'info>,
+    pub creator_registry: &'a mut Account<'info, MarketRegistry>,
+    pub creator_registry_page: &'a mut Account<'info, MarketRegistryPage>,
+    pub mint_registry: &'a mut Account<'info, MarketRegistry>,
+    pub mint_registry_page: &'a mut Account<'info, MarketRegistryPage>,
+    pub system_program: &'a Program<'info, System>,
+    pub token_program: &'a Program<'info, Token>,
+    pub rent: &'a Sysvar<'info, Rent>,
+}
+
+impl NewMarket<'_, '_> {
+    /// Validates the parameters and writes them to the market, escrows the
+    /// creation bond paid by the payer, and registers the market with its
+    /// creator and token mint.
+    pub fn create(
+        self,
+        params: InitializeMarketParams,
+        creator: Pubkey,
+        bumps: &BTreeMap<String, u8>,
+    ) -> Result<()> {
+        let bump = |name: &str| {
+            bumps
+                .get(name)
+                .copied()
+                .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))
+        };
+
+        let mint_policy = MintPolicy::load(self.mint_policy)?;
+        params.init_market(
+            self.global_state,
+            mint_policy.min_fill,
+            self.market,
+            creator,
+            self.token_mint.key(),
+            (self.yes_token_account.key(), bump("yes_token_account")?),
+            (self.no_token_account.key(), bump("no_token_account")?),
+        )?;
+
+        post_creation_bond(
+            self.market,
+            self.global_state,
+            &mint_policy,
+            self.payer,
+            self.payer_token_account,
+            (self.bond_token_account, bump("bond_token_account")?),
+            &self.token_mint.to_account_info(),
+            self.authority,
+            self.token_program,
+            self.system_program,
+            self.rent,
+        )?;
+
+        // Index the market by its creator and token mint.
+        let market = self.market.key();
+        register_market(
+            self.creator_registry,
+            self.creator_registry_page,
+            creator,
+            market,
+        )?;
+        register_market(
+            self.mint_registry,
+            self.mint_registry_page,
+            self.token_mint.key(),
+            market,
+        )?;
+        self.market.registered = true;
+
+        Ok(())
+    }
+}
+
+impl<'info> InitializeMarket<'info> {
+    fn new_market(&mut self) -> NewMarket<'_, 'info> {
+        NewMarket {
+            global_state: &self.global_state,
+            market: &mut self.market,
+            authority: &self.authority,
+            payer: &self.creator,
+            payer_token_account: &self.creator_token_account,
+            token_mint: &self.token_mint,
+            mint_policy: &self.mint_policy,
+            yes_token_account: &self.yes_token_account,
+            no_token_account: &self.no_token_account,
+            bond_token_account: &self.bond_token_account,
+            creator_registry: &mut self.creator_registry,
+            creator_registry_page: &mut self.creator_registry_page,
+            mint_registry: &mut self.mint_registry,
+            mint_registry_page: &mut self.mint_registry_page,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            rent: &self.rent,
+        }
+    }
 }
 
 pub fn handler(ctx: Context<InitializeMarket>, params: InitializeMarketParams) -> ProgramResult {
-    let InitializeMarketParams {
-        close_ts,
-        expiry_ts,
-        resolution_delay,
-        uri,
-        yes_amount,
-        no_amount,
-        resolver,
-    } = params;
-
-    // Exit early if timestamps or parameters are invalid.
-    ctx.accounts.validate_params(yes_amount, no_amount)?;
-    ctx.accounts.validate_ts(close_ts, expiry_ts)?;
-
-    let market = &mut ctx.accounts.market;
-
-    // Exit early if info is invalid.
-    market.uri = UriResource::validate(&uri)?;
-
-    market.creator = ctx.accounts.creator.key();
-    market.resolver = resolver.key();
-    market.token_mint = ctx.accounts.token_mint.key();
-    market.yes_token_account = ctx.accounts.yes_token_account.key();
-    market.no_token_account = ctx.accounts.no_token_account.key();
-    market.yes_amount = yes_amount;
-    market.no_amount = no_amount;
-    market.close_ts = close_ts;
-    market.expiry_ts = expiry_ts;
-    market.outcome_ts = 0;
-    market.resolution_delay = resolution_delay;
-    market.yes_account_bump = *ctx
-        .bumps
-        .get("yes_token_account")
-        .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
-    market.no_account_bump = *ctx
-        .bumps
-        .get("no_token_account")
-        .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
+    let creator = ctx.accounts.creator.key();
+    ctx.accounts
+        .new_market()
+        .create(params, creator, &ctx.bumps)?;
 
     Ok(())
 }
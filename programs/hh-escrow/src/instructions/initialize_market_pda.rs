@@ -0,0 +1,189 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::instructions::{InitializeMarketParams, NewMarket};
+use crate::state::{
+    GlobalState, Market, MarketCounter, MarketRegistry, MarketRegistryPage, CREATOR_REGISTRY_SEED,
+    MINT_REGISTRY_SEED,
+};
+
+/// Initializes a [`Market`] at the PDA `[b"market", creator, nonce]`, where
+/// the nonce is handed out by the creator's [`MarketCounter`].
+///
+/// Otherwise the same as [`InitializeMarket`](crate::instructions::InitializeMarket),
+/// but without requiring the market to sign.
+#[derive(Accounts)]
+#[instruction(params: InitializeMarketParams)]
+pub struct InitializeMarketPda<'info> {
+    /// The global state account, holding the market policy.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    /// The creator's market counter, initialized on the first market.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketCounter::LEN,
+        seeds = [b"counter", creator.key_ref().as_ref()],
+        bump,
+    )]
+    pub market_counter: Box<Account<'info, MarketCounter>>,
+    /// The market account to initialize.
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Market::LEN,
+        seeds = [
+            b"market",
+            creator.key_ref().as_ref(),
+            &market_counter.next_nonce.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// The authority for the two token accounts.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"authority", market.key_ref().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    /// The creator for the market.
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    /// The token that this market is denominated in.
+    pub token_mint: Box<Account<'info, Mint>>,
+    /// The minimum fill policy of the token mint.
+    ///
+    /// CHECK: Only read if it was initialized by this program, otherwise the
+    /// token mint has no minimum fill.
+    #[account(seeds = [b"mint_policy", token_mint.key_ref().as_ref()], bump)]
+    pub mint_policy: UncheckedAccount<'info>,
+    /// Escrow for tokens on the yes side of the market.
+    #[account(
+        init,
+        payer = creator,
+        token::mint = token_mint,
+        token::authority = authority,
+        seeds = [b"yes", market.key_ref().as_ref()],
+        bump,
+    )]
+    pub yes_token_account: Box<Account<'info, TokenAccount>>,
+    /// Escrow for tokens on the no side of the market.
+    #[account(
+        init,
+        payer = creator,
+        token::mint = token_mint,
+        token::authority = authority,
+        seeds = [b"no", market.key_ref().as_ref()],
+        bump,
+    )]
+    pub no_token_account: Box<Account<'info, TokenAccount>>,
+    /// Escrow for the creation bond, only created if the bond is denominated
+    /// in the token mint.
+    ///
+    /// CHECK: The address is checked by the seeds, and the account is created
+    /// as a token account if the bond is in the token mint.
+    #[account(mut, seeds = [b"bond", market.key_ref().as_ref()], bump)]
+    pub bond_token_account: UncheckedAccount<'info>,
+    /// The creator's token account paying the creation bond, if it is
+    /// denominated in the token mint.
+    ///
+    /// CHECK: Only used as the source of a transfer signed by the creator,
+    /// which the token program checks.
+    #[account(mut)]
+    pub creator_token_account: UncheckedAccount<'info>,
+    /// The registry of the creator's markets.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistry::LEN,
+        seeds = [b"registry", CREATOR_REGISTRY_SEED, creator.key_ref().as_ref()],
+        bump,
+    )]
+    pub creator_registry: Box<Account<'info, MarketRegistry>>,
+    /// The tail page of the creator's registry.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistryPage::LEN,
+        seeds = [
+            b"registry_page",
+            creator_registry.key_ref().as_ref(),
+            &creator_registry.tail_page.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub creator_registry_page: Box<Account<'info, MarketRegistryPage>>,
+    /// The registry of the token mint's markets.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistry::LEN,
+        seeds = [b"registry", MINT_REGISTRY_SEED, token_mint.key_ref().as_ref()],
+        bump,
+    )]
+    pub mint_registry: Box<Account<'info, MarketRegistry>>,
+    /// The tail page of the token mint's registry.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + MarketRegistryPage::LEN,
+        seeds = [
+            b"registry_page",
+            mint_registry.key_ref().as_ref(),
+            &mint_registry.tail_page.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub mint_registry_page: Box<Account<'info, MarketRegistryPage>>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+    /// The SPL Token Program.
+    pub token_program: Program<'info, Token>,
+    /// The Sysvar rent.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> InitializeMarketPda<'info> {
+    fn new_market(&mut self) -> NewMarket<'_, 'info> {
+        NewMarket {
+            global_state: &self.global_state,
+            market: &mut self.market,
+            authority: &self.authority,
+            payer: &self.creator,
+            payer_token_account: &self.creator_token_account,
+            token_mint: &self.token_mint,
+            mint_policy: &self.mint_policy,
+            yes_token_account: &self.yes_token_account,
+            no_token_account: &self.no_token_account,
+            bond_token_account: &self.bond_token_account,
+            creator_registry: &mut self.creator_registry,
+            creator_registry_page: &mut self.creator_registry_page,
+            mint_registry: &mut self.mint_registry,
+            mint_registry_page: &mut self.mint_registry_page,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            rent: &self.rent,
+        }
+    }
+}
+
+pub fn handler(ctx: Context<InitializeMarketPda>, params: InitializeMarketParams) -> ProgramResult {
+    let creator = ctx.accounts.creator.key();
+    ctx.accounts
+        .new_market()
+        .create(params, creator, &ctx.bumps)?;
+
+    // Hand out the next nonce.
+    let market_counter = &mut ctx.accounts.market_counter;
+    market_counter.version = MarketCounter::VERSION;
+    market_counter.creator = ctx.accounts.creator.key();
+    market_counter.next_nonce = market_counter
+        .next_nonce
+        .checked_add(1)
+        .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+    Ok(())
+}
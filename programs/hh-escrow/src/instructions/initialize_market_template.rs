@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::instructions::InitializeMarketParams;
+use crate::state::{
+    DepositLimits, GlobalState, MarketTemplate, MarketTitle, MintPolicy, UriResource,
+    CATEGORY_LEN, RESOLVER_DATA_MAX_LEN,
+};
+
+/// Parameters for initializing a market template.
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct InitializeMarketTemplateParams {
+    /// The close time of the first market.
+    pub close_ts: u64,
+    /// The expiry time of the first market.
+    pub expiry_ts: u64,
+    /// The number of seconds between two consecutive markets.
+    pub cadence: u64,
+    /// Amount of seconds to wait until a resolution is final.
+    pub resolution_delay: u32,
+    /// The amount of tokens to put on the yes side of each market.
+    pub yes_amount: u64,
+    /// The amount of tokens to put on the no side of each market.
+    pub no_amount: u64,
+    /// The resolver for each market. Ignored if `resolver_program` is set.
+    pub resolver: Pubkey,
+    /// The program invoked with `resolver_data` to initialize the resolver of
+    /// each market.
+    pub resolver_program: Option<Pubkey>,
+    /// The instruction data passed to `resolver_program`.
+    pub resolver_data: Vec<u8>,
+    /// The prefix of the URI of each market, followed by the market index.
+    pub uri_prefix: String,
+    /// A short title for each market.
+    pub title: String,
+    /// The category tag of each market, as ASCII padded with trailing zeroes.
+    pub category: [u8; CATEGORY_LEN],
+    /// Limits on deposits into the yes side of each market.
+    pub yes_limits: DepositLimits,
+    /// Limits on deposits into the no side of each market.
+    pub no_limits: DepositLimits,
+    /// If true, deposits are refused until the resolver acknowledges a market.
+    pub require_acknowledgement: bool,
+    /// If true, the resolver may resolve a market before its expiry time.
+    pub allow_early_resolution: bool,
+    /// The resolution window of each market, in seconds after its expiry.
+    pub max_resolution_window: u32,
+    /// The bond the resolver of each market posts when acknowledging it.
+    /// Requires acknowledgement if nonzero.
+    pub resolver_bond: u64,
+}
+
+/// Initializes a [`MarketTemplate`], from which markets can be spawned with
+/// [`SpawnMarketFromTemplate`](crate::instructions::SpawnMarketFromTemplate).
+///
+/// The resolver program of the template, if any, must be the template
+/// resolver program allowed by the global state.
+#[derive(Accounts)]
+#[instruction(params: InitializeMarketTemplateParams)]
+pub struct InitializeMarketTemplate<'info> {
+    /// The global state account, holding the market policy.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    /// The template account to initialize.
+    #[account(init, payer = creator, space = 8 + MarketTemplate::LEN)]
+    pub template: Box<Account<'info, MarketTemplate>>,
+    /// The creator of the template.
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    /// The token that the spawned markets are denominated in.
+    pub token_mint: Account<'info, Mint>,
+    /// The minimum fill policy of the token mint.
+    ///
+    /// CHECK: Only read if it was initialized by this program, otherwise the
+    /// token mint has no minimum fill.
+    #[account(seeds = [b"mint_policy", token_mint.key_ref().as_ref()], bump)]
+    pub mint_policy: UncheckedAccount<'info>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeMarketTemplate>,
+    params: InitializeMarketTemplateParams,
+) -> ProgramResult {
+    let InitializeMarketTemplateParams {
+        close_ts,
+        expiry_ts,
+        cadence,
+        resolution_delay,
+        yes_amount,
+        no_amount,
+        resolver,
+        resolver_program,
+        resolver_data,
+        uri_prefix,
+        title,
+        category,
+        yes_limits,
+        no_limits,
+        require_acknowledgement,
+        allow_early_resolution,
+        max_resolution_window,
+        resolver_bond,
+    } = params;
+
+    if cadence == 0 {
+        return Err(error!(ErrorCode::InvalidTemplateCadence).into());
+    }
+    if resolver_data.len() > RESOLVER_DATA_MAX_LEN {
+        return Err(error!(ErrorCode::InvalidResolverData).into());
+    }
+    if let Some(resolver_program) = resolver_program {
+        if resolver_program != ctx.accounts.global_state.template_resolver_program {
+            return Err(error!(ErrorCode::ResolverProgramNotAllowed).into());
+        }
+    }
+
+    let template = &mut ctx.accounts.template;
+
+    template.version = MarketTemplate::VERSION;
+    template.creator = ctx.accounts.creator.key();
+    template.token_mint = ctx.accounts.token_mint.key();
+    template.resolver = resolver;
+    template.resolver_program = resolver_program.unwrap_or_default();
+    template.yes_amount = yes_amount;
+    template.no_amount = no_amount;
+    template.yes_limits = yes_limits;
+    template.no_limits = no_limits;
+    template.resolution_delay = resolution_delay;
+    template.require_acknowledgement = require_acknowledgement;
+    template.allow_early_resolution = allow_early_resolution;
+    template.max_resolution_window = max_resolution_window;
+    template.close_ts = close_ts;
+    template.expiry_ts = expiry_ts;
+    template.cadence = cadence;
+    template.spawned = 0;
+    template.category = category;
+    template.title = MarketTitle::validate(&title)?;
+    template.uri_prefix = UriResource::validate(&uri_prefix)?;
+    template.resolver_data_len = resolver_data.len() as u16;
+    template.resolver_data[..resolver_data.len()].copy_from_slice(&resolver_data);
+    template.resolver_bond = resolver_bond;
+
+    // Check that the first market is valid, and that every market URI fits.
+    let first = InitializeMarketParams::from_template(
+        template,
+        u64::MAX,
+        (close_ts, expiry_ts),
+        resolver,
+    )?;
+    first.validate_params()?;
+    first.validate_ts()?;
+    first.validate_policy(
+        &ctx.accounts.global_state,
+        MintPolicy::load(&ctx.accounts.mint_policy)?.min_fill,
+    )?;
+    first.validate_metadata()?;
+
+    Ok(())
+}
@@ -28,7 +28,9 @@ pub struct InitializeUserPosition<'info> {
 
 pub fn handler(ctx: Context<InitializeUserPosition>) -> ProgramResult {
     let user_position = &mut ctx.accounts.user_position;
+    user_position.version = UserPosition::VERSION;
     user_position.market = ctx.accounts.market.key();
+    ctx.accounts.market.open_position()?;
 
     Ok(())
 }
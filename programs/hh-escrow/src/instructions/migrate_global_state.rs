@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::state::GlobalState;
+use crate::utils::migrate_account;
+
+/// Migrates an unversioned [GlobalState] account to the versioned layout.
+///
+/// Anyone can migrate an account, as the migration keeps all of its fields,
+/// and new fields take their default value.
+#[derive(Accounts)]
+pub struct MigrateGlobalState<'info> {
+    /// The unversioned [GlobalState] account to migrate.
+    ///
+    /// CHECK: The account cannot be deserialized before the migration. The
+    /// owner and discriminator are checked by the migration.
+    #[account(mut, seeds = [b"global"], bump)]
+    pub global_state: UncheckedAccount<'info>,
+    /// Payer for the additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateGlobalState>) -> ProgramResult {
+    migrate_account::<GlobalState>(
+        &ctx.accounts.global_state,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        GlobalState::LEN,
+        GlobalState::from_unversioned,
+    )?;
+
+    Ok(())
+}
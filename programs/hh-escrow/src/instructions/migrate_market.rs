@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, Market};
+use crate::utils::migrate_account;
+
+/// Parameters for the [MigrateMarket] instruction.
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct MigrateMarketParams {
+    /// The number of unversioned [UserPosition](crate::state::UserPosition)
+    /// accounts of the market. Unversioned markets do not count their
+    /// positions, so the count is taken off-chain.
+    pub positions: u64,
+}
+
+/// Migrates an unversioned [Market] account to the versioned layout.
+///
+/// The migration keeps all of the fields, and new fields take their default
+/// value. Only the owner of the global state can migrate a market, as it
+/// vouches for the number of user positions, which must all be migrated
+/// before the market can be closed.
+#[derive(Accounts)]
+pub struct MigrateMarket<'info> {
+    /// The unversioned [Market] account to migrate.
+    ///
+    /// CHECK: The account cannot be deserialized before the migration. The
+    /// owner and discriminator are checked by the migration.
+    #[account(mut)]
+    pub market: UncheckedAccount<'info>,
+    /// The global state account, which must have been migrated.
+    #[account(
+        seeds = [b"global"],
+        bump,
+        has_one = owner @ ErrorCode::IncorrectGlobalStateOwner,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    /// The owner of the global state.
+    pub owner: Signer<'info>,
+    /// Payer for the additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateMarket>, params: MigrateMarketParams) -> ProgramResult {
+    migrate_account::<Market>(
+        &ctx.accounts.market,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        Market::LEN,
+        |data| {
+            let mut market = Market::from_unversioned(data)?;
+            market.unmigrated_positions = params.positions;
+
+            Ok(market)
+        },
+    )?;
+
+    Ok(())
+}
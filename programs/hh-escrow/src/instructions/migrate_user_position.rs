@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::error::ErrorCode;
+use crate::state::{deserialize_v0, Market, UserPosition, UserPositionV0};
+use crate::utils::migrate_account;
+
+/// Migrates an unversioned [UserPosition] account to the versioned layout,
+/// counting it as an open position of its market rather than an unmigrated
+/// one.
+///
+/// Anyone can migrate an account, as the migration keeps all of its fields,
+/// and new fields take their default value.
+#[derive(Accounts)]
+pub struct MigrateUserPosition<'info> {
+    /// The unversioned [UserPosition] account to migrate.
+    ///
+    /// CHECK: The account cannot be deserialized before the migration. The
+    /// owner and discriminator are checked by the migration.
+    #[account(mut)]
+    pub user_position: UncheckedAccount<'info>,
+    /// The [Market] of the position, which must have been migrated.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// Payer for the additional rent.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<MigrateUserPosition>) -> ProgramResult {
+    let market = ctx.accounts.market.key();
+    migrate_account::<UserPosition>(
+        &ctx.accounts.user_position,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+        UserPosition::LEN,
+        |data| {
+            let user_position = UserPosition::from(deserialize_v0::<UserPositionV0>(data)?);
+            if user_position.market != market {
+                return Err(error!(ErrorCode::IncorrectMarket));
+            }
+
+            Ok(user_position)
+        },
+    )?;
+    ctx.accounts.market.migrate_position()?;
+
+    Ok(())
+}
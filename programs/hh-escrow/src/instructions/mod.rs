@@ -1,19 +1,59 @@
+pub mod arbitrate;
 pub mod claim;
+pub mod claim_slashed_bond;
+pub mod close_market;
+pub mod close_user_position;
+pub mod collect_fees;
 pub mod deposit;
+pub mod dispute_outcome;
 pub mod initialize_global_state;
 pub mod initialize_market;
+pub mod initialize_market_pda;
+pub mod initialize_market_template;
 pub mod initialize_user_position;
+pub mod migrate_global_state;
+pub mod migrate_market;
+pub mod migrate_user_position;
 pub mod resolver_acknowledge;
+pub mod set_allowlist_root;
 pub mod set_global_state;
+pub mod set_market_policy;
+pub mod set_mint_policy;
+pub mod set_resolver;
+pub mod settle_creation_bond;
+pub mod settle_dispute_bond;
+pub mod settle_position;
+pub mod settle_resolver_bond;
+pub mod spawn_market_from_template;
 pub mod update_state;
 pub mod withdraw;
 
+pub use self::arbitrate::*;
 pub use self::claim::*;
+pub use self::claim_slashed_bond::*;
+pub use self::close_market::*;
+pub use self::close_user_position::*;
+pub use self::collect_fees::*;
 pub use self::deposit::*;
+pub use self::dispute_outcome::*;
 pub use self::initialize_global_state::*;
 pub use self::initialize_market::*;
+pub use self::initialize_market_pda::*;
+pub use self::initialize_market_template::*;
 pub use self::initialize_user_position::*;
+pub use self::migrate_global_state::*;
+pub use self::migrate_market::*;
+pub use self::migrate_user_position::*;
 pub use self::resolver_acknowledge::*;
+pub use self::set_allowlist_root::*;
 pub use self::set_global_state::*;
+pub use self::set_market_policy::*;
+pub use self::set_mint_policy::*;
+pub use self::set_resolver::*;
+pub use self::settle_creation_bond::*;
+pub use self::settle_dispute_bond::*;
+pub use self::settle_position::*;
+pub use self::settle_resolver_bond::*;
+pub use self::spawn_market_from_template::*;
 pub use self::update_state::*;
 pub use self::withdraw::*;
@@ -1,25 +1,108 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
 use solana_program::entrypoint::ProgramResult;
 
+use common::traits::KeyRef;
+
 use crate::error::ErrorCode;
-use crate::state::{Market};
+use crate::state::Market;
+use crate::utils::{create_escrow_token_account, non_signer_transfer};
 
 /// Allows the resolver to acknowledge a given market.
+///
+/// If the market requires a resolver bond, it is posted into the resolver
+/// bond escrow the first time the market is acknowledged. The bond accounts
+/// are then passed in the remaining accounts, in order:
+///
+/// 0. The authority for the market token accounts.
+/// 1. The token that the market is denominated in.
+/// 2. The resolver bond escrow at `[b"resolver_bond", market]`, created by
+///    this instruction.
+/// 3. The bond payer, signing for the bond and paying for its escrow.
+/// 4. The token account of the bond payer paying the bond.
+/// 5. The Solana System Program.
+/// 6. The SPL Token Program.
+/// 7. The Sysvar rent.
 #[derive(Accounts)]
 pub struct ResolverAcknowledge<'info> {
     /// The market account to acknowledge.
     #[account(mut, has_one = resolver @ ErrorCode::IncorrectResolver)]
-    pub market: Account<'info, Market>,
+    pub market: Box<Account<'info, Market>>,
     /// The resolver for the market.
     pub resolver: Signer<'info>,
 }
 
-pub fn handler(
-    ctx: Context<ResolverAcknowledge>,
+impl<'info> ResolverAcknowledge<'info> {
+    /// Escrows the resolver bond from the bond accounts, returning the bond
+    /// payer.
+    fn post_resolver_bond(&self, bond_accounts: &[AccountInfo<'info>]) -> Result<Pubkey> {
+        if bond_accounts.len() != 8 {
+            return Err(error!(ErrorCode::InvalidResolverBondAccounts));
+        }
+        let authority = &bond_accounts[0];
+        let token_mint = &bond_accounts[1];
+        let resolver_bond_token_account = &bond_accounts[2];
+        let bond_payer = &bond_accounts[3];
+        let bond_payer_token_account = &bond_accounts[4];
+
+        let market_key = self.market.key_ref();
+        let (key, _) =
+            Pubkey::find_program_address(&[b"authority", market_key.as_ref()], &crate::ID);
+        if key != *authority.key {
+            return Err(error!(ErrorCode::IncorrectMarketAuthority));
+        }
+        if *token_mint.key != self.market.token_mint {
+            return Err(error!(ErrorCode::IncorrectTokenMint));
+        }
+        let (key, bump) =
+            Pubkey::find_program_address(&[b"resolver_bond", market_key.as_ref()], &crate::ID);
+        if key != *resolver_bond_token_account.key {
+            return Err(error!(ErrorCode::IncorrectResolverBondEscrow));
+        }
+        if !bond_payer.is_signer {
+            return Err(error!(ErrorCode::InvalidResolverBondAccounts));
+        }
+
+        let system_program = Program::<System>::try_from(&bond_accounts[5])?;
+        let token_program = Program::<Token>::try_from(&bond_accounts[6])?;
+        let rent = Sysvar::<Rent>::from_account_info(&bond_accounts[7])?;
+
+        create_escrow_token_account(
+            bond_payer,
+            resolver_bond_token_account,
+            &[b"resolver_bond", market_key.as_ref(), &[bump]],
+            token_mint,
+            authority,
+            &token_program,
+            &system_program,
+            &rent,
+        )?;
+        non_signer_transfer(
+            &token_program,
+            bond_payer_token_account,
+            resolver_bond_token_account,
+            bond_payer,
+            self.market.resolver_bond,
+        )?;
+
+        Ok(*bond_payer.key)
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ResolverAcknowledge<'info>>,
 ) -> ProgramResult {
-    let market = &mut ctx.accounts.market;
+    // The bond is only posted once.
+    if ctx.accounts.market.acknowledged {
+        return Ok(());
+    }
+
+    if ctx.accounts.market.resolver_bond > 0 {
+        let bond_payer = ctx.accounts.post_resolver_bond(ctx.remaining_accounts)?;
+        ctx.accounts.market.resolver_bond_payer = bond_payer;
+    }
 
-    market.acknowledged = true;
+    ctx.accounts.market.acknowledged = true;
 
     Ok(())
 }
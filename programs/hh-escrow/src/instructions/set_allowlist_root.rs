@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::error::ErrorCode;
+use crate::state::Market;
+
+/// Parameters for the [SetAllowlistRoot] instruction.
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct SetAllowlistRootParams {
+    /// The new Merkle root of the public keys allowed to deposit. If not set,
+    /// anyone can deposit.
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+/// Allows the creator to rotate the allowlist of a market before it closes.
+#[derive(Accounts)]
+#[instruction(params: SetAllowlistRootParams)]
+pub struct SetAllowlistRoot<'info> {
+    /// The market to update.
+    #[account(mut, has_one = creator @ ErrorCode::IncorrectCreator)]
+    pub market: Account<'info, Market>,
+    /// The creator of the market.
+    pub creator: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetAllowlistRoot>, params: SetAllowlistRootParams) -> ProgramResult {
+    let SetAllowlistRootParams { allowlist_root } = params;
+
+    let now = Clock::get()?.unix_timestamp as u64;
+    if ctx.accounts.market.close_ts <= now {
+        return Err(error!(ErrorCode::MarketClosed).into());
+    }
+
+    let market = &mut ctx.accounts.market;
+    market.allowlist_root = Market::allowlist_root(allowlist_root);
+
+    Ok(())
+}
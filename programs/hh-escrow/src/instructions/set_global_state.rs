@@ -4,11 +4,23 @@ use solana_program::entrypoint::ProgramResult;
 use crate::error::ErrorCode;
 use crate::state::{Bps, GlobalState};
 
+/// Parameters for the [SetGlobalState] instruction.
+///
+/// Settings added after the owner, fee cut and fee wallet are optional, and
+/// left unchanged if `None`.
 #[derive(Clone, AnchorDeserialize, AnchorSerialize)]
 pub struct SetGlobalStateParams {
-    new_owner: Pubkey,
-    new_fee_cut_bps: u16,
-    new_fee_wallet: Pubkey,
+    pub new_owner: Pubkey,
+    pub new_fee_cut_bps: u16,
+    pub new_fee_wallet: Pubkey,
+    pub new_crank_tip_bps: Option<u16>,
+    pub new_referral_bps: Option<u16>,
+    pub new_min_resolution_window: Option<u32>,
+    pub new_max_resolution_window: Option<u32>,
+    pub new_creation_bond_lamports: Option<u64>,
+    pub new_arbiter: Option<Pubkey>,
+    pub new_dispute_bond_lamports: Option<u64>,
+    pub new_template_resolver_program: Option<Pubkey>,
 }
 
 #[derive(Accounts)]
@@ -30,11 +42,52 @@ pub fn handler(ctx: Context<SetGlobalState>, params: SetGlobalStateParams) -> Pr
         new_owner,
         new_fee_cut_bps,
         new_fee_wallet,
+        new_crank_tip_bps,
+        new_referral_bps,
+        new_min_resolution_window,
+        new_max_resolution_window,
+        new_creation_bond_lamports,
+        new_arbiter,
+        new_dispute_bond_lamports,
+        new_template_resolver_program,
     } = params;
     let global_state = &mut ctx.accounts.global_state;
 
     global_state.fee_cut_bps =
         Bps::new(new_fee_cut_bps).ok_or_else(|| error!(ErrorCode::FeeTooHigh))?;
+    if let Some(new_crank_tip_bps) = new_crank_tip_bps {
+        global_state.crank_tip_bps =
+            Bps::new(new_crank_tip_bps).ok_or_else(|| error!(ErrorCode::FeeTooHigh))?;
+    }
+    if let Some(new_referral_bps) = new_referral_bps {
+        global_state.referral_bps =
+            Bps::new(new_referral_bps).ok_or_else(|| error!(ErrorCode::FeeTooHigh))?;
+    }
+    if let Some(new_min_resolution_window) = new_min_resolution_window {
+        global_state.min_resolution_window = new_min_resolution_window;
+    }
+    if let Some(new_max_resolution_window) = new_max_resolution_window {
+        if new_max_resolution_window == 0 {
+            return Err(error!(ErrorCode::InvalidResolutionWindowBounds).into());
+        }
+        global_state.max_resolution_window = new_max_resolution_window;
+    }
+    let (min_resolution_window, max_resolution_window) = global_state.resolution_window_bounds();
+    if min_resolution_window > max_resolution_window {
+        return Err(error!(ErrorCode::InvalidResolutionWindowBounds).into());
+    }
+    if let Some(new_creation_bond_lamports) = new_creation_bond_lamports {
+        global_state.creation_bond_lamports = new_creation_bond_lamports;
+    }
+    if let Some(new_arbiter) = new_arbiter {
+        global_state.arbiter = new_arbiter;
+    }
+    if let Some(new_dispute_bond_lamports) = new_dispute_bond_lamports {
+        global_state.dispute_bond_lamports = new_dispute_bond_lamports;
+    }
+    if let Some(new_template_resolver_program) = new_template_resolver_program {
+        global_state.template_resolver_program = new_template_resolver_program;
+    }
     global_state.owner = new_owner;
     global_state.fee_wallet = new_fee_wallet;
 
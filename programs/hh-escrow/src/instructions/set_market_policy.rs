@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, MarketPolicy};
+
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct SetMarketPolicyParams {
+    pub new_market_policy: MarketPolicy,
+}
+
+/// Sets the limits on the parameters of new markets.
+#[derive(Accounts)]
+#[instruction(params: SetMarketPolicyParams)]
+pub struct SetMarketPolicy<'info> {
+    /// The global state account.
+    #[account(
+        mut,
+        seeds = [b"global"],
+        bump,
+        has_one = owner @ ErrorCode::IncorrectGlobalStateOwner,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<SetMarketPolicy>, params: SetMarketPolicyParams) -> ProgramResult {
+    let SetMarketPolicyParams { new_market_policy } = params;
+    new_market_policy.validate()?;
+
+    ctx.accounts.global_state.market_policy = new_market_policy;
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, MintPolicy};
+
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct SetMintPolicyParams {
+    pub new_min_fill: u64,
+    pub new_creation_bond: u64,
+}
+
+/// Sets the limits on new markets denominated in a token mint, initializing
+/// the [`MintPolicy`] if needed.
+#[derive(Accounts)]
+#[instruction(params: SetMintPolicyParams)]
+pub struct SetMintPolicy<'info> {
+    /// The global state account.
+    #[account(
+        seeds = [b"global"],
+        bump,
+        has_one = owner @ ErrorCode::IncorrectGlobalStateOwner,
+    )]
+    pub global_state: Account<'info, GlobalState>,
+    pub owner: Signer<'info>,
+    /// The token mint the policy applies to.
+    pub token_mint: Account<'info, Mint>,
+    /// The policy of the token mint.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MintPolicy::LEN,
+        seeds = [b"mint_policy", token_mint.key_ref().as_ref()],
+        bump,
+    )]
+    pub mint_policy: Account<'info, MintPolicy>,
+    /// The account paying for the policy account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<SetMintPolicy>, params: SetMintPolicyParams) -> ProgramResult {
+    let SetMintPolicyParams {
+        new_min_fill,
+        new_creation_bond,
+    } = params;
+
+    let mint_policy = &mut ctx.accounts.mint_policy;
+
+    mint_policy.version = MintPolicy::VERSION;
+    mint_policy.token_mint = ctx.accounts.token_mint.key();
+    mint_policy.min_fill = new_min_fill;
+    mint_policy.creation_bond = new_creation_bond;
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{Market, UserPosition};
+
+/// Parameters for the [SetResolver] instruction.
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct SetResolverParams {
+    /// The new resolver for the market.
+    pub resolver: Pubkey,
+}
+
+/// Allows the creator to reassign the resolver of a market before the resolver
+/// acknowledges it.
+#[derive(Accounts)]
+#[instruction(params: SetResolverParams)]
+pub struct SetResolver<'info> {
+    /// The market to update.
+    #[account(mut, has_one = creator @ ErrorCode::IncorrectCreator)]
+    pub market: Account<'info, Market>,
+    /// The creator's [UserPosition] account for the market, which may not be
+    /// initialized.
+    ///
+    /// CHECK: The address is checked by the seeds constraint, and the data is
+    /// only deserialized if the account has been initialized.
+    #[account(
+        seeds = [b"user", creator.key_ref().as_ref(), market.key_ref().as_ref()],
+        bump
+    )]
+    pub creator_position: UncheckedAccount<'info>,
+    /// The creator of the market.
+    pub creator: Signer<'info>,
+}
+
+impl SetResolver<'_> {
+    pub fn can_set_resolver(&self) -> Result<()> {
+        if self.market.acknowledged {
+            return Err(error!(ErrorCode::AlreadyAcknowledged));
+        }
+
+        // Only deposits made by the creator are allowed.
+        let (yes_amount, no_amount) = if self.creator_position.data_is_empty() {
+            (0, 0)
+        } else {
+            let position: Account<UserPosition> = Account::try_from(&self.creator_position)?;
+            (position.yes_amount, position.no_amount)
+        };
+        if self.market.yes_filled != yes_amount || self.market.no_filled != no_amount {
+            return Err(error!(ErrorCode::DepositsExist));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<SetResolver>, params: SetResolverParams) -> ProgramResult {
+    let SetResolverParams { resolver } = params;
+
+    ctx.accounts.can_set_resolver()?;
+
+    let market = &mut ctx.accounts.market;
+    market.resolver = resolver;
+
+    Ok(())
+}
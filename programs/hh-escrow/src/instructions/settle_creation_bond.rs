@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use solana_program::entrypoint::ProgramResult;
+use spl_associated_token_account::get_associated_token_address;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, Market};
+use crate::utils::signer_transfer;
+
+/// Permissionless crank that settles the creation bond of a finalized
+/// [`Market`].
+///
+/// The bond is refunded to its payer, unless the market is invalid because it
+/// was not filled or never resolved, in which case it is forfeited to the fee
+/// wallet. Bonds in the token mint are paid to the associated token account
+/// of the recipient.
+#[derive(Accounts)]
+pub struct SettleCreationBond<'info> {
+    /// The global state account.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// The market whose bond is settled.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// The authority for the market token accounts.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"authority", market.key_ref().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    /// Escrow for the creation bond, if it is denominated in the token mint.
+    ///
+    /// CHECK: The address is checked by the seeds. Writes only occur via the
+    /// token program.
+    #[account(mut, seeds = [b"bond", market.key_ref().as_ref()], bump)]
+    pub bond_token_account: UncheckedAccount<'info>,
+    /// The account that posted the bond.
+    ///
+    /// CHECK: The address is checked against the market, and it only
+    /// receives lamports.
+    #[account(
+        mut,
+        address = market.creation_bond_payer @ ErrorCode::IncorrectBondPayer,
+    )]
+    pub bond_payer: UncheckedAccount<'info>,
+    /// The associated token account of the bond payer. Ignored unless the
+    /// bond is refunded in the token mint.
+    ///
+    /// CHECK: The address is checked if it is used. Writes only occur via the
+    /// token program.
+    #[account(mut)]
+    pub bond_payer_token_account: UncheckedAccount<'info>,
+    /// The fee wallet of the global state.
+    ///
+    /// CHECK: The address is checked against the global state, and it only
+    /// receives lamports.
+    #[account(
+        mut,
+        address = global_state.fee_wallet @ ErrorCode::IncorrectFeeWallet,
+    )]
+    pub fee_wallet: UncheckedAccount<'info>,
+    /// The associated token account of the fee wallet. Ignored unless the
+    /// bond is forfeited in the token mint.
+    ///
+    /// CHECK: The address is checked if it is used. Writes only occur via the
+    /// token program.
+    #[account(mut)]
+    pub fee_account: UncheckedAccount<'info>,
+    /// The SPL Token program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SettleCreationBond<'info> {
+    /// Checks that the market is finalized with a bond left to settle, and
+    /// returns whether the bond is forfeited.
+    fn can_settle(&mut self) -> Result<bool> {
+        let now = Clock::get()?.unix_timestamp as u64;
+        if !self.market.finalize(now)? {
+            return Err(error!(ErrorCode::NotFinalized));
+        }
+        if self.market.creation_bond == 0 {
+            return Err(error!(ErrorCode::NoCreationBond));
+        }
+
+        Ok(self.market.status().forfeits_creation_bond())
+    }
+
+    /// Returns the recipient of the bond, checking that token accounts are
+    /// associated with their wallet.
+    fn recipient(&self, forfeited: bool) -> Result<&AccountInfo<'info>> {
+        let (wallet, recipient) = match (forfeited, self.market.creation_bond_in_mint) {
+            (true, false) => return Ok(&*self.fee_wallet),
+            (false, false) => return Ok(&*self.bond_payer),
+            (true, true) => (self.fee_wallet.key_ref(), &*self.fee_account),
+            (false, true) => (self.bond_payer.key_ref(), &*self.bond_payer_token_account),
+        };
+
+        let key = get_associated_token_address(wallet, &self.market.token_mint);
+        if key != *recipient.key_ref() {
+            return Err(error!(ErrorCode::AssociatedTokenAccountRequired));
+        }
+
+        Ok(recipient)
+    }
+}
+
+pub fn handler(ctx: Context<SettleCreationBond>) -> ProgramResult {
+    let forfeited = ctx.accounts.can_settle()?;
+    let recipient = ctx.accounts.recipient(forfeited)?.clone();
+
+    let market = &mut ctx.accounts.market;
+    let bond = market.creation_bond;
+    market.creation_bond = 0;
+
+    // Lamports are held by the market account itself, which this program owns.
+    if !market.creation_bond_in_mint {
+        let market_info = market.to_account_info();
+        **market_info.try_borrow_mut_lamports()? = market_info
+            .lamports()
+            .checked_sub(bond)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+        **recipient.try_borrow_mut_lamports()? = recipient
+            .lamports()
+            .checked_add(bond)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+        return Ok(());
+    }
+
+    let bump = *ctx
+        .bumps
+        .get("authority")
+        .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
+    let market_key = ctx.accounts.market.key();
+    let seeds = [b"authority", market_key.as_ref(), &[bump]];
+    signer_transfer(
+        &ctx.accounts.token_program,
+        &ctx.accounts.bond_token_account,
+        &recipient,
+        &ctx.accounts.authority,
+        &[&seeds],
+        bond,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use solana_program::entrypoint::ProgramResult;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, Market, UserPosition};
+
+/// Permissionless crank that settles the dispute bond of a [UserPosition] in
+/// a finalized [`Market`].
+///
+/// The bond is refunded to the user, unless the arbiter upheld the disputed
+/// outcome, in which case it is forfeited to the fee wallet.
+#[derive(Accounts)]
+pub struct SettleDisputeBond<'info> {
+    /// The global state account.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// The market whose outcome was disputed.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// The disputing user's [UserPosition] account, which holds the bond.
+    #[account(
+        mut,
+        seeds = [b"user", user.key_ref().as_ref(), market.key_ref().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// The user who disputed the outcome.
+    ///
+    /// CHECK: The address is checked by the seeds constraint on the user
+    /// position, and it only receives lamports.
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+    /// The fee wallet of the global state.
+    ///
+    /// CHECK: The address is checked against the global state, and it only
+    /// receives lamports.
+    #[account(
+        mut,
+        address = global_state.fee_wallet @ ErrorCode::IncorrectFeeWallet,
+    )]
+    pub fee_wallet: UncheckedAccount<'info>,
+}
+
+impl<'info> SettleDisputeBond<'info> {
+    /// Checks that the market is finalized with a bond left to settle, and
+    /// returns the recipient of the bond.
+    fn can_settle(&mut self) -> Result<&AccountInfo<'info>> {
+        let now = Clock::get()?.unix_timestamp as u64;
+        if !self.market.finalize(now)? {
+            return Err(error!(ErrorCode::NotFinalized));
+        }
+        if self.user_position.dispute_bond == 0 {
+            return Err(error!(ErrorCode::NoDisputeBond));
+        }
+
+        if self.market.dispute_upheld {
+            Ok(&*self.fee_wallet)
+        } else {
+            Ok(&*self.user)
+        }
+    }
+}
+
+pub fn handler(ctx: Context<SettleDisputeBond>) -> ProgramResult {
+    let recipient = ctx.accounts.can_settle()?.clone();
+
+    let user_position = &mut ctx.accounts.user_position;
+    let bond = user_position.dispute_bond;
+    user_position.dispute_bond = 0;
+
+    // Lamports are held by the user position, which this program owns.
+    let position_info = user_position.to_account_info();
+    **position_info.try_borrow_mut_lamports()? = position_info
+        .lamports()
+        .checked_sub(bond)
+        .ok_or_else(|| error!(ErrorCode::Overflow))?;
+    **recipient.try_borrow_mut_lamports()? = recipient
+        .lamports()
+        .checked_add(bond)
+        .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+    Ok(())
+}
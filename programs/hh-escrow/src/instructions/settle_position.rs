@@ -0,0 +1,242 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use solana_program::entrypoint::ProgramResult;
+use spl_associated_token_account::get_associated_token_address;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{GlobalState, Market, Outcome, UserPosition};
+use crate::utils::{referral, signer_transfer};
+
+/// Permissionless crank that settles a user's position in a finalized market.
+///
+/// Funds are paid out to the user's associated token account for the market
+/// mint. For Yes/No markets, the cranker receives a share of the protocol fee
+/// as a tip, and the referrer of the position its referral share. The rest of
+/// the fee is accrued on the market as in [Claim](crate::instructions::Claim).
+#[derive(Accounts)]
+pub struct SettlePosition<'info> {
+    /// The global state account.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Account<'info, GlobalState>,
+    /// The user whose position is being settled.
+    ///
+    /// CHECK: We only need the public key from this account, which is checked
+    /// by the seeds constraint on the user position.
+    pub user: UncheckedAccount<'info>,
+    /// The user's associated token account for the market mint.
+    #[account(mut)]
+    pub user_token_account: Account<'info, TokenAccount>,
+    /// The cranker's token account, which receives the tip.
+    #[account(
+        mut,
+        constraint = cranker_token_account.owner == *cranker.key_ref() @ ErrorCode::CrankerAccountIncorrectOwner,
+    )]
+    pub cranker_token_account: Account<'info, TokenAccount>,
+    /// The associated token account of the referrer of the position. Ignored
+    /// if the position has no referrer.
+    ///
+    /// CHECK: The address is checked against the referrer of the position if
+    /// there is one. Writes only occur via the token program.
+    #[account(mut)]
+    pub referrer_token_account: UncheckedAccount<'info>,
+    /// Escrow for tokens on the yes side of the market.
+    ///
+    /// CHECK: We do not read any data from this account. The correctness of the
+    /// account is checked by the constraint on the market account. Writes
+    /// only occur via the token program, which performs necessary checks on
+    /// sufficient balance and matching token mints.
+    #[account(mut)]
+    pub yes_token_account: UncheckedAccount<'info>,
+    /// Escrow for tokens on the no side of the market.
+    ///
+    /// CHECK: We do not read any data from this account. The correctness of the
+    /// account is checked by the constraint on the market account. Writes
+    /// only occur via the token program, which performs necessary checks on
+    /// sufficient balance and matching token mints.
+    #[account(mut)]
+    pub no_token_account: UncheckedAccount<'info>,
+    /// The user's [UserPosition] account.
+    #[account(
+        mut,
+        seeds = [b"user", user.key_ref().as_ref(), market.key_ref().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    /// The [Market] the position belongs to.
+    #[account(
+        mut,
+        has_one = yes_token_account @ ErrorCode::IncorrectYesEscrow,
+        has_one = no_token_account @ ErrorCode::IncorrectNoEscrow,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// The authority for the market token accounts.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"authority", market.key_ref().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    /// The SPL Token program.
+    pub token_program: Program<'info, Token>,
+    /// The account cranking the settlement.
+    pub cranker: Signer<'info>,
+}
+
+impl SettlePosition<'_> {
+    pub fn can_settle(&mut self) -> Result<()> {
+        // Funds can only be sent to the user's associated token account, as
+        // the user is not a signer.
+        let key = get_associated_token_address(self.user.key_ref(), &self.market.token_mint);
+        if key != *self.user_token_account.key_ref() {
+            return Err(error!(ErrorCode::UserAccountNotAssociated));
+        }
+
+        let now = Clock::get()?.unix_timestamp as u64;
+        if !self.market.finalize(now)? {
+            return Err(error!(ErrorCode::NotFinalized));
+        }
+
+        Ok(())
+    }
+
+    /// Calls the given function with the signer seeds.
+    fn with_signer_seeds<F, R>(&self, f: F, bump: u8) -> R
+    where
+        F: Fn(&[&[u8]]) -> R,
+    {
+        let market_key = self.market.key_ref();
+        let seeds = [b"authority", market_key.as_ref(), &[bump]];
+
+        f(&seeds)
+    }
+}
+
+pub fn handler(ctx: Context<SettlePosition>) -> ProgramResult {
+    ctx.accounts.can_settle()?;
+
+    let bump_seed = *ctx
+        .bumps
+        .get("authority")
+        .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
+
+    // Invalid markets refund the position in full.
+    if ctx.accounts.market.outcome == Outcome::Invalid {
+        let user_position = &mut ctx.accounts.user_position;
+        let yes_withdraw = user_position.yes_amount;
+        let no_withdraw = user_position.no_amount;
+        user_position.yes_amount = 0;
+        user_position.no_amount = 0;
+
+        ctx.accounts.with_signer_seeds(
+            |signer| {
+                signer_transfer(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.yes_token_account,
+                    &ctx.accounts.user_token_account.to_account_info(),
+                    &ctx.accounts.authority,
+                    &[signer],
+                    yes_withdraw,
+                )?;
+
+                signer_transfer(
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.no_token_account,
+                    &ctx.accounts.user_token_account.to_account_info(),
+                    &ctx.accounts.authority,
+                    &[signer],
+                    no_withdraw,
+                )
+            },
+            bump_seed,
+        )?;
+
+        return Ok(());
+    }
+
+    // Compute the winnings and the fees, of which the cranker and the referrer
+    // receive a share, recording the winnings as paid out and the rest of the
+    // fee as accrued. The tip takes precedence if the shares exceed the fee.
+    let global_state = &ctx.accounts.global_state;
+    let (winning_num, winnings) = ctx.accounts.market.pay_out(&ctx.accounts.user_position)?;
+    let (fee, remaining_winnings) = global_state.fee_cut_bps.fee_received(winnings);
+    let (tip, fee_after_tip) = global_state.crank_tip_bps.fee_received(fee);
+    let referral = referral(
+        global_state,
+        &ctx.accounts.user_position,
+        &ctx.accounts.market.token_mint,
+        &ctx.accounts.referrer_token_account,
+        fee,
+    )?
+    .min(fee_after_tip);
+    ctx.accounts.market.accrue_fee(fee_after_tip - referral)?;
+    let (winning_side_holdings, losing_side_holdings) = match ctx.accounts.market.outcome {
+        Outcome::Yes => (
+            &ctx.accounts.yes_token_account,
+            &ctx.accounts.no_token_account,
+        ),
+        Outcome::No => (
+            &ctx.accounts.no_token_account,
+            &ctx.accounts.yes_token_account,
+        ),
+        _ => unreachable!(),
+    };
+
+    // Reset the user position.
+    let user_position = &mut ctx.accounts.user_position;
+    user_position.yes_amount = 0;
+    user_position.no_amount = 0;
+
+    // If the winning side was 0 we can exit early.
+    if winning_num == 0 {
+        return Ok(());
+    }
+
+    ctx.accounts.with_signer_seeds(
+        |signer| {
+            // Tip to the cranker.
+            signer_transfer(
+                &ctx.accounts.token_program,
+                losing_side_holdings,
+                &ctx.accounts.cranker_token_account.to_account_info(),
+                &ctx.accounts.authority,
+                &[signer],
+                tip,
+            )?;
+
+            // Referral to the referrer's wallet.
+            if referral > 0 {
+                signer_transfer(
+                    &ctx.accounts.token_program,
+                    losing_side_holdings,
+                    &ctx.accounts.referrer_token_account,
+                    &ctx.accounts.authority,
+                    &[signer],
+                    referral,
+                )?;
+            }
+
+            // Winnings to the user's wallet.
+            signer_transfer(
+                &ctx.accounts.token_program,
+                losing_side_holdings,
+                &ctx.accounts.user_token_account.to_account_info(),
+                &ctx.accounts.authority,
+                &[signer],
+                remaining_winnings,
+            )?;
+
+            // Original position to the user's wallet.
+            signer_transfer(
+                &ctx.accounts.token_program,
+                winning_side_holdings,
+                &ctx.accounts.user_token_account.to_account_info(),
+                &ctx.accounts.authority,
+                &[signer],
+                winning_num,
+            )
+        },
+        bump_seed,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use solana_program::entrypoint::ProgramResult;
+use spl_associated_token_account::get_associated_token_address;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::Market;
+use crate::utils::signer_transfer;
+
+/// Permissionless crank that refunds the resolver bond of a finalized
+/// [`Market`] to the associated token account of its payer, unless the
+/// arbiter overturned the outcome.
+#[derive(Accounts)]
+pub struct SettleResolverBond<'info> {
+    /// The market whose resolver bond is refunded.
+    #[account(mut)]
+    pub market: Box<Account<'info, Market>>,
+    /// The authority for the market token accounts.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"authority", market.key_ref().as_ref()], bump)]
+    pub authority: UncheckedAccount<'info>,
+    /// Escrow for the resolver bond.
+    ///
+    /// CHECK: The address is checked by the seeds. Writes only occur via the
+    /// token program.
+    #[account(mut, seeds = [b"resolver_bond", market.key_ref().as_ref()], bump)]
+    pub resolver_bond_token_account: UncheckedAccount<'info>,
+    /// The associated token account of the account that posted the bond.
+    ///
+    /// CHECK: The address is checked against the bond payer of the market.
+    /// Writes only occur via the token program.
+    #[account(mut)]
+    pub bond_payer_token_account: UncheckedAccount<'info>,
+    /// The SPL Token program.
+    pub token_program: Program<'info, Token>,
+}
+
+impl SettleResolverBond<'_> {
+    /// Checks that the market is finalized without slashing the resolver,
+    /// with a bond left to refund.
+    fn can_settle(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp as u64;
+        if !self.market.finalize(now)? {
+            return Err(error!(ErrorCode::NotFinalized));
+        }
+        if self.market.resolver_slashed {
+            return Err(error!(ErrorCode::ResolverSlashed));
+        }
+        if self.market.resolver_bond_outstanding() == 0 {
+            return Err(error!(ErrorCode::NoResolverBond));
+        }
+
+        let key =
+            get_associated_token_address(&self.market.resolver_bond_payer, &self.market.token_mint);
+        if key != *self.bond_payer_token_account.key_ref() {
+            return Err(error!(ErrorCode::AssociatedTokenAccountRequired));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<SettleResolverBond>) -> ProgramResult {
+    ctx.accounts.can_settle()?;
+
+    let market = &mut ctx.accounts.market;
+    let bond = market.resolver_bond_outstanding();
+    market.resolver_bond_paid = market.resolver_bond;
+
+    let bump = *ctx
+        .bumps
+        .get("authority")
+        .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
+    let market_key = ctx.accounts.market.key();
+    let seeds = [b"authority", market_key.as_ref(), &[bump]];
+    signer_transfer(
+        &ctx.accounts.token_program,
+        &ctx.accounts.resolver_bond_token_account,
+        &ctx.accounts.bond_payer_token_account,
+        &ctx.accounts.authority,
+        &[&seeds],
+        bond,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,309 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use solana_program::entrypoint::ProgramResult;
+use solana_program::instruction::{AccountMeta, Instruction};
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::instructions::{InitializeMarketParams, NewMarket};
+use crate::state::{
+    GlobalState, Market, MarketRegistry, MarketRegistryPage, MarketTemplate, CREATOR_REGISTRY_SEED,
+    MINT_REGISTRY_SEED,
+};
+
+/// Spawns the next [`Market`] of a [`MarketTemplate`] at the PDA
+/// `[b"market", template, index]`. Anyone can spawn a market, and pays for
+/// its accounts.
+///
+/// If the template has a resolver program, it must still be the template
+/// resolver program allowed by the global state. The remaining accounts must
+/// start with the resolver and the resolver program, followed by any accounts
+/// the resolver program needs. The resolver program is then invoked with the
+/// template's resolver data and the accounts
+/// `[resolver, market, spawn_authority, system_program, ..]`, where the spawn
+/// authority signs to prove the market was spawned by this program. The
+/// spawn authority holds no tokens and no authority over the market, and no
+/// other account is forwarded as a signer, so the resolver account must be
+/// funded beforehand. The resolver program must create the resolver, which
+/// it then owns.
+///
+/// The resolver program cannot call back into this program, so spawned
+/// markets requiring acknowledgement wait for the resolver to acknowledge
+/// them, and post its bond, in a later instruction.
+#[derive(Accounts)]
+pub struct SpawnMarketFromTemplate<'info> {
+    /// The global state account, holding the market policy.
+    #[account(seeds = [b"global"], bump)]
+    pub global_state: Box<Account<'info, GlobalState>>,
+    /// The template to spawn the market from.
+    #[account(mut, has_one = token_mint)]
+    pub template: Box<Account<'info, MarketTemplate>>,
+    /// The market account to initialize.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + Market::LEN,
+        seeds = [
+            b"market",
+            template.key_ref().as_ref(),
+            &template.spawned.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+    /// The authority for the two token accounts.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"authority", market.key_ref().as_ref()], bump)]
+    pub authority: AccountInfo<'info>,
+    /// The signer proving to the resolver program that the market is spawned
+    /// by this program.
+    ///
+    /// CHECK: We do not read/write any data from this account.
+    #[account(seeds = [b"spawn", market.key_ref().as_ref()], bump)]
+    pub spawn_authority: UncheckedAccount<'info>,
+    /// The account paying for the market.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// The token that this market is denominated in.
+    pub token_mint: Box<Account<'info, Mint>>,
+    /// The minimum fill policy of the token mint.
+    ///
+    /// CHECK: Only read if it was initialized by this program, otherwise the
+    /// token mint has no minimum fill.
+    #[account(seeds = [b"mint_policy", token_mint.key_ref().as_ref()], bump)]
+    pub mint_policy: UncheckedAccount<'info>,
+    /// Escrow for tokens on the yes side of the market.
+    #[account(
+        init,
+        payer = payer,
+        token::mint = token_mint,
+        token::authority = authority,
+        seeds = [b"yes", market.key_ref().as_ref()],
+        bump,
+    )]
+    pub yes_token_account: Box<Account<'info, TokenAccount>>,
+    /// Escrow for tokens on the no side of the market.
+    #[account(
+        init,
+        payer = payer,
+        token::mint = token_mint,
+        token::authority = authority,
+        seeds = [b"no", market.key_ref().as_ref()],
+        bump,
+    )]
+    pub no_token_account: Box<Account<'info, TokenAccount>>,
+    /// Escrow for the creation bond, only created if the bond is denominated
+    /// in the token mint.
+    ///
+    /// CHECK: The address is checked by the seeds, and the account is created
+    /// as a token account if the bond is in the token mint.
+    #[account(mut, seeds = [b"bond", market.key_ref().as_ref()], bump)]
+    pub bond_token_account: UncheckedAccount<'info>,
+    /// The payer's token account paying the creation bond, if it is
+    /// denominated in the token mint.
+    ///
+    /// CHECK: Only used as the source of a transfer signed by the payer,
+    /// which the token program checks.
+    #[account(mut)]
+    pub payer_token_account: UncheckedAccount<'info>,
+    /// The registry of the creator's markets.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MarketRegistry::LEN,
+        seeds = [b"registry", CREATOR_REGISTRY_SEED, template.creator.as_ref()],
+        bump,
+    )]
+    pub creator_registry: Box<Account<'info, MarketRegistry>>,
+    /// The tail page of the creator's registry.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MarketRegistryPage::LEN,
+        seeds = [
+            b"registry_page",
+            creator_registry.key_ref().as_ref(),
+            &creator_registry.tail_page.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub creator_registry_page: Box<Account<'info, MarketRegistryPage>>,
+    /// The registry of the token mint's markets.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MarketRegistry::LEN,
+        seeds = [b"registry", MINT_REGISTRY_SEED, token_mint.key_ref().as_ref()],
+        bump,
+    )]
+    pub mint_registry: Box<Account<'info, MarketRegistry>>,
+    /// The tail page of the token mint's registry.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MarketRegistryPage::LEN,
+        seeds = [
+            b"registry_page",
+            mint_registry.key_ref().as_ref(),
+            &mint_registry.tail_page.to_le_bytes(),
+        ],
+        bump,
+    )]
+    pub mint_registry_page: Box<Account<'info, MarketRegistryPage>>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+    /// The SPL Token Program.
+    pub token_program: Program<'info, Token>,
+    /// The Sysvar rent.
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> SpawnMarketFromTemplate<'info> {
+    fn new_market(&mut self) -> NewMarket<'_, 'info> {
+        NewMarket {
+            global_state: &self.global_state,
+            market: &mut self.market,
+            authority: &self.authority,
+            payer: &self.payer,
+            payer_token_account: &self.payer_token_account,
+            token_mint: &self.token_mint,
+            mint_policy: &self.mint_policy,
+            yes_token_account: &self.yes_token_account,
+            no_token_account: &self.no_token_account,
+            bond_token_account: &self.bond_token_account,
+            creator_registry: &mut self.creator_registry,
+            creator_registry_page: &mut self.creator_registry_page,
+            mint_registry: &mut self.mint_registry,
+            mint_registry_page: &mut self.mint_registry_page,
+            system_program: &self.system_program,
+            token_program: &self.token_program,
+            rent: &self.rent,
+        }
+    }
+
+    /// Splits the remaining accounts into the resolver, the resolver program
+    /// and the accounts forwarded to the resolver program.
+    fn split_resolver_accounts<'a>(
+        &self,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> Result<(&'a AccountInfo<'info>, &'a AccountInfo<'info>, &'a [AccountInfo<'info>])> {
+        match remaining_accounts {
+            [resolver, resolver_program, rest @ ..] => {
+                if *resolver_program.key != self.template.resolver_program {
+                    return Err(error!(ErrorCode::IncorrectResolverProgram));
+                }
+                if *resolver_program.key != self.global_state.template_resolver_program {
+                    return Err(error!(ErrorCode::ResolverProgramNotAllowed));
+                }
+
+                Ok((resolver, resolver_program, rest))
+            }
+            _ => Err(error!(ErrorCode::MissingResolverAccount)),
+        }
+    }
+
+    /// Invokes the resolver program to initialize the resolver of the market.
+    ///
+    /// The resolver must be a new account, which the resolver program owns
+    /// once invoked, so that the market can't be spawned with an account the
+    /// resolver program did not create for it.
+    fn initialize_resolver(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        bump: u8,
+    ) -> Result<()> {
+        let (resolver, resolver_program, rest) = self.split_resolver_accounts(remaining_accounts)?;
+        if *resolver.owner != System::id() || !resolver.data_is_empty() {
+            return Err(error!(ErrorCode::ResolverNotInitialized));
+        }
+
+        // Signer privileges of the spawning transaction are not forwarded.
+        let mut accounts = vec![
+            AccountMeta::new(*resolver.key, false),
+            AccountMeta::new_readonly(self.market.key(), false),
+            AccountMeta::new_readonly(self.spawn_authority.key(), true),
+            AccountMeta::new_readonly(self.system_program.key(), false),
+        ];
+        accounts.extend(rest.iter().map(|account| AccountMeta {
+            pubkey: *account.key,
+            is_signer: false,
+            is_writable: account.is_writable,
+        }));
+
+        let mut account_infos = vec![
+            resolver.clone(),
+            self.market.to_account_info(),
+            self.spawn_authority.to_account_info(),
+            self.system_program.to_account_info(),
+            resolver_program.clone(),
+        ];
+        account_infos.extend_from_slice(rest);
+
+        let ix = Instruction {
+            program_id: *resolver_program.key,
+            accounts,
+            data: self.template.resolver_data().to_vec(),
+        };
+
+        let market_key = self.market.key_ref();
+        let seeds = [b"spawn", market_key.as_ref(), &[bump]];
+        solana_program::program::invoke_signed(&ix, &account_infos, &[&seeds])?;
+
+        if resolver.owner != resolver_program.key || resolver.data_is_empty() {
+            return Err(error!(ErrorCode::ResolverNotInitialized));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, SpawnMarketFromTemplate<'info>>,
+) -> ProgramResult {
+    let now = Clock::get()?.unix_timestamp as u64;
+
+    let template = &ctx.accounts.template;
+    let index = template.spawned;
+    let schedule = template.next_schedule(now)?;
+
+    let resolver = if template.has_resolver_program() {
+        let (resolver, _, _) = ctx.accounts.split_resolver_accounts(ctx.remaining_accounts)?;
+        resolver.key()
+    } else {
+        template.resolver
+    };
+
+    let params = InitializeMarketParams::from_template(template, index, schedule, resolver)?;
+    let creator = template.creator;
+    ctx.accounts
+        .new_market()
+        .create(params, creator, &ctx.bumps)?;
+
+    // Schedule the market after this one.
+    let template = &mut ctx.accounts.template;
+    let (close_ts, expiry_ts) = schedule;
+    template.close_ts = close_ts
+        .checked_add(template.cadence)
+        .ok_or_else(|| error!(ErrorCode::Overflow))?;
+    template.expiry_ts = expiry_ts
+        .checked_add(template.cadence)
+        .ok_or_else(|| error!(ErrorCode::Overflow))?;
+    template.spawned = index
+        .checked_add(1)
+        .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+    if ctx.accounts.template.has_resolver_program() {
+        let bump = *ctx
+            .bumps
+            .get("spawn_authority")
+            .ok_or_else(|| error!(ErrorCode::NonCanonicalBumpSeed))?;
+
+        // Write the market, so that the resolver program can read it.
+        ctx.accounts.market.exit(&crate::ID)?;
+        ctx.accounts.initialize_resolver(ctx.remaining_accounts, bump)?;
+    }
+
+    Ok(())
+}
@@ -21,8 +21,11 @@ impl UpdateState<'_> {
     /// Legal updates:
     /// - Before the expiry ts:
     ///   - Open => Invalid, Invalid => Open (latter resets outcome_ts to 0)
+    ///   - Open => Yes, Open => No, if the market allows early resolution, so
+    ///     an early outcome can't be changed before the expiry ts
     /// - After the expiry ts:
     ///   - Cannot return to Open.
+    /// - Never while the outcome is disputed.
     ///
     /// Finalization checks should occur before this check.
     pub fn can_update(&self, now: u64, outcome: Outcome) -> Result<()> {
@@ -30,9 +33,18 @@ impl UpdateState<'_> {
             return Err(error!(ErrorCode::IncorrectResolver));
         }
 
+        // Only the arbiter rules on a disputed outcome.
+        if self.market.disputed {
+            return Err(error!(ErrorCode::MarketDisputed));
+        }
+
         if now < self.market.expiry_ts {
             let legal_transition = match self.market.outcome {
-                Outcome::Open => matches!(outcome, Outcome::Invalid),
+                Outcome::Open => {
+                    matches!(outcome, Outcome::Invalid)
+                        || (self.market.allow_early_resolution
+                            && matches!(outcome, Outcome::Yes | Outcome::No))
+                }
                 Outcome::Yes => false,
                 Outcome::No => false,
                 Outcome::Invalid => matches!(outcome, Outcome::Open),
@@ -75,6 +87,11 @@ pub fn handler(ctx: Context<UpdateState>, params: UpdateStateParams) -> ProgramR
     } else {
         market.outcome_ts = now;
     }
+    // An early resolution closes deposits immediately, unless they already
+    // closed.
+    if matches!(outcome, Outcome::Yes | Outcome::No) {
+        market.close_ts = market.close_ts.min(now);
+    }
     market.outcome = outcome;
 
     Ok(())
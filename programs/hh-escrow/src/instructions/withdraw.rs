@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 use solana_program::entrypoint::ProgramResult;
 
 use common::traits::KeyRef;
@@ -14,7 +15,9 @@ use crate::utils::signer_transfer;
 /// call to [UpdateStatus].
 #[derive(Accounts)]
 pub struct Withdraw<'info> {
-    /// The user withdrawing funds.
+    /// The user withdrawing funds, paying for the destination token account
+    /// if needed.
+    #[account(mut)]
     pub user: Signer<'info>,
     /// The yes token account for the market.
     /// 
@@ -32,13 +35,21 @@ pub struct Withdraw<'info> {
     /// sufficient balance and matching token mints.
     #[account(mut)]
     pub no_token_account: UncheckedAccount<'info>,
-    /// The user's token account. We explicitly check the owner for this
-    /// account.
-    #[account(mut,
-        constraint = user_token_account.key_ref() != yes_token_account.key_ref() && user_token_account.key_ref() != no_token_account.key_ref() @ ErrorCode::UserAccountCannotBeMarketAccount,
-        constraint = user_token_account.owner == *user.key_ref() @ ErrorCode::UserAccountIncorrectOwner
+    /// The wallet receiving the refund, which may be the user.
+    ///
+    /// CHECK: We only need the public key from this account.
+    pub destination: UncheckedAccount<'info>,
+    /// The associated token account of the destination wallet, created with
+    /// the user as payer if it does not exist yet.
+    #[account(
+        init_if_needed,
+        payer = user,
+        associated_token::mint = token_mint,
+        associated_token::authority = destination,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: Box<Account<'info, TokenAccount>>,
+    /// The token mint of the market.
+    pub token_mint: Box<Account<'info, Mint>>,
     /// The authority for the market token accounts.
     /// 
     /// CHECK: We do not read/write any data from this account.
@@ -50,6 +61,7 @@ pub struct Withdraw<'info> {
         constraint = market.outcome == Outcome::Invalid @ ErrorCode::MarketNotInvalid,
         has_one = yes_token_account @ ErrorCode::IncorrectYesEscrow,
         has_one = no_token_account @ ErrorCode::IncorrectNoEscrow,
+        has_one = token_mint @ ErrorCode::IncorrectTokenMint,
     )]
     pub market: Account<'info, Market>,
     /// The user's [UserPosition] account for this market.
@@ -57,6 +69,12 @@ pub struct Withdraw<'info> {
     pub user_position: Account<'info, UserPosition>,
     /// The SPL Token Program.
     pub token_program: Program<'info, Token>,
+    /// The SPL Associated Token Account program.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// The Solana System Program.
+    pub system_program: Program<'info, System>,
+    /// The Sysvar rent.
+    pub rent: Sysvar<'info, Rent>,
 }
 
 impl Withdraw<'_> {
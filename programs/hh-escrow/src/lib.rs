@@ -64,7 +64,111 @@ pub mod hh_escrow {
         instructions::set_global_state::handler(ctx, params)
     }
 
-    pub fn resolver_acknowledge(ctx: Context<ResolverAcknowledge>) -> ProgramResult {
+    pub fn set_market_policy(
+        ctx: Context<SetMarketPolicy>,
+        params: SetMarketPolicyParams,
+    ) -> ProgramResult {
+        instructions::set_market_policy::handler(ctx, params)
+    }
+
+    pub fn set_mint_policy(
+        ctx: Context<SetMintPolicy>,
+        params: SetMintPolicyParams,
+    ) -> ProgramResult {
+        instructions::set_mint_policy::handler(ctx, params)
+    }
+
+    pub fn resolver_acknowledge<'info>(
+        ctx: Context<'_, '_, '_, 'info, ResolverAcknowledge<'info>>,
+    ) -> ProgramResult {
         instructions::resolver_acknowledge::handler(ctx)
     }
+
+    pub fn settle_position(ctx: Context<SettlePosition>) -> ProgramResult {
+        instructions::settle_position::handler(ctx)
+    }
+
+    pub fn settle_creation_bond(ctx: Context<SettleCreationBond>) -> ProgramResult {
+        instructions::settle_creation_bond::handler(ctx)
+    }
+
+    pub fn set_allowlist_root(
+        ctx: Context<SetAllowlistRoot>,
+        params: SetAllowlistRootParams,
+    ) -> ProgramResult {
+        instructions::set_allowlist_root::handler(ctx, params)
+    }
+
+    pub fn set_resolver(ctx: Context<SetResolver>, params: SetResolverParams) -> ProgramResult {
+        instructions::set_resolver::handler(ctx, params)
+    }
+
+    pub fn migrate_market(
+        ctx: Context<MigrateMarket>,
+        params: MigrateMarketParams,
+    ) -> ProgramResult {
+        instructions::migrate_market::handler(ctx, params)
+    }
+
+    pub fn migrate_user_position(ctx: Context<MigrateUserPosition>) -> ProgramResult {
+        instructions::migrate_user_position::handler(ctx)
+    }
+
+    pub fn migrate_global_state(ctx: Context<MigrateGlobalState>) -> ProgramResult {
+        instructions::migrate_global_state::handler(ctx)
+    }
+
+    pub fn initialize_market_pda(
+        ctx: Context<InitializeMarketPda>,
+        params: InitializeMarketParams,
+    ) -> ProgramResult {
+        instructions::initialize_market_pda::handler(ctx, params)
+    }
+
+    pub fn initialize_market_template(
+        ctx: Context<InitializeMarketTemplate>,
+        params: InitializeMarketTemplateParams,
+    ) -> ProgramResult {
+        instructions::initialize_market_template::handler(ctx, params)
+    }
+
+    pub fn spawn_market_from_template<'info>(
+        ctx: Context<'_, '_, '_, 'info, SpawnMarketFromTemplate<'info>>,
+    ) -> ProgramResult {
+        instructions::spawn_market_from_template::handler(ctx)
+    }
+
+    pub fn close_market(ctx: Context<CloseMarket>) -> ProgramResult {
+        instructions::close_market::handler(ctx)
+    }
+
+    pub fn collect_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectFees<'info>>,
+    ) -> ProgramResult {
+        instructions::collect_fees::handler(ctx)
+    }
+
+    pub fn dispute_outcome(ctx: Context<DisputeOutcome>) -> ProgramResult {
+        instructions::dispute_outcome::handler(ctx)
+    }
+
+    pub fn arbitrate(ctx: Context<Arbitrate>, params: ArbitrateParams) -> ProgramResult {
+        instructions::arbitrate::handler(ctx, params)
+    }
+
+    pub fn settle_resolver_bond(ctx: Context<SettleResolverBond>) -> ProgramResult {
+        instructions::settle_resolver_bond::handler(ctx)
+    }
+
+    pub fn claim_slashed_bond(ctx: Context<ClaimSlashedBond>) -> ProgramResult {
+        instructions::claim_slashed_bond::handler(ctx)
+    }
+
+    pub fn settle_dispute_bond(ctx: Context<SettleDisputeBond>) -> ProgramResult {
+        instructions::settle_dispute_bond::handler(ctx)
+    }
+
+    pub fn close_user_position(ctx: Context<CloseUserPosition>) -> ProgramResult {
+        instructions::close_user_position::handler(ctx)
+    }
 }
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Limits on deposits into one side of a market.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorDeserialize, AnchorSerialize)]
+pub struct DepositLimits {
+    /// The minimum size of a single deposit. 0 if there is no minimum.
+    pub min_deposit: u64,
+    /// The maximum amount a single user may deposit in total. 0 if there is
+    /// no maximum.
+    pub max_per_user: u64,
+}
+
+impl DepositLimits {
+    pub const LEN: usize = 2 * 8;
+
+    /// Validates the limits for a side that is filled by `side_amount` tokens.
+    pub fn validate(&self, side_amount: u64) -> Result<()> {
+        if self.min_deposit > side_amount {
+            return Err(error!(ErrorCode::InvalidDepositLimits));
+        }
+        if self.max_per_user != 0 && self.min_deposit > self.max_per_user {
+            return Err(error!(ErrorCode::InvalidDepositLimits));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the amount that can be deposited, given the amount the user has
+    /// already deposited and the amount left to fill the side.
+    ///
+    /// If `allow_partial` is true, the amount is clipped to what is allowed
+    /// rather than failing. A deposit below the minimum is always allowed if it
+    /// fills the rest of the side.
+    pub fn deposit_amount(
+        &self,
+        deposited: u64,
+        left: u64,
+        amount: u64,
+        allow_partial: bool,
+    ) -> Result<u64> {
+        if left < amount && !allow_partial {
+            return Err(error!(ErrorCode::OverAllowedAmount));
+        }
+        let mut amount = left.min(amount);
+
+        if self.max_per_user != 0 {
+            let user_left = self.max_per_user.saturating_sub(deposited);
+            if user_left < amount && !allow_partial {
+                return Err(error!(ErrorCode::OverUserLimit));
+            }
+            amount = user_left.min(amount);
+        }
+
+        if amount != 0 && amount < self.min_deposit && amount != left {
+            return Err(error!(ErrorCode::BelowMinimumDeposit));
+        }
+
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that no limits behaves as the unlimited market.
+    #[test]
+    fn check_no_limits() {
+        let limits = DepositLimits::default();
+
+        assert_eq!(limits.deposit_amount(100, 10, 5, false).unwrap(), 5);
+        assert_eq!(limits.deposit_amount(100, 10, 15, true).unwrap(), 10);
+        assert!(limits.deposit_amount(100, 10, 15, false).is_err());
+    }
+
+    // Checks that the cumulative amount deposited by a user is capped.
+    #[test]
+    fn check_max_per_user() {
+        let limits = DepositLimits {
+            max_per_user: 10,
+            ..Default::default()
+        };
+
+        assert_eq!(limits.deposit_amount(4, 100, 6, false).unwrap(), 6);
+        assert_eq!(limits.deposit_amount(4, 100, 7, true).unwrap(), 6);
+        assert!(limits.deposit_amount(4, 100, 7, false).is_err());
+        assert_eq!(limits.deposit_amount(10, 100, 1, true).unwrap(), 0);
+    }
+
+    // Checks that small deposits are rejected, unless they fill the side.
+    #[test]
+    fn check_min_deposit() {
+        let limits = DepositLimits {
+            min_deposit: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(limits.deposit_amount(0, 100, 5, false).unwrap(), 5);
+        assert_eq!(limits.deposit_amount(0, 100, 0, false).unwrap(), 0);
+        assert!(limits.deposit_amount(0, 100, 4, false).is_err());
+        assert_eq!(limits.deposit_amount(0, 3, 3, false).unwrap(), 3);
+        assert_eq!(limits.deposit_amount(0, 3, 10, true).unwrap(), 3);
+    }
+
+    // Checks that a deposit clipped by the user limit still respects the
+    // minimum deposit.
+    #[test]
+    fn check_clipped_below_min_deposit() {
+        let limits = DepositLimits {
+            min_deposit: 5,
+            max_per_user: 12,
+        };
+
+        assert!(limits.deposit_amount(10, 100, 5, true).is_err());
+    }
+
+    // Checks that limits that can never be satisfied are rejected.
+    #[test]
+    fn check_validate() {
+        let limits = DepositLimits {
+            min_deposit: 5,
+            max_per_user: 4,
+        };
+        assert!(limits.validate(100).is_err());
+
+        let limits = DepositLimits {
+            min_deposit: 5,
+            ..Default::default()
+        };
+        assert!(limits.validate(4).is_err());
+        assert!(limits.validate(5).is_ok());
+    }
+}
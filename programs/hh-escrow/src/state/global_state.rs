@@ -1,19 +1,93 @@
 use anchor_lang::prelude::*;
 
-use crate::state::Bps;
+use crate::state::{Bps, MarketPolicy, DEFAULT_RESOLUTION_WINDOW};
+
+/// The size of the space reserved for future fields of [GlobalState].
+pub const GLOBAL_STATE_RESERVED_LEN: usize = 128;
 
 /// The [GlobalState] account.
 #[account]
-#[derive(Default)]
 pub struct GlobalState {
+    /// The version of the account layout.
+    pub version: u8,
     /// The owner of the global state account.
     pub owner: Pubkey,
     /// The protocol fee in basis points.
     pub fee_cut_bps: Bps,
     /// The wallet which will own the protocol fee.
     pub fee_wallet: Pubkey,
+    /// The share of the protocol fee paid to whoever cranks a position
+    /// settlement, in basis points.
+    pub crank_tip_bps: Bps,
+    /// The share of the protocol fee paid to the referrer of a position when
+    /// it is claimed, in basis points.
+    pub referral_bps: Bps,
+    /// The minimum resolution window of a new market, in seconds.
+    pub min_resolution_window: u32,
+    /// The maximum resolution window of a new market, in seconds. 0 if not
+    /// configured, in which case [DEFAULT_RESOLUTION_WINDOW] is the maximum.
+    pub max_resolution_window: u32,
+    /// Limits on the parameters of new markets.
+    pub market_policy: MarketPolicy,
+    /// The creation bond of a new market, in lamports, unless the policy of
+    /// its token mint sets a bond in tokens.
+    pub creation_bond_lamports: u64,
+    /// The arbiter ruling on disputed outcomes. The default public key if
+    /// outcomes cannot be disputed.
+    pub arbiter: Pubkey,
+    /// The bond in lamports a user posts to dispute an outcome, refunded
+    /// unless the arbiter upholds the outcome.
+    pub dispute_bond_lamports: u64,
+    /// The only resolver program that markets spawned from templates may
+    /// invoke. The default public key if templates cannot have one.
+    pub template_resolver_program: Pubkey,
+    /// Space reserved for future fields.
+    pub reserved: [u8; GLOBAL_STATE_RESERVED_LEN],
+}
+
+impl Default for GlobalState {
+    fn default() -> Self {
+        GlobalState {
+            version: GlobalState::VERSION,
+            owner: Pubkey::default(),
+            fee_cut_bps: Bps::default(),
+            fee_wallet: Pubkey::default(),
+            crank_tip_bps: Bps::default(),
+            referral_bps: Bps::default(),
+            min_resolution_window: 0,
+            max_resolution_window: 0,
+            market_policy: MarketPolicy::default(),
+            creation_bond_lamports: 0,
+            arbiter: Pubkey::default(),
+            dispute_bond_lamports: 0,
+            template_resolver_program: Pubkey::default(),
+            reserved: [0; GLOBAL_STATE_RESERVED_LEN],
+        }
+    }
 }
 
 impl GlobalState {
-    pub const LEN: usize = 32 + 2 + 32;
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1
+        + 32
+        + 2
+        + 32
+        + 2 * 2
+        + 2 * 4
+        + MarketPolicy::LEN
+        + 8
+        + 32
+        + 8
+        + 32
+        + GLOBAL_STATE_RESERVED_LEN;
+
+    /// Returns the minimum and maximum resolution window of a new market.
+    pub fn resolution_window_bounds(&self) -> (u32, u32) {
+        match self.max_resolution_window {
+            0 => (self.min_resolution_window, DEFAULT_RESOLUTION_WINDOW),
+            max => (self.min_resolution_window, max),
+        }
+    }
 }
@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{
+    Bps, GlobalState, Market, MarketMetadata, Outcome, UriResource, UserPosition, URI_MAX_LEN,
+};
+
+/// The maximum length of a URI in the unversioned [Market] layout.
+pub const URI_MAX_LEN_V0: usize = 256;
+
+/// Deserializes the data after the discriminator of an account created before
+/// versioning was introduced. The data must match the layout exactly.
+pub fn deserialize_v0<T: AnchorDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_from_slice(data).map_err(|_| error!(ErrorCode::UnknownAccountLayout))
+}
+
+/// The unversioned [GlobalState] layout.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct GlobalStateV0 {
+    pub owner: Pubkey,
+    pub fee_cut_bps: Bps,
+    pub fee_wallet: Pubkey,
+}
+
+impl GlobalStateV0 {
+    pub const LEN: usize = 32 + 2 + 32;
+}
+
+impl GlobalState {
+    /// Converts the data of an unversioned global state.
+    pub fn from_unversioned(data: &[u8]) -> Result<GlobalState> {
+        let v0 = deserialize_v0::<GlobalStateV0>(data)?;
+
+        Ok(GlobalState {
+            owner: v0.owner,
+            fee_cut_bps: v0.fee_cut_bps,
+            fee_wallet: v0.fee_wallet,
+            ..GlobalState::default()
+        })
+    }
+}
+
+/// The URI of the unversioned [Market] layout.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct UriResourceV0 {
+    pub len: u16,
+    pub uri: [u8; URI_MAX_LEN_V0],
+}
+
+impl UriResourceV0 {
+    pub const LEN: usize = 2 + URI_MAX_LEN_V0;
+}
+
+impl TryFrom<UriResourceV0> for UriResource {
+    type Error = Error;
+
+    /// Keeps the URI, failing if it does not fit in [URI_MAX_LEN] bytes, as a
+    /// truncated URI would point to the wrong document.
+    fn try_from(v0: UriResourceV0) -> Result<Self> {
+        let len = v0.len as usize;
+        if len > URI_MAX_LEN {
+            return Err(error!(ErrorCode::UriTooLongToMigrate));
+        }
+
+        let mut uri = [0; URI_MAX_LEN];
+        uri[..len].copy_from_slice(&v0.uri[..len]);
+
+        Ok(UriResource {
+            len: len as u16,
+            uri,
+        })
+    }
+}
+
+/// The unversioned [Market] layout.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct MarketV0 {
+    pub creator: Pubkey,
+    pub resolver: Pubkey,
+    pub token_mint: Pubkey,
+    pub yes_token_account: Pubkey,
+    pub no_token_account: Pubkey,
+    pub yes_amount: u64,
+    pub yes_filled: u64,
+    pub no_amount: u64,
+    pub no_filled: u64,
+    pub close_ts: u64,
+    pub expiry_ts: u64,
+    pub outcome_ts: u64,
+    pub resolution_delay: u32,
+    pub outcome: Outcome,
+    pub finalized: bool,
+    pub yes_account_bump: u8,
+    pub no_account_bump: u8,
+    pub acknowledged: bool,
+    pub uri: UriResourceV0,
+}
+
+impl MarketV0 {
+    pub const LEN: usize = 5 * 32 + 7 * 8 + 4 + 1 + 1 + 2 * 1 + 1 + UriResourceV0::LEN;
+}
+
+impl Market {
+    /// Converts the data of an unversioned market.
+    ///
+    /// The URI is kept in metadata with version 0, as it has no title,
+    /// category or content hash.
+    pub fn from_unversioned(data: &[u8]) -> Result<Market> {
+        let v0 = deserialize_v0::<MarketV0>(data)?;
+
+        Ok(Market {
+            creator: v0.creator,
+            resolver: v0.resolver,
+            token_mint: v0.token_mint,
+            yes_token_account: v0.yes_token_account,
+            no_token_account: v0.no_token_account,
+            yes_amount: v0.yes_amount,
+            yes_filled: v0.yes_filled,
+            no_amount: v0.no_amount,
+            no_filled: v0.no_filled,
+            close_ts: v0.close_ts,
+            expiry_ts: v0.expiry_ts,
+            outcome_ts: v0.outcome_ts,
+            resolution_delay: v0.resolution_delay,
+            outcome: v0.outcome,
+            finalized: v0.finalized,
+            yes_account_bump: v0.yes_account_bump,
+            no_account_bump: v0.no_account_bump,
+            acknowledged: v0.acknowledged,
+            metadata: MarketMetadata {
+                version: 0,
+                uri: v0.uri.try_into()?,
+                ..MarketMetadata::default()
+            },
+            ..Market::default()
+        })
+    }
+}
+
+/// The unversioned [UserPosition] layout.
+#[derive(AnchorDeserialize, AnchorSerialize)]
+pub struct UserPositionV0 {
+    pub market: Pubkey,
+    pub yes_amount: u64,
+    pub no_amount: u64,
+}
+
+impl UserPositionV0 {
+    pub const LEN: usize = 32 + 2 * 8;
+}
+
+impl From<UserPositionV0> for UserPosition {
+    fn from(v0: UserPositionV0) -> Self {
+        UserPosition {
+            market: v0.market,
+            yes_amount: v0.yes_amount,
+            no_amount: v0.no_amount,
+            ..UserPosition::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri_v0(uri: &[u8]) -> UriResourceV0 {
+        let mut bytes = [0; URI_MAX_LEN_V0];
+        bytes[..uri.len()].copy_from_slice(uri);
+
+        UriResourceV0 {
+            len: uri.len() as u16,
+            uri: bytes,
+        }
+    }
+
+    // Checks that the unversioned lengths match the serialized layouts.
+    #[test]
+    fn check_len() {
+        let market = MarketV0 {
+            creator: Pubkey::default(),
+            resolver: Pubkey::default(),
+            token_mint: Pubkey::default(),
+            yes_token_account: Pubkey::default(),
+            no_token_account: Pubkey::default(),
+            yes_amount: 0,
+            yes_filled: 0,
+            no_amount: 0,
+            no_filled: 0,
+            close_ts: 0,
+            expiry_ts: 0,
+            outcome_ts: 0,
+            resolution_delay: 0,
+            outcome: Outcome::Open,
+            finalized: false,
+            yes_account_bump: 0,
+            no_account_bump: 0,
+            acknowledged: false,
+            uri: uri_v0(b""),
+        };
+        assert_eq!(market.try_to_vec().unwrap().len(), MarketV0::LEN);
+        assert_eq!(MarketV0::LEN, 483);
+
+        let global_state = GlobalStateV0 {
+            owner: Pubkey::default(),
+            fee_cut_bps: Bps::default(),
+            fee_wallet: Pubkey::default(),
+        };
+        assert_eq!(global_state.try_to_vec().unwrap().len(), GlobalStateV0::LEN);
+    }
+
+    // Checks that URIs are kept, and that URIs too long for the current
+    // layout fail to migrate.
+    #[test]
+    fn check_migrate_uri() {
+        let migrated = UriResource::try_from(uri_v0(b"ar://abc")).unwrap();
+        assert_eq!(migrated.as_str(), Some("ar://abc"));
+
+        let migrated = UriResource::try_from(uri_v0(&[b'a'; URI_MAX_LEN])).unwrap();
+        assert_eq!(migrated.len as usize, URI_MAX_LEN);
+
+        assert!(UriResource::try_from(uri_v0(&[b'a'; URI_MAX_LEN + 1])).is_err());
+    }
+
+    // Checks that only data of the exact unversioned layout is converted.
+    #[test]
+    fn check_from_unversioned() {
+        let data = vec![0; MarketV0::LEN];
+        assert!(Market::from_unversioned(&data).is_ok());
+        assert!(Market::from_unversioned(&data[1..]).is_err());
+        assert!(Market::from_unversioned(&vec![0; Market::LEN]).is_err());
+        assert!(GlobalState::from_unversioned(&[0; GlobalStateV0::LEN + 2]).is_err());
+    }
+}
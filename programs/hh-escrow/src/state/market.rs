@@ -1,15 +1,22 @@
 use anchor_lang::prelude::*;
 
+use common::merkle;
+
 use crate::error::ErrorCode;
-use crate::state::{Outcome, UriResource};
+use crate::state::{DepositLimits, MarketMetadata, MarketStatus, Outcome, UserPosition};
+
+/// The resolution window of markets created before it was configurable: 30
+/// days after the expiry before a result is final.
+pub const DEFAULT_RESOLUTION_WINDOW: u32 = 86_400 * 30;
 
-/// 30 days max delay before a result is set.
-pub const MAX_DELAY_SEC: u32 = 86_400 * 30;
+/// The size of the space reserved for future fields of [Market].
+pub const MARKET_RESERVED_LEN: usize = 248;
 
 /// The [Market] account.
 #[account]
-#[derive(Default)]
 pub struct Market {
+    /// The version of the account layout.
+    pub version: u8,
     /// Creator of the market.
     pub creator: Pubkey,
     /// Resolver for the market.
@@ -47,12 +54,209 @@ pub struct Market {
     pub no_account_bump: u8,
     /// Whether the resolver has acknowledged this market.
     pub acknowledged: bool,
-    /// The URI to the market's info (i.e. title, description)
-    pub uri: UriResource,
+    /// Whether deposits are refused until the resolver has acknowledged this
+    /// market.
+    pub require_acknowledgement: bool,
+    /// Limits on deposits into the yes side.
+    pub yes_limits: DepositLimits,
+    /// Limits on deposits into the no side.
+    pub no_limits: DepositLimits,
+    /// The Merkle root of the public keys allowed to deposit into the market.
+    /// All zeroes if anyone can deposit.
+    pub allowlist_root: [u8; 32],
+    /// The market's info (i.e. title, category, description URI). Fields
+    /// before it can't change size, see [Market::CATEGORY_OFFSET].
+    pub metadata: MarketMetadata,
+    /// The amount placed on the winning side that has been paid back.
+    pub claimed_stakes: u64,
+    /// The winnings, including fees, that have been paid out of the losing
+    /// side.
+    pub paid_winnings: u64,
+    /// The fees taken from claimed winnings that are still held in the losing
+    /// side, until they are collected.
+    pub accrued_fees: u64,
+    /// Whether the resolver may set the outcome to a side before the expiry
+    /// time, which closes deposits.
+    pub allow_early_resolution: bool,
+    /// The number of seconds after the expiry after which the outcome is
+    /// final, or the market invalid if no outcome was set. 0 for
+    /// [DEFAULT_RESOLUTION_WINDOW].
+    pub max_resolution_window: u32,
+    /// The creation bond escrowed by the market, until it is refunded or
+    /// forfeited. Held as lamports by the market account, or as tokens by the
+    /// bond escrow if `creation_bond_in_mint` is set. 0 once settled.
+    pub creation_bond: u64,
+    /// Whether the creation bond is denominated in the token mint.
+    pub creation_bond_in_mint: bool,
+    /// The account that posted the creation bond, and receives it back.
+    pub creation_bond_payer: Pubkey,
+    /// The bond the resolver posts in the token mint when acknowledging the
+    /// market. 0 if no bond is required.
+    pub resolver_bond: u64,
+    /// The account that posted the resolver bond, and receives it back. The
+    /// default public key until the bond is posted.
+    pub resolver_bond_payer: Pubkey,
+    /// The amount of the resolver bond that has been refunded or paid out to
+    /// disputing users.
+    pub resolver_bond_paid: u64,
+    /// Whether a user disputed the outcome, which is then only finalized by
+    /// the arbiter or at the end of the resolution window.
+    pub disputed: bool,
+    /// Whether the arbiter overturned the outcome, in which case the resolver
+    /// bond is paid out to the disputing users.
+    pub resolver_slashed: bool,
+    /// The total stake of the positions disputing the outcome.
+    pub disputed_stake: u64,
+    /// The stake of the disputing positions that have been paid their share
+    /// of a slashed resolver bond.
+    pub slashed_stake_claimed: u64,
+    /// The number of [UserPosition] accounts of the market that are not
+    /// closed. The market can only be closed once there are none left.
+    pub open_positions: u64,
+    /// Whether the market was added to the registries of its creator and
+    /// token mint. Markets created before registries were not.
+    pub registered: bool,
+    /// Whether the arbiter upheld the disputed outcome, in which case the
+    /// dispute bonds of the disputing positions are slashed.
+    pub dispute_upheld: bool,
+    /// The number of [UserPosition] accounts of a migrated market that are
+    /// still in the unversioned layout. They are counted as open positions
+    /// once migrated.
+    pub unmigrated_positions: u64,
+    /// Space reserved for future fields.
+    pub reserved: [u8; MARKET_RESERVED_LEN],
+}
+
+impl Default for Market {
+    fn default() -> Self {
+        Market {
+            version: Market::VERSION,
+            creator: Pubkey::default(),
+            resolver: Pubkey::default(),
+            token_mint: Pubkey::default(),
+            yes_token_account: Pubkey::default(),
+            no_token_account: Pubkey::default(),
+            yes_amount: 0,
+            yes_filled: 0,
+            no_amount: 0,
+            no_filled: 0,
+            close_ts: 0,
+            expiry_ts: 0,
+            outcome_ts: 0,
+            resolution_delay: 0,
+            outcome: Outcome::default(),
+            finalized: false,
+            yes_account_bump: 0,
+            no_account_bump: 0,
+            acknowledged: false,
+            require_acknowledgement: false,
+            yes_limits: DepositLimits::default(),
+            no_limits: DepositLimits::default(),
+            allowlist_root: [0; 32],
+            metadata: MarketMetadata::default(),
+            claimed_stakes: 0,
+            paid_winnings: 0,
+            accrued_fees: 0,
+            allow_early_resolution: false,
+            max_resolution_window: 0,
+            creation_bond: 0,
+            creation_bond_in_mint: false,
+            creation_bond_payer: Pubkey::default(),
+            resolver_bond: 0,
+            resolver_bond_payer: Pubkey::default(),
+            resolver_bond_paid: 0,
+            disputed: false,
+            resolver_slashed: false,
+            disputed_stake: 0,
+            slashed_stake_claimed: 0,
+            open_positions: 0,
+            registered: false,
+            dispute_upheld: false,
+            unmigrated_positions: 0,
+            reserved: [0; MARKET_RESERVED_LEN],
+        }
+    }
 }
 
 impl Market {
-    pub const LEN: usize = 5 * 32 + 7 * 8 + 4 + 1 + 1 + 2 * 1 + 1 + UriResource::LEN;
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1
+        + Market::METADATA_OFFSET
+        + MarketMetadata::LEN
+        + 3 * 8
+        + 1
+        + 4
+        + 8
+        + 1
+        + 32
+        + 8
+        + 32
+        + 8
+        + 2 * 1
+        + 2 * 8
+        + 8
+        + 2 * 1
+        + 8
+        + MARKET_RESERVED_LEN;
+
+    /// The size of the fields after the version and before the metadata.
+    const METADATA_OFFSET: usize =
+        5 * 32 + 7 * 8 + 4 + 1 + 1 + 2 * 1 + 1 + 1 + 2 * DepositLimits::LEN + 32;
+
+    /// The offset of the category tag in the account data, including the
+    /// discriminator, for filtering markets by category with `memcmp`.
+    pub const CATEGORY_OFFSET: usize =
+        8 + 1 + Market::METADATA_OFFSET + MarketMetadata::CATEGORY_OFFSET;
+
+    /// Returns the stored allowlist root for the given optional root.
+    pub fn allowlist_root(root: Option<[u8; 32]>) -> [u8; 32] {
+        root.unwrap_or_default()
+    }
+
+    /// Checks whether the market is restricted to an allowlist.
+    pub fn is_private(&self) -> bool {
+        self.allowlist_root != [0; 32]
+    }
+
+    /// Checks whether the given user is allowed to deposit into the market,
+    /// using a Merkle proof of the user's public key.
+    pub fn is_allowed(&self, user: &Pubkey, proof: &[[u8; 32]]) -> bool {
+        !self.is_private()
+            || merkle::verify(
+                proof,
+                &self.allowlist_root,
+                merkle::hash_leaf(user.as_ref()),
+            )
+    }
+
+    /// Checks whether deposits into the market are allowed by the resolver.
+    pub fn is_acknowledged(&self) -> bool {
+        self.acknowledged || !self.require_acknowledgement
+    }
+
+    /// Returns the fields that determine the outcome and payouts of the market.
+    pub fn status(&self) -> MarketStatus {
+        MarketStatus {
+            yes_amount: self.yes_amount,
+            yes_filled: self.yes_filled,
+            no_amount: self.no_amount,
+            no_filled: self.no_filled,
+            close_ts: self.close_ts,
+            expiry_ts: self.expiry_ts,
+            outcome_ts: self.outcome_ts,
+            resolution_delay: self.resolution_delay,
+            max_resolution_window: self.max_resolution_window,
+            disputed: self.disputed,
+            arbitrated: self.resolver_slashed || self.dispute_upheld,
+            outcome: self.outcome,
+            acknowledged: self.acknowledged,
+            require_acknowledgement: self.require_acknowledgement,
+            claimed_stakes: self.claimed_stakes,
+            paid_winnings: self.paid_winnings,
+        }
+    }
 
     /// Checks whether the market is finalized. If the `finalized` flag is not
     /// flipped, checks conditions that would cause the market to be finalized,
@@ -63,42 +267,49 @@ impl Market {
             return Ok(true);
         }
 
-        // Failed to fill funds.
-        if (self.yes_filled < self.yes_amount || self.no_filled < self.no_amount)
-            && now >= self.close_ts
-        {
-            self.finalized = true;
-            self.outcome = Outcome::Invalid;
-            return Ok(true);
-        }
-
-        // Beyond MAX_DELAY_SEC of the expiry.
-        if now
-            >= self
-                .expiry_ts
-                .checked_add(MAX_DELAY_SEC.into())
-                .ok_or(ErrorCode::Overflow)?
-        {
-            if self.outcome == Outcome::Open {
-                self.outcome = Outcome::Invalid;
+        match self.status().finalized_outcome(now)? {
+            Some(outcome) => {
+                self.finalized = true;
+                self.outcome = outcome;
+                Ok(true)
             }
-            self.finalized = true;
-            return Ok(true);
+            None => Ok(false),
         }
+    }
 
-        // Beyond resolution delay of the outcome.
-        if self.outcome != Outcome::Open
-            && now
-                >= self
-                    .outcome_ts
-                    .checked_add(self.resolution_delay.into())
-                    .ok_or(ErrorCode::Overflow)?
-        {
-            self.finalized = true;
-            return Ok(true);
-        }
+    /// Returns the amount the given position placed on the winning side, and
+    /// the winnings owed to it from the losing side before fees.
+    ///
+    /// Must only be called once the market has been finalized to either
+    /// [`Outcome::Yes`] or [`Outcome::No`].
+    pub fn winnings(&self, position: &UserPosition) -> (u64, u64) {
+        self.status().winnings(position)
+    }
+
+    /// Same as [Market::winnings], but records the amounts as paid out.
+    pub fn pay_out(&mut self, position: &UserPosition) -> Result<(u64, u64)> {
+        let (winning_num, winnings) = self.winnings(position);
+        self.claimed_stakes = self
+            .claimed_stakes
+            .checked_add(winning_num)
+            .ok_or(ErrorCode::Overflow)?;
+        self.paid_winnings = self
+            .paid_winnings
+            .checked_add(winnings)
+            .ok_or(ErrorCode::Overflow)?;
 
-        Ok(false)
+        Ok((winning_num, winnings))
+    }
+
+    /// Records a fee taken from paid out winnings, left in the losing side
+    /// until it is collected.
+    pub fn accrue_fee(&mut self, fee: u64) -> Result<()> {
+        self.accrued_fees = self
+            .accrued_fees
+            .checked_add(fee)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
     }
 
     /// Same as `is_and_set_finalize`, but errors if the market is finalized.
@@ -115,6 +326,71 @@ impl Market {
 
         Ok(())
     }
+
+    /// Returns the amount of the resolver bond held in escrow.
+    pub fn resolver_bond_outstanding(&self) -> u64 {
+        if self.resolver_bond_payer == Pubkey::default() {
+            return 0;
+        }
+
+        self.resolver_bond.saturating_sub(self.resolver_bond_paid)
+    }
+
+    /// Records the share of a slashed resolver bond paid to a disputing
+    /// position with the given stake, and returns it.
+    ///
+    /// Shares are pro rata to the disputed stake and rounded down, except for
+    /// the last position to be paid out, which receives the rest of the bond.
+    pub fn pay_out_slashed_bond(&mut self, stake: u64) -> Result<u64> {
+        let claimed = self
+            .slashed_stake_claimed
+            .checked_add(stake)
+            .ok_or(ErrorCode::Overflow)?;
+        let share = if claimed >= self.disputed_stake {
+            self.resolver_bond_outstanding()
+        } else {
+            (self.resolver_bond as u128 * stake as u128 / self.disputed_stake as u128) as u64
+        };
+
+        self.slashed_stake_claimed = claimed;
+        self.resolver_bond_paid = self
+            .resolver_bond_paid
+            .checked_add(share)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(share)
+    }
+
+    /// Records a new [UserPosition] of the market.
+    pub fn open_position(&mut self) -> Result<()> {
+        self.open_positions = self
+            .open_positions
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Records a migrated [UserPosition] of the market as open, failing if
+    /// all of its unversioned positions were already migrated.
+    pub fn migrate_position(&mut self) -> Result<()> {
+        self.unmigrated_positions = self
+            .unmigrated_positions
+            .checked_sub(1)
+            .ok_or(ErrorCode::NoUnmigratedPositions)?;
+
+        self.open_position()
+    }
+
+    /// Records a closed [UserPosition] of the market.
+    pub fn close_position(&mut self) -> Result<()> {
+        self.open_positions = self
+            .open_positions
+            .checked_sub(1)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
 }
 
 // TODO: Mock the Clock implementation.
@@ -133,7 +409,7 @@ mod tests {
 
         let result = market.finalize(0).unwrap();
 
-        assert_eq!(result, true);
+        assert!(result);
     }
 
     // Check that we finalize the market if it fails to fill all its funds for
@@ -148,8 +424,8 @@ mod tests {
 
         let result = market.finalize(0).unwrap();
 
-        assert_eq!(result, true);
-        assert_eq!(market.finalized, true);
+        assert!(result);
+        assert!(market.finalized);
         assert_eq!(market.outcome, Outcome::Invalid);
     }
 
@@ -165,8 +441,8 @@ mod tests {
 
         let result = market.finalize(0).unwrap();
 
-        assert_eq!(result, true);
-        assert_eq!(market.finalized, true);
+        assert!(result);
+        assert!(market.finalized);
         assert_eq!(market.outcome, Outcome::Invalid);
     }
 
@@ -175,14 +451,14 @@ mod tests {
     #[test]
     fn check_finalized_max_delay_invalid() {
         let mut market = Market {
-            resolution_delay: MAX_DELAY_SEC + 1,
+            resolution_delay: DEFAULT_RESOLUTION_WINDOW + 1,
             ..Default::default()
         };
 
-        let result = market.finalize(MAX_DELAY_SEC.into()).unwrap();
+        let result = market.finalize(DEFAULT_RESOLUTION_WINDOW.into()).unwrap();
 
-        assert_eq!(result, true);
-        assert_eq!(market.finalized, true);
+        assert!(result);
+        assert!(market.finalized);
         assert_eq!(market.outcome_ts, 0);
         assert_eq!(market.outcome, Outcome::Invalid);
     }
@@ -192,35 +468,169 @@ mod tests {
     #[test]
     fn check_finalized_max_delay_valid() {
         let mut market = Market {
-            resolution_delay: MAX_DELAY_SEC + 2,
+            resolution_delay: DEFAULT_RESOLUTION_WINDOW + 2,
             outcome_ts: 1,
             outcome: Outcome::Yes,
             ..Default::default()
         };
 
-        let result = market.finalize(MAX_DELAY_SEC.into()).unwrap();
+        let result = market.finalize(DEFAULT_RESOLUTION_WINDOW.into()).unwrap();
 
-        assert_eq!(result, true);
-        assert_eq!(market.finalized, true);
+        assert!(result);
+        assert!(market.finalized);
         assert_eq!(market.outcome, Outcome::Yes);
     }
 
+    // Check that a configured resolution window replaces the default one.
+    #[test]
+    fn check_finalized_resolution_window() {
+        let mut market = Market {
+            expiry_ts: 100,
+            resolution_delay: 60,
+            max_resolution_window: 3_600,
+            ..Default::default()
+        };
+
+        assert!(!market.finalize(3_699).unwrap());
+
+        let result = market.finalize(3_700).unwrap();
+
+        assert!(result);
+        assert!(market.finalized);
+        assert_eq!(market.outcome, Outcome::Invalid);
+    }
+
     // Check that we finalize the market to the given outcome if we've passed
     // the resolution delay from when the outcome was set.
     #[test]
     fn check_finalized_resolution_delay() {
         let mut market = Market {
             resolution_delay: 10,
-            outcome_ts: (MAX_DELAY_SEC - 20).into(),
+            outcome_ts: (DEFAULT_RESOLUTION_WINDOW - 20).into(),
             expiry_ts: 5,
             outcome: Outcome::Yes,
             ..Default::default()
         };
 
-        let result = market.finalize((MAX_DELAY_SEC - 10).into()).unwrap();
+        let result = market.finalize((DEFAULT_RESOLUTION_WINDOW - 10).into()).unwrap();
 
-        assert_eq!(result, true);
-        assert_eq!(market.finalized, true);
+        assert!(result);
+        assert!(market.finalized);
         assert_eq!(market.outcome, Outcome::Yes);
     }
+
+    // Check that we finalize the market if it requires acknowledgement and
+    // the resolver has not acknowledged it by the close time.
+    #[test]
+    fn check_finalized_unacknowledged() {
+        let mut market = Market {
+            close_ts: 10,
+            expiry_ts: 20,
+            require_acknowledgement: true,
+            ..Default::default()
+        };
+
+        assert!(!market.finalize(9).unwrap());
+
+        let result = market.finalize(10).unwrap();
+
+        assert!(result);
+        assert!(market.finalized);
+        assert_eq!(market.outcome, Outcome::Invalid);
+    }
+
+    // Check that an acknowledged market is not finalized at the close time.
+    #[test]
+    fn check_not_finalized_acknowledged() {
+        let mut market = Market {
+            close_ts: 10,
+            expiry_ts: 20,
+            require_acknowledgement: true,
+            acknowledged: true,
+            ..Default::default()
+        };
+
+        let result = market.finalize(10).unwrap();
+
+        assert!(!result);
+        assert!(!market.finalized);
+    }
+
+    // Check that the category is found at the advertised offset in the
+    // account data.
+    #[test]
+    fn check_category_offset() {
+        let mut market = Market::default();
+        market.metadata.category = [7; 16];
+
+        let mut data = Vec::new();
+        market.try_serialize(&mut data).unwrap();
+
+        assert_eq!(data.len(), 8 + Market::LEN);
+        assert_eq!(data[Market::CATEGORY_OFFSET..][..16], [7; 16]);
+    }
+
+    // Check that winnings are taken pro rata from the losing side, rounding
+    // down, and that only the winning side of the position counts.
+    #[test]
+    fn check_winnings() {
+        let mut market = Market {
+            yes_amount: 3,
+            no_amount: 10,
+            outcome: Outcome::Yes,
+            ..Default::default()
+        };
+        let position = UserPosition {
+            yes_amount: 1,
+            no_amount: 5,
+            ..Default::default()
+        };
+
+        assert_eq!(market.winnings(&position), (1, 3));
+
+        market.outcome = Outcome::No;
+
+        assert_eq!(market.winnings(&position), (5, 1));
+    }
+
+    // Check that the last position paid out on the winning side receives
+    // the rest of the losing side.
+    #[test]
+    fn check_pay_out_remainder() {
+        let mut market = Market {
+            yes_amount: 3,
+            no_amount: 10,
+            outcome: Outcome::Yes,
+            ..Default::default()
+        };
+        let position = UserPosition {
+            yes_amount: 1,
+            ..Default::default()
+        };
+
+        assert_eq!(market.pay_out(&position).unwrap(), (1, 3));
+        assert_eq!(market.pay_out(&position).unwrap(), (1, 3));
+        assert_eq!(market.winnings(&position), (1, 4));
+        assert_eq!(market.pay_out(&position).unwrap(), (1, 4));
+        assert_eq!(market.claimed_stakes, 3);
+        assert_eq!(market.paid_winnings, 10);
+    }
+
+    // Check that a slashed resolver bond is shared pro rata between the
+    // disputing positions, the last one receiving the rest.
+    #[test]
+    fn check_pay_out_slashed_bond() {
+        let mut market = Market {
+            resolver_bond: 100,
+            resolver_bond_payer: Pubkey::new_unique(),
+            disputed_stake: 30,
+            ..Default::default()
+        };
+
+        assert_eq!(market.pay_out_slashed_bond(10).unwrap(), 33);
+        assert_eq!(market.pay_out_slashed_bond(10).unwrap(), 33);
+        assert_eq!(market.pay_out_slashed_bond(10).unwrap(), 34);
+        assert_eq!(market.resolver_bond_outstanding(), 0);
+        assert_eq!(market.slashed_stake_claimed, 30);
+    }
 }
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+
+/// The size of the space reserved for future fields of [MarketCounter].
+pub const MARKET_COUNTER_RESERVED_LEN: usize = 64;
+
+/// Hands out the nonces of the markets created at PDAs by a given creator.
+///
+/// Markets created by the creator are found at the PDAs
+/// `[b"market", creator, nonce]` for every nonce below `next_nonce`.
+#[account]
+pub struct MarketCounter {
+    /// The version of the account layout.
+    pub version: u8,
+    /// The creator the markets belong to.
+    pub creator: Pubkey,
+    /// The nonce of the next market created by the creator.
+    pub next_nonce: u64,
+    /// Space reserved for future fields.
+    pub reserved: [u8; MARKET_COUNTER_RESERVED_LEN],
+}
+
+impl Default for MarketCounter {
+    fn default() -> Self {
+        MarketCounter {
+            version: MarketCounter::VERSION,
+            creator: Pubkey::default(),
+            next_nonce: 0,
+            reserved: [0; MARKET_COUNTER_RESERVED_LEN],
+        }
+    }
+}
+
+impl MarketCounter {
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1 + 32 + 8 + MARKET_COUNTER_RESERVED_LEN;
+}
@@ -0,0 +1,179 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::UriResource;
+
+/// The maximum length of a market title in bytes.
+pub const TITLE_MAX_LEN: usize = 64;
+
+/// The length of a market category tag in bytes.
+pub const CATEGORY_LEN: usize = 16;
+
+/// Descriptive information about a [Market](crate::state::Market).
+///
+/// The category is stored first, so that clients can filter markets by
+/// category with a `memcmp` at a fixed offset.
+#[derive(Clone, Default, AnchorDeserialize, AnchorSerialize)]
+pub struct MarketMetadata {
+    /// The version of the metadata layout.
+    pub version: u8,
+    /// The category tag, as ASCII padded with trailing zeroes. All zeroes if
+    /// the market is uncategorized.
+    pub category: [u8; CATEGORY_LEN],
+    /// The SHA-256 hash of the off-chain JSON document found at `uri`.
+    pub content_hash: [u8; 32],
+    /// A short title for the market.
+    pub title: MarketTitle,
+    /// The URI to the market's off-chain JSON document.
+    pub uri: UriResource,
+}
+
+impl MarketMetadata {
+    /// The current version of the metadata layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1 + CATEGORY_LEN + 32 + MarketTitle::LEN + UriResource::LEN;
+
+    /// The offset of the category tag within the metadata.
+    pub const CATEGORY_OFFSET: usize = 1;
+
+    /// Validates the metadata.
+    pub fn validate(
+        uri: &str,
+        title: &str,
+        category: [u8; CATEGORY_LEN],
+        content_hash: [u8; 32],
+    ) -> Result<MarketMetadata> {
+        Ok(MarketMetadata {
+            version: Self::VERSION,
+            category: validate_category(category)?,
+            content_hash,
+            title: MarketTitle::validate(title)?,
+            uri: UriResource::validate(uri)?,
+        })
+    }
+}
+
+/// Checks that the category is printable ASCII followed only by zero padding.
+fn validate_category(category: [u8; CATEGORY_LEN]) -> Result<[u8; CATEGORY_LEN]> {
+    let len = category
+        .iter()
+        .position(|&b| b == 0)
+        .unwrap_or(CATEGORY_LEN);
+    let (tag, padding) = category.split_at(len);
+
+    if !tag.iter().all(|b| b.is_ascii_graphic()) || padding.iter().any(|&b| b != 0) {
+        return Err(error!(ErrorCode::InvalidMarketCategory));
+    }
+
+    Ok(category)
+}
+
+/// A short string title.
+#[derive(Clone, AnchorDeserialize, AnchorSerialize)]
+pub struct MarketTitle {
+    /// The length of the title.
+    pub len: u8,
+    /// The title buffer.
+    pub title: [u8; TITLE_MAX_LEN],
+}
+
+impl Default for MarketTitle {
+    fn default() -> Self {
+        MarketTitle {
+            len: 0,
+            title: [0u8; TITLE_MAX_LEN],
+        }
+    }
+}
+
+impl MarketTitle {
+    /// Validates the title, which must be non-empty and fit in
+    /// [TITLE_MAX_LEN] bytes.
+    pub fn validate(title: &str) -> Result<MarketTitle> {
+        let len = title.len();
+        if len == 0 || len > TITLE_MAX_LEN || title.chars().any(char::is_control) {
+            return Err(error!(ErrorCode::InvalidMarketTitle));
+        }
+
+        let mut bytes = [0; TITLE_MAX_LEN];
+        bytes[..len].copy_from_slice(title.as_bytes());
+
+        Ok(MarketTitle {
+            len: len as u8,
+            title: bytes,
+        })
+    }
+
+    /// Returns the title as a string, or [None] if the stored bytes are not
+    /// valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        let bytes = self.title.get(..self.len as usize)?;
+        std::str::from_utf8(bytes).ok()
+    }
+
+    pub const LEN: usize = 1 + TITLE_MAX_LEN;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(tag: &[u8]) -> [u8; CATEGORY_LEN] {
+        let mut category = [0; CATEGORY_LEN];
+        category[..tag.len()].copy_from_slice(tag);
+        category
+    }
+
+    // Checks that valid metadata is stored with the current version.
+    #[test]
+    fn check_valid_metadata() {
+        let metadata = MarketMetadata::validate(
+            "https://example.com/market.json",
+            "Will it rain tomorrow?",
+            category(b"weather"),
+            [1; 32],
+        )
+        .unwrap();
+
+        assert_eq!(metadata.version, MarketMetadata::VERSION);
+        assert_eq!(metadata.category, category(b"weather"));
+        assert_eq!(metadata.content_hash, [1; 32]);
+        assert_eq!(metadata.title.len, 22);
+    }
+
+    // Checks that the category is found at the advertised offset.
+    #[test]
+    fn check_category_offset() {
+        let metadata = MarketMetadata {
+            category: category(b"sports"),
+            ..Default::default()
+        };
+        let bytes = metadata.try_to_vec().unwrap();
+
+        assert_eq!(bytes.len(), MarketMetadata::LEN);
+        assert_eq!(
+            &bytes[MarketMetadata::CATEGORY_OFFSET..][..CATEGORY_LEN],
+            &category(b"sports"),
+        );
+    }
+
+    // Checks that categories must be ASCII padded with zeroes.
+    #[test]
+    fn check_invalid_category() {
+        assert!(validate_category([0; CATEGORY_LEN]).is_ok());
+        assert!(validate_category([b'a'; CATEGORY_LEN]).is_ok());
+        assert!(validate_category(category(b"a b")).is_err());
+        assert!(validate_category(category(b"a\0b")).is_err());
+        assert!(validate_category(category(&[0xff])).is_err());
+    }
+
+    // Checks that titles must be non-empty and fit in the buffer.
+    #[test]
+    fn check_invalid_title() {
+        assert!(MarketTitle::validate("").is_err());
+        assert!(MarketTitle::validate("a\nb").is_err());
+        assert!(MarketTitle::validate(&"a".repeat(TITLE_MAX_LEN)).is_ok());
+        assert!(MarketTitle::validate(&"a".repeat(TITLE_MAX_LEN + 1)).is_err());
+    }
+}
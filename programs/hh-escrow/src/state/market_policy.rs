@@ -0,0 +1,129 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Limits on the parameters of new markets, set by the owner of the global
+/// state. A maximum of 0 means there is no maximum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, AnchorDeserialize, AnchorSerialize)]
+pub struct MarketPolicy {
+    /// The minimum number of seconds between the creation and the close of a
+    /// market.
+    pub min_close_delay: u32,
+    /// The maximum number of seconds between the creation and the close of a
+    /// market.
+    pub max_close_delay: u32,
+    /// The maximum number of seconds between the close and the expiry of a
+    /// market.
+    pub max_expiry_gap: u32,
+    /// The minimum resolution delay of a market.
+    pub min_resolution_delay: u32,
+    /// The maximum resolution delay of a market.
+    pub max_resolution_delay: u32,
+    /// The maximum length of the URI of a market.
+    pub max_uri_len: u16,
+}
+
+impl MarketPolicy {
+    pub const LEN: usize = 5 * 4 + 2;
+
+    /// Validates that every minimum is below its maximum.
+    pub fn validate(&self) -> Result<()> {
+        if self.max_close_delay != 0 && self.min_close_delay > self.max_close_delay {
+            return Err(error!(ErrorCode::InvalidMarketPolicy));
+        }
+        if self.max_resolution_delay != 0 && self.min_resolution_delay > self.max_resolution_delay {
+            return Err(error!(ErrorCode::InvalidMarketPolicy));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the close and expiry timestamps of a market created at `now`.
+    pub fn check_schedule(&self, now: u64, close_ts: u64, expiry_ts: u64) -> Result<()> {
+        let close_delay = close_ts.saturating_sub(now);
+        if close_delay < self.min_close_delay.into() {
+            return Err(error!(ErrorCode::CloseTooSoon));
+        }
+        if self.max_close_delay != 0 && close_delay > self.max_close_delay.into() {
+            return Err(error!(ErrorCode::CloseTooLate));
+        }
+        if self.max_expiry_gap != 0
+            && expiry_ts.saturating_sub(close_ts) > self.max_expiry_gap.into()
+        {
+            return Err(error!(ErrorCode::ExpiryTooLate));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the resolution delay of a market.
+    pub fn check_resolution_delay(&self, resolution_delay: u32) -> Result<()> {
+        if resolution_delay < self.min_resolution_delay {
+            return Err(error!(ErrorCode::ResolutionDelayTooShort));
+        }
+        if self.max_resolution_delay != 0 && resolution_delay > self.max_resolution_delay {
+            return Err(error!(ErrorCode::ResolutionDelayAbovePolicy));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the URI of a market.
+    pub fn check_uri(&self, uri: &str) -> Result<()> {
+        if self.max_uri_len != 0 && uri.len() > self.max_uri_len.into() {
+            return Err(error!(ErrorCode::UriTooLong));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that the default policy does not restrict markets.
+    #[test]
+    fn check_no_policy() {
+        let policy = MarketPolicy::default();
+
+        assert!(policy.validate().is_ok());
+        assert!(policy.check_schedule(100, 100, u64::MAX).is_ok());
+        assert!(policy.check_resolution_delay(u32::MAX).is_ok());
+        assert!(policy.check_uri(&"a".repeat(1_000)).is_ok());
+    }
+
+    // Checks the bounds on the close and expiry timestamps.
+    #[test]
+    fn check_schedule() {
+        let policy = MarketPolicy {
+            min_close_delay: 10,
+            max_close_delay: 20,
+            max_expiry_gap: 5,
+            ..Default::default()
+        };
+
+        assert!(policy.check_schedule(100, 109, 110).is_err());
+        assert!(policy.check_schedule(100, 110, 115).is_ok());
+        assert!(policy.check_schedule(100, 120, 120).is_ok());
+        assert!(policy.check_schedule(100, 121, 121).is_err());
+        assert!(policy.check_schedule(100, 110, 116).is_err());
+    }
+
+    // Checks that a minimum above its maximum is rejected.
+    #[test]
+    fn check_validate() {
+        let policy = MarketPolicy {
+            min_resolution_delay: 10,
+            max_resolution_delay: 9,
+            ..Default::default()
+        };
+        assert!(policy.validate().is_err());
+
+        let policy = MarketPolicy {
+            min_close_delay: 10,
+            ..Default::default()
+        };
+        assert!(policy.validate().is_ok());
+    }
+}
@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+
+/// Seed used to derive the [MarketRegistry] of a creator.
+pub const CREATOR_REGISTRY_SEED: &[u8] = b"creator";
+
+/// Seed used to derive the [MarketRegistry] of a token mint.
+pub const MINT_REGISTRY_SEED: &[u8] = b"mint";
+
+/// The number of markets held by a [MarketRegistryPage].
+pub const REGISTRY_PAGE_LEN: usize = 32;
+
+/// The size of the space reserved for future fields of [MarketRegistry].
+pub const MARKET_REGISTRY_RESERVED_LEN: usize = 64;
+
+/// The size of the space reserved for future fields of [MarketRegistryPage].
+pub const MARKET_REGISTRY_PAGE_RESERVED_LEN: usize = 32;
+
+/// An on-chain index of the markets of a creator or of a token mint.
+///
+/// The registry is found at the PDA `[b"registry", kind, key]`, where `kind`
+/// is [CREATOR_REGISTRY_SEED] or [MINT_REGISTRY_SEED]. Its markets are held
+/// by the pages found at the PDAs `[b"registry_page", registry, page]` for
+/// every page up to `tail_page`.
+#[account]
+pub struct MarketRegistry {
+    /// The version of the account layout.
+    pub version: u8,
+    /// The creator or token mint the markets are indexed by.
+    pub key: Pubkey,
+    /// The page new markets are appended to.
+    pub tail_page: u32,
+    /// The number of markets currently in the registry.
+    pub market_count: u64,
+    /// Space reserved for future fields.
+    pub reserved: [u8; MARKET_REGISTRY_RESERVED_LEN],
+}
+
+impl Default for MarketRegistry {
+    fn default() -> Self {
+        MarketRegistry {
+            version: MarketRegistry::VERSION,
+            key: Pubkey::default(),
+            tail_page: 0,
+            market_count: 0,
+            reserved: [0; MARKET_REGISTRY_RESERVED_LEN],
+        }
+    }
+}
+
+impl MarketRegistry {
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1 + 32 + 4 + 8 + MARKET_REGISTRY_RESERVED_LEN;
+
+    /// Appends a market to the tail page of the registry, and moves on to the
+    /// next page once the tail page is full.
+    pub fn append(
+        &mut self,
+        registry: Pubkey,
+        key: Pubkey,
+        page: &mut MarketRegistryPage,
+        market: Pubkey,
+    ) -> Result<()> {
+        self.version = MarketRegistry::VERSION;
+        self.key = key;
+
+        page.version = MarketRegistryPage::VERSION;
+        page.registry = registry;
+        page.page = self.tail_page;
+        page.push(market)?;
+
+        if page.is_full() {
+            self.tail_page = self
+                .tail_page
+                .checked_add(1)
+                .ok_or_else(|| error!(ErrorCode::Overflow))?;
+        }
+        self.market_count = self
+            .market_count
+            .checked_add(1)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+        Ok(())
+    }
+
+    /// Removes a market from the given page of the registry.
+    pub fn remove(&mut self, page: &mut MarketRegistryPage, market: &Pubkey) -> Result<()> {
+        page.remove(market)?;
+
+        self.market_count = self
+            .market_count
+            .checked_sub(1)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+        Ok(())
+    }
+}
+
+/// A page of a [MarketRegistry].
+///
+/// Markets are only ever appended to a page. Removed markets leave the
+/// default public key in their slot.
+#[account]
+pub struct MarketRegistryPage {
+    /// The version of the account layout.
+    pub version: u8,
+    /// The registry this page belongs to.
+    pub registry: Pubkey,
+    /// The index of this page in the registry.
+    pub page: u32,
+    /// The number of slots used so far.
+    pub len: u16,
+    /// The markets in this page.
+    pub markets: [Pubkey; REGISTRY_PAGE_LEN],
+    /// Space reserved for future fields.
+    pub reserved: [u8; MARKET_REGISTRY_PAGE_RESERVED_LEN],
+}
+
+impl Default for MarketRegistryPage {
+    fn default() -> Self {
+        MarketRegistryPage {
+            version: MarketRegistryPage::VERSION,
+            registry: Pubkey::default(),
+            page: 0,
+            len: 0,
+            markets: [Pubkey::default(); REGISTRY_PAGE_LEN],
+            reserved: [0; MARKET_REGISTRY_PAGE_RESERVED_LEN],
+        }
+    }
+}
+
+impl MarketRegistryPage {
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize =
+        1 + 32 + 4 + 2 + 32 * REGISTRY_PAGE_LEN + MARKET_REGISTRY_PAGE_RESERVED_LEN;
+
+    /// Checks whether every slot of the page has been used.
+    pub fn is_full(&self) -> bool {
+        self.len as usize >= REGISTRY_PAGE_LEN
+    }
+
+    /// Returns the markets in the page, skipping removed markets.
+    pub fn markets(&self) -> impl Iterator<Item = &Pubkey> {
+        self.markets[..self.len as usize]
+            .iter()
+            .filter(|market| **market != Pubkey::default())
+    }
+
+    fn push(&mut self, market: Pubkey) -> Result<()> {
+        if self.is_full() {
+            return Err(error!(ErrorCode::RegistryPageFull));
+        }
+
+        self.markets[self.len as usize] = market;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    fn remove(&mut self, market: &Pubkey) -> Result<()> {
+        let slot = self.markets[..self.len as usize]
+            .iter_mut()
+            .find(|slot| *slot == market)
+            .ok_or_else(|| error!(ErrorCode::MarketNotRegistered))?;
+        *slot = Pubkey::default();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that markets fill a page before moving on to the next page.
+    #[test]
+    fn check_append_moves_to_next_page() {
+        let mut registry = MarketRegistry::default();
+        let mut page = MarketRegistryPage::default();
+
+        for _ in 0..REGISTRY_PAGE_LEN {
+            assert_eq!(registry.tail_page, 0);
+            registry
+                .append(Pubkey::default(), Pubkey::default(), &mut page, Pubkey::new_unique())
+                .unwrap();
+        }
+
+        assert_eq!(registry.tail_page, 1);
+        assert_eq!(registry.market_count, REGISTRY_PAGE_LEN as u64);
+        assert!(page.is_full());
+        assert!(page.push(Pubkey::new_unique()).is_err());
+    }
+
+    // Checks that removed markets are skipped, and cannot be removed twice.
+    #[test]
+    fn check_remove() {
+        let mut registry = MarketRegistry::default();
+        let mut page = MarketRegistryPage::default();
+        let markets = [Pubkey::new_unique(), Pubkey::new_unique()];
+
+        for market in markets {
+            registry
+                .append(Pubkey::default(), Pubkey::default(), &mut page, market)
+                .unwrap();
+        }
+        registry.remove(&mut page, &markets[0]).unwrap();
+
+        assert_eq!(registry.market_count, 1);
+        assert_eq!(page.markets().collect::<Vec<_>>(), vec![&markets[1]]);
+        assert!(registry.remove(&mut page, &markets[0]).is_err());
+    }
+
+    // Checks that the advertised sizes match the serialized sizes.
+    #[test]
+    fn check_len() {
+        let mut data = Vec::new();
+        MarketRegistry::default().try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + MarketRegistry::LEN);
+
+        let mut data = Vec::new();
+        MarketRegistryPage::default().try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + MarketRegistryPage::LEN);
+    }
+}
@@ -0,0 +1,221 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{Outcome, UserPosition, DEFAULT_RESOLUTION_WINDOW};
+
+/// The fields of a market that determine its outcome and payouts.
+///
+/// This is small enough to copy onto the stack, so that both [Market] and
+/// [MarketZc] apply the same rules without loading the whole account.
+///
+/// [Market]: crate::state::Market
+/// [MarketZc]: crate::state::MarketZc
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MarketStatus {
+    pub yes_amount: u64,
+    pub yes_filled: u64,
+    pub no_amount: u64,
+    pub no_filled: u64,
+    pub close_ts: u64,
+    pub expiry_ts: u64,
+    pub outcome_ts: u64,
+    pub resolution_delay: u32,
+    pub max_resolution_window: u32,
+    pub disputed: bool,
+    pub arbitrated: bool,
+    pub outcome: Outcome,
+    pub acknowledged: bool,
+    pub require_acknowledgement: bool,
+    pub claimed_stakes: u64,
+    pub paid_winnings: u64,
+}
+
+impl MarketStatus {
+    /// Returns the number of seconds after the expiry after which the
+    /// outcome is final.
+    pub fn resolution_window(&self) -> u32 {
+        match self.max_resolution_window {
+            0 => DEFAULT_RESOLUTION_WINDOW,
+            window => window,
+        }
+    }
+
+    /// Returns the outcome the market should be finalized to, or [None] if
+    /// it should not be finalized yet.
+    pub fn finalized_outcome(&self, now: u64) -> Result<Option<Outcome>> {
+        // Failed to fill funds.
+        if (self.yes_filled < self.yes_amount || self.no_filled < self.no_amount)
+            && now >= self.close_ts
+        {
+            return Ok(Some(Outcome::Invalid));
+        }
+
+        // Resolver never acknowledged a market that requires it.
+        if self.require_acknowledgement && !self.acknowledged && now >= self.close_ts {
+            return Ok(Some(Outcome::Invalid));
+        }
+
+        // Beyond the resolution window of the expiry.
+        if now
+            >= self
+                .expiry_ts
+                .checked_add(self.resolution_window().into())
+                .ok_or(ErrorCode::Overflow)?
+        {
+            if self.outcome == Outcome::Open {
+                return Ok(Some(Outcome::Invalid));
+            }
+            return Ok(Some(self.outcome));
+        }
+
+        // Beyond resolution delay of the outcome, unless it awaits the
+        // arbiter.
+        if self.outcome != Outcome::Open
+            && !self.disputed
+            && now
+                >= self
+                    .outcome_ts
+                    .checked_add(self.resolution_delay.into())
+                    .ok_or(ErrorCode::Overflow)?
+        {
+            return Ok(Some(self.outcome));
+        }
+
+        Ok(None)
+    }
+
+    /// Checks whether the creation bond is forfeited, which is the case if
+    /// the market is invalid because it was not filled or never resolved.
+    /// A market ruled invalid by the arbiter, or set invalid by its resolver,
+    /// was resolved, so the bond is refunded.
+    ///
+    /// Must only be called once the market has been finalized.
+    pub fn forfeits_creation_bond(&self) -> bool {
+        if self.outcome != Outcome::Invalid || self.arbitrated {
+            return false;
+        }
+
+        let unfilled = self.yes_filled < self.yes_amount || self.no_filled < self.no_amount;
+        // The resolver never set an outcome, or set the market back to open.
+        let never_resolved = self.outcome_ts == 0;
+
+        unfilled || never_resolved
+    }
+
+    /// Returns the amount the given position placed on the winning side, and
+    /// the winnings owed to it from the losing side before fees.
+    ///
+    /// Winnings are pro rata and rounded down, except for the last position
+    /// to be paid out, which receives the rest of the losing side.
+    ///
+    /// Must only be called once the market has been finalized to either
+    /// [`Outcome::Yes`] or [`Outcome::No`].
+    pub fn winnings(&self, position: &UserPosition) -> (u64, u64) {
+        let (winning_num, winning_denom, pool) = match self.outcome {
+            Outcome::Yes => (position.yes_amount, self.yes_amount, self.no_amount),
+            Outcome::No => (position.no_amount, self.no_amount, self.yes_amount),
+            _ => unreachable!(),
+        };
+
+        // The last position on the winning side takes what is left of the
+        // pool, so that no rounding dust is stranded in the losing escrow.
+        if winning_num > 0 && self.claimed_stakes.saturating_add(winning_num) == winning_denom {
+            return (winning_num, pool.saturating_sub(self.paid_winnings));
+        }
+
+        // Both numbers are u64, so this should not overflow. Morever, num / denom *
+        // pool <= pool, so the cast to u64 should not lose information beyond any
+        // fractional portion of the division.
+        let winnings = (((winning_num as u128) * (pool as u128)) / (winning_denom as u128)) as u64;
+
+        (winning_num, winnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A filled market, finalized to invalid after the resolver set it so.
+    fn resolved_invalid() -> MarketStatus {
+        MarketStatus {
+            yes_amount: 10,
+            yes_filled: 10,
+            no_amount: 20,
+            no_filled: 20,
+            outcome_ts: 100,
+            outcome: Outcome::Invalid,
+            ..Default::default()
+        }
+    }
+
+    // Checks that the bond is forfeited if the yes side was not filled.
+    #[test]
+    fn check_forfeits_unfilled_yes() {
+        let status = MarketStatus {
+            yes_filled: 9,
+            outcome_ts: 0,
+            ..resolved_invalid()
+        };
+
+        assert!(status.forfeits_creation_bond());
+    }
+
+    // Checks that the bond is forfeited if the no side was not filled.
+    #[test]
+    fn check_forfeits_unfilled_no() {
+        let status = MarketStatus {
+            no_filled: 19,
+            outcome_ts: 0,
+            ..resolved_invalid()
+        };
+
+        assert!(status.forfeits_creation_bond());
+    }
+
+    // Checks that the bond is forfeited if the resolver never set an outcome
+    // before the resolution window passed.
+    #[test]
+    fn check_forfeits_never_resolved() {
+        let status = MarketStatus {
+            outcome_ts: 0,
+            ..resolved_invalid()
+        };
+
+        assert!(status.forfeits_creation_bond());
+    }
+
+    // Checks that the bond is refunded if the resolver set the market to
+    // invalid.
+    #[test]
+    fn check_refunds_resolved_invalid() {
+        assert!(!resolved_invalid().forfeits_creation_bond());
+    }
+
+    // Checks that the bond is refunded if the arbiter ruled the market
+    // invalid, whatever its other fields.
+    #[test]
+    fn check_refunds_arbitrated_invalid() {
+        let status = MarketStatus {
+            yes_filled: 9,
+            outcome_ts: 0,
+            arbitrated: true,
+            ..resolved_invalid()
+        };
+
+        assert!(!status.forfeits_creation_bond());
+    }
+
+    // Checks that the bond is refunded if the market finalized to a side.
+    #[test]
+    fn check_refunds_yes_or_no() {
+        for outcome in [Outcome::Yes, Outcome::No] {
+            let status = MarketStatus {
+                outcome,
+                ..resolved_invalid()
+            };
+
+            assert!(!status.forfeits_creation_bond());
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{DepositLimits, MarketTitle, UriResource, CATEGORY_LEN};
+
+/// The maximum length of the instruction data passed to the resolver program.
+pub const RESOLVER_DATA_MAX_LEN: usize = 256;
+
+/// The size of the space reserved for future fields of [MarketTemplate].
+pub const MARKET_TEMPLATE_RESERVED_LEN: usize = 115;
+
+/// A recurring market, from which a new [Market](crate::state::Market) can be
+/// spawned every `cadence` seconds.
+///
+/// Markets spawned from the template are found at the PDAs
+/// `[b"market", template, index]` for every index below `spawned`.
+#[account]
+pub struct MarketTemplate {
+    /// The version of the account layout.
+    pub version: u8,
+    /// Creator of the template, and of every market spawned from it.
+    pub creator: Pubkey,
+    /// The token the spawned markets are denominated in.
+    pub token_mint: Pubkey,
+    /// Resolver for the spawned markets. Ignored if `resolver_program` is set.
+    pub resolver: Pubkey,
+    /// The program invoked to initialize the resolver of each spawned market.
+    /// The default public key if there is none.
+    pub resolver_program: Pubkey,
+    /// The amount of tokens to fill the yes side.
+    pub yes_amount: u64,
+    /// The amount of tokens to fill the no side.
+    pub no_amount: u64,
+    /// Limits on deposits into the yes side.
+    pub yes_limits: DepositLimits,
+    /// Limits on deposits into the no side.
+    pub no_limits: DepositLimits,
+    /// The delay in seconds before the outcome is finalized.
+    pub resolution_delay: u32,
+    /// Whether deposits are refused until the resolver has acknowledged a
+    /// spawned market.
+    pub require_acknowledgement: bool,
+    /// Whether the resolver may resolve a spawned market before its expiry
+    /// time.
+    pub allow_early_resolution: bool,
+    /// The resolution window of the spawned markets, in seconds after their
+    /// expiry.
+    pub max_resolution_window: u32,
+    /// The close timestamp of the next market to spawn.
+    pub close_ts: u64,
+    /// The expiry timestamp of the next market to spawn.
+    pub expiry_ts: u64,
+    /// The number of seconds between two consecutive markets.
+    pub cadence: u64,
+    /// The number of markets spawned so far.
+    pub spawned: u64,
+    /// The category tag of the spawned markets.
+    pub category: [u8; CATEGORY_LEN],
+    /// The title of the spawned markets.
+    pub title: MarketTitle,
+    /// The URI of a spawned market is this prefix followed by its index.
+    pub uri_prefix: UriResource,
+    /// The length of `resolver_data`.
+    pub resolver_data_len: u16,
+    /// The instruction data passed to `resolver_program`.
+    pub resolver_data: [u8; RESOLVER_DATA_MAX_LEN],
+    /// The bond the resolver of each spawned market posts when acknowledging
+    /// it, in the token mint.
+    pub resolver_bond: u64,
+    /// Space reserved for future fields.
+    pub reserved: [u8; MARKET_TEMPLATE_RESERVED_LEN],
+}
+
+impl Default for MarketTemplate {
+    fn default() -> Self {
+        MarketTemplate {
+            version: MarketTemplate::VERSION,
+            creator: Pubkey::default(),
+            token_mint: Pubkey::default(),
+            resolver: Pubkey::default(),
+            resolver_program: Pubkey::default(),
+            yes_amount: 0,
+            no_amount: 0,
+            yes_limits: DepositLimits::default(),
+            no_limits: DepositLimits::default(),
+            resolution_delay: 0,
+            require_acknowledgement: false,
+            allow_early_resolution: false,
+            max_resolution_window: 0,
+            close_ts: 0,
+            expiry_ts: 0,
+            cadence: 0,
+            spawned: 0,
+            category: [0; CATEGORY_LEN],
+            title: MarketTitle::default(),
+            uri_prefix: UriResource::default(),
+            resolver_data_len: 0,
+            resolver_data: [0; RESOLVER_DATA_MAX_LEN],
+            resolver_bond: 0,
+            reserved: [0; MARKET_TEMPLATE_RESERVED_LEN],
+        }
+    }
+}
+
+impl MarketTemplate {
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1
+        + 4 * 32
+        + 2 * 8
+        + 2 * DepositLimits::LEN
+        + 4
+        + 1
+        + 1
+        + 4
+        + 4 * 8
+        + CATEGORY_LEN
+        + MarketTitle::LEN
+        + UriResource::LEN
+        + 2
+        + RESOLVER_DATA_MAX_LEN
+        + 8
+        + MARKET_TEMPLATE_RESERVED_LEN;
+
+    /// Checks whether spawned markets have their resolver initialized by a
+    /// resolver program.
+    pub fn has_resolver_program(&self) -> bool {
+        self.resolver_program != Pubkey::default()
+    }
+
+    /// Returns the instruction data passed to the resolver program.
+    pub fn resolver_data(&self) -> &[u8] {
+        &self.resolver_data[..self.resolver_data_len as usize]
+    }
+
+    /// Returns the URI of the market spawned at the given index.
+    pub fn uri(&self, index: u64) -> Option<String> {
+        Some(format!("{}{}", self.uri_prefix.as_str()?, index))
+    }
+
+    /// Returns the close and expiry timestamps of the next market to spawn.
+    ///
+    /// Periods whose close time has already passed are skipped. Fails if the
+    /// last spawned market has not closed yet, so that only one market of the
+    /// series accepts deposits at a time.
+    pub fn next_schedule(&self, now: u64) -> Result<(u64, u64)> {
+        if self.spawned > 0 && self.close_ts.saturating_sub(self.cadence) > now {
+            return Err(error!(ErrorCode::TemplateNotDue));
+        }
+
+        let periods = match now.checked_sub(self.close_ts) {
+            Some(late) if late > 0 => (late - 1) / self.cadence + 1,
+            _ => 0,
+        };
+        let shift = periods
+            .checked_mul(self.cadence)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+        let close_ts = self
+            .close_ts
+            .checked_add(shift)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+        let expiry_ts = self
+            .expiry_ts
+            .checked_add(shift)
+            .ok_or_else(|| error!(ErrorCode::Overflow))?;
+
+        Ok((close_ts, expiry_ts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(close_ts: u64, spawned: u64) -> MarketTemplate {
+        MarketTemplate {
+            close_ts,
+            expiry_ts: close_ts + 5,
+            cadence: 10,
+            spawned,
+            ..Default::default()
+        }
+    }
+
+    // Checks that the stored schedule is used while it is still in the future.
+    #[test]
+    fn check_schedule_on_time() {
+        assert_eq!(template(100, 0).next_schedule(0).unwrap(), (100, 105));
+        assert_eq!(template(100, 3).next_schedule(90).unwrap(), (100, 105));
+        assert_eq!(template(100, 3).next_schedule(100).unwrap(), (100, 105));
+    }
+
+    // Checks that missed periods are skipped, landing on the first close time
+    // that has not passed yet.
+    #[test]
+    fn check_schedule_skips_missed_periods() {
+        assert_eq!(template(100, 3).next_schedule(101).unwrap(), (110, 115));
+        assert_eq!(template(100, 3).next_schedule(110).unwrap(), (110, 115));
+        assert_eq!(template(100, 3).next_schedule(135).unwrap(), (140, 145));
+    }
+
+    // Checks that the next market cannot be spawned while the last spawned
+    // market is still open, except for the first market.
+    #[test]
+    fn check_schedule_not_due() {
+        assert!(template(100, 1).next_schedule(89).is_err());
+        assert!(template(100, 0).next_schedule(89).is_ok());
+    }
+
+    // Checks that the URI of a spawned market ends with its index.
+    #[test]
+    fn check_uri() {
+        let template = MarketTemplate {
+            uri_prefix: UriResource::validate("https://example.com/weekly/").unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            template.uri(12).as_deref(),
+            Some("https://example.com/weekly/12")
+        );
+    }
+
+    // Checks that the advertised size matches the serialized size.
+    #[test]
+    fn check_len() {
+        let mut data = Vec::new();
+        MarketTemplate::default().try_serialize(&mut data).unwrap();
+
+        assert_eq!(data.len(), 8 + MarketTemplate::LEN);
+    }
+}
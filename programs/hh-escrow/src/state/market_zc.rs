@@ -0,0 +1,247 @@
+use std::cell::RefMut;
+
+use anchor_lang::prelude::*;
+use anchor_lang::{Discriminator, ZeroCopy};
+use bytemuck::{Pod, Zeroable};
+
+use crate::error::ErrorCode;
+use crate::state::{
+    DepositLimits, Market, MarketMetadata, MarketStatus, Outcome, UserPosition,
+    MARKET_RESERVED_LEN,
+};
+
+/// A zero-copy view of the [Market] account, for use with [AccountLoader].
+///
+/// The layout is byte-for-byte the Borsh layout of [Market], so the same
+/// account can be loaded as either type. Fields without a [Pod]
+/// representation are stored as raw bytes, with accessors to convert them.
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C, packed)]
+pub struct MarketZc {
+    pub version: u8,
+    pub creator: Pubkey,
+    pub resolver: Pubkey,
+    pub token_mint: Pubkey,
+    pub yes_token_account: Pubkey,
+    pub no_token_account: Pubkey,
+    pub yes_amount: u64,
+    pub yes_filled: u64,
+    pub no_amount: u64,
+    pub no_filled: u64,
+    pub close_ts: u64,
+    pub expiry_ts: u64,
+    pub outcome_ts: u64,
+    pub resolution_delay: u32,
+    outcome: u8,
+    finalized: u8,
+    pub yes_account_bump: u8,
+    pub no_account_bump: u8,
+    acknowledged: u8,
+    require_acknowledgement: u8,
+    yes_limits: [u8; DepositLimits::LEN],
+    no_limits: [u8; DepositLimits::LEN],
+    pub allowlist_root: [u8; 32],
+    metadata: [u8; MarketMetadata::LEN],
+    pub claimed_stakes: u64,
+    pub paid_winnings: u64,
+    pub accrued_fees: u64,
+    allow_early_resolution: u8,
+    pub max_resolution_window: u32,
+    pub creation_bond: u64,
+    creation_bond_in_mint: u8,
+    pub creation_bond_payer: Pubkey,
+    pub resolver_bond: u64,
+    pub resolver_bond_payer: Pubkey,
+    pub resolver_bond_paid: u64,
+    disputed: u8,
+    resolver_slashed: u8,
+    pub disputed_stake: u64,
+    pub slashed_stake_claimed: u64,
+    pub open_positions: u64,
+    registered: u8,
+    dispute_upheld: u8,
+    pub unmigrated_positions: u64,
+    reserved: [u8; MARKET_RESERVED_LEN],
+}
+
+// The zero-copy view must cover exactly the Borsh layout.
+const _: [(); Market::LEN] = [(); std::mem::size_of::<MarketZc>()];
+
+impl Discriminator for MarketZc {
+    fn discriminator() -> [u8; 8] {
+        Market::discriminator()
+    }
+}
+
+impl Owner for MarketZc {
+    fn owner() -> Pubkey {
+        crate::ID
+    }
+}
+
+impl ZeroCopy for MarketZc {}
+
+impl MarketZc {
+    /// Loads the market for writing, after checking that the account has the
+    /// current layout. Markets that were not migrated have a shorter layout
+    /// that can't be viewed.
+    pub fn load_mut_checked<'a>(
+        loader: &'a AccountLoader<'_, MarketZc>,
+    ) -> Result<RefMut<'a, MarketZc>> {
+        {
+            let info = loader.to_account_info();
+            let data = info.try_borrow_data()?;
+            if data.len() != 8 + Market::LEN || data[8] != Market::VERSION {
+                return Err(error!(ErrorCode::UnknownAccountLayout));
+            }
+        }
+
+        loader.load_mut()
+    }
+
+    /// The outcome of the market.
+    pub fn outcome(&self) -> Result<Outcome> {
+        match self.outcome {
+            0 => Ok(Outcome::Open),
+            1 => Ok(Outcome::Yes),
+            2 => Ok(Outcome::No),
+            3 => Ok(Outcome::Invalid),
+            _ => Err(error!(ErrorCode::UnknownAccountLayout)),
+        }
+    }
+
+    /// Whether the market is finalized.
+    pub fn finalized(&self) -> bool {
+        self.finalized != 0
+    }
+
+    /// Whether the resolver has acknowledged this market.
+    pub fn acknowledged(&self) -> bool {
+        self.acknowledged != 0
+    }
+
+    /// Whether the resolver may set the outcome to a side before the expiry
+    /// time.
+    pub fn allow_early_resolution(&self) -> bool {
+        self.allow_early_resolution != 0
+    }
+
+    /// Returns the fields that determine the outcome and payouts of the market.
+    pub fn status(&self) -> Result<MarketStatus> {
+        Ok(MarketStatus {
+            yes_amount: self.yes_amount,
+            yes_filled: self.yes_filled,
+            no_amount: self.no_amount,
+            no_filled: self.no_filled,
+            close_ts: self.close_ts,
+            expiry_ts: self.expiry_ts,
+            outcome_ts: self.outcome_ts,
+            resolution_delay: self.resolution_delay,
+            max_resolution_window: self.max_resolution_window,
+            disputed: self.disputed != 0,
+            arbitrated: self.resolver_slashed != 0 || self.dispute_upheld != 0,
+            outcome: self.outcome()?,
+            acknowledged: self.acknowledged(),
+            require_acknowledgement: self.require_acknowledgement != 0,
+            claimed_stakes: self.claimed_stakes,
+            paid_winnings: self.paid_winnings,
+        })
+    }
+
+    /// Same as [Market::finalize].
+    pub fn finalize(&mut self, now: u64) -> Result<bool> {
+        // Already finalized.
+        if self.finalized() {
+            return Ok(true);
+        }
+
+        match self.status()?.finalized_outcome(now)? {
+            Some(outcome) => {
+                self.finalized = 1;
+                self.outcome = outcome as u8;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Same as [Market::winnings].
+    pub fn winnings(&self, position: &UserPosition) -> Result<(u64, u64)> {
+        Ok(self.status()?.winnings(position))
+    }
+
+    /// Same as [Market::pay_out].
+    pub fn pay_out(&mut self, position: &UserPosition) -> Result<(u64, u64)> {
+        let (winning_num, winnings) = self.winnings(position)?;
+        let claimed_stakes = self.claimed_stakes;
+        let paid_winnings = self.paid_winnings;
+        self.claimed_stakes = claimed_stakes
+            .checked_add(winning_num)
+            .ok_or(ErrorCode::Overflow)?;
+        self.paid_winnings = paid_winnings
+            .checked_add(winnings)
+            .ok_or(ErrorCode::Overflow)?;
+
+        Ok((winning_num, winnings))
+    }
+
+    /// Records a fee taken from claimed winnings, left in the losing side
+    /// until it is collected.
+    pub fn accrue_fee(&mut self, fee: u64) -> Result<()> {
+        let accrued_fees = self.accrued_fees;
+        self.accrued_fees = accrued_fees.checked_add(fee).ok_or(ErrorCode::Overflow)?;
+
+        Ok(())
+    }
+
+    /// Returns the escrow holding the losing side, from which fees are paid,
+    /// or [None] if the market has not resolved to either side.
+    pub fn losing_token_account(&self) -> Result<Option<Pubkey>> {
+        let account = match self.outcome()? {
+            Outcome::Yes => Some(self.no_token_account),
+            Outcome::No => Some(self.yes_token_account),
+            _ => None,
+        };
+
+        Ok(account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that a Borsh-serialized market reads back the same through the
+    // zero-copy view.
+    #[test]
+    fn check_layout_matches() {
+        let market = Market {
+            creator: Pubkey::new_unique(),
+            yes_amount: 10,
+            no_filled: 7,
+            resolution_delay: 30,
+            outcome: Outcome::No,
+            finalized: true,
+            acknowledged: true,
+            allowlist_root: [5; 32],
+            ..Default::default()
+        };
+
+        let mut data = Vec::new();
+        market.try_serialize(&mut data).unwrap();
+
+        assert_eq!(data[..8], MarketZc::discriminator());
+
+        let zc: &MarketZc = bytemuck::from_bytes(&data[8..]);
+
+        assert_eq!(zc.version, Market::VERSION);
+        assert_eq!(zc.creator, market.creator);
+        assert_eq!({ zc.yes_amount }, 10);
+        assert_eq!({ zc.no_filled }, 7);
+        assert_eq!({ zc.resolution_delay }, 30);
+        assert_eq!(zc.outcome().unwrap(), Outcome::No);
+        assert!(zc.finalized());
+        assert!(zc.acknowledged());
+        assert_eq!(zc.allowlist_root, [5; 32]);
+    }
+}
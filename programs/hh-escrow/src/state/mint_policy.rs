@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+
+use crate::state::GlobalState;
+
+/// The size of the space reserved for future fields of [MintPolicy].
+pub const MINT_POLICY_RESERVED_LEN: usize = 56;
+
+/// Limits on new markets denominated in a given token mint, set by the owner
+/// of the global state.
+///
+/// Found at the PDA `[b"mint_policy", token_mint]`. A mint without a policy
+/// account has no limits.
+#[account]
+pub struct MintPolicy {
+    /// The version of the account layout.
+    pub version: u8,
+    /// The token mint the policy applies to.
+    pub token_mint: Pubkey,
+    /// The minimum amount of tokens to fill either side of a market.
+    pub min_fill: u64,
+    /// The creation bond of a new market, in tokens. 0 if new markets post
+    /// the creation bond of the global state in lamports instead.
+    pub creation_bond: u64,
+    /// Space reserved for future fields.
+    pub reserved: [u8; MINT_POLICY_RESERVED_LEN],
+}
+
+impl Default for MintPolicy {
+    fn default() -> Self {
+        MintPolicy {
+            version: MintPolicy::VERSION,
+            token_mint: Pubkey::default(),
+            min_fill: 0,
+            creation_bond: 0,
+            reserved: [0; MINT_POLICY_RESERVED_LEN],
+        }
+    }
+}
+
+impl MintPolicy {
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1 + 32 + 2 * 8 + MINT_POLICY_RESERVED_LEN;
+
+    /// Returns the policy at the given account, or the default policy without
+    /// limits if the account was never initialized.
+    ///
+    /// The address of the account must already be checked.
+    pub fn load(info: &AccountInfo) -> Result<MintPolicy> {
+        if info.owner != &crate::ID {
+            return Ok(MintPolicy::default());
+        }
+
+        Ok(Account::<MintPolicy>::try_from(info)?.into_inner())
+    }
+
+    /// Returns the creation bond of a new market in the token mint, and
+    /// whether it is denominated in the mint rather than in lamports.
+    pub fn creation_bond(&self, global_state: &GlobalState) -> (u64, bool) {
+        match self.creation_bond {
+            0 => (global_state.creation_bond_lamports, false),
+            bond => (bond, true),
+        }
+    }
+}
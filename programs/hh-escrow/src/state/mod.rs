@@ -1,13 +1,33 @@
 mod bps;
+mod deposit_limits;
 mod global_state;
+mod legacy;
 mod market;
+mod market_counter;
+mod market_metadata;
+mod market_policy;
+mod market_registry;
+mod market_status;
+mod market_template;
+mod market_zc;
+mod mint_policy;
 mod outcome;
 mod uri;
 mod user_position;
 
 pub use self::bps::*;
+pub use self::deposit_limits::*;
 pub use self::global_state::*;
+pub use self::legacy::*;
 pub use self::market::*;
+pub use self::market_counter::*;
+pub use self::market_metadata::*;
+pub use self::market_policy::*;
+pub use self::market_registry::*;
+pub use self::market_status::*;
+pub use self::market_template::*;
+pub use self::market_zc::*;
+pub use self::mint_policy::*;
 pub use self::outcome::*;
 pub use self::uri::*;
 pub use self::user_position::*;
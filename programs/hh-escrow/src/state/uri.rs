@@ -2,8 +2,11 @@ use anchor_lang::prelude::*;
 
 use crate::error::ErrorCode;
 
-// FIXME: This should be 200;
-const URI_MAX_LEN: usize = 256;
+/// The maximum length of a URI in bytes.
+pub const URI_MAX_LEN: usize = 200;
+
+/// URI schemes accepted for market resources.
+const URI_SCHEMES: [&str; 3] = ["https://", "ipfs://", "ar://"];
 
 /// A string URI.
 #[derive(Clone, AnchorDeserialize, AnchorSerialize)]
@@ -25,12 +28,22 @@ impl Default for UriResource {
 
 impl UriResource {
     /// Validates the resource.
+    ///
+    /// The URI must be non-empty, fit in [URI_MAX_LEN] bytes, use one of the
+    /// accepted schemes and not contain whitespace or control characters.
     pub fn validate(uri: &str) -> Result<UriResource> {
         let len = uri.len();
         if len > URI_MAX_LEN {
             return Err(error!(ErrorCode::InvalidMarketResource));
         }
 
+        let valid_scheme = URI_SCHEMES
+            .iter()
+            .any(|scheme| uri.len() > scheme.len() && uri.starts_with(scheme));
+        if !valid_scheme || uri.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(error!(ErrorCode::InvalidMarketResource));
+        }
+
         let mut bytes = [0; URI_MAX_LEN];
         bytes[..len].copy_from_slice(uri.as_bytes());
 
@@ -40,5 +53,51 @@ impl UriResource {
         })
     }
 
+    /// Returns the URI as a string, or [None] if the stored bytes are not
+    /// valid UTF-8.
+    pub fn as_str(&self) -> Option<&str> {
+        let bytes = self.uri.get(..self.len as usize)?;
+        std::str::from_utf8(bytes).ok()
+    }
+
     pub const LEN: usize = 2 + URI_MAX_LEN;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that URIs with an accepted scheme are stored as given.
+    #[test]
+    fn check_valid_uri() {
+        let uri = "https://example.com/market.json";
+        let resource = UriResource::validate(uri).unwrap();
+
+        assert_eq!(resource.len as usize, uri.len());
+        assert_eq!(resource.as_str(), Some(uri));
+        assert!(UriResource::validate("ipfs://bafybeigdyrzt").is_ok());
+        assert!(UriResource::validate("ar://abc").is_ok());
+    }
+
+    // Checks that empty, schemeless and malformed URIs are rejected.
+    #[test]
+    fn check_invalid_uri() {
+        assert!(UriResource::validate("").is_err());
+        assert!(UriResource::validate("https://").is_err());
+        assert!(UriResource::validate("example.com/market.json").is_err());
+        assert!(UriResource::validate("ftp://example.com").is_err());
+        assert!(UriResource::validate("https://example.com/a b").is_err());
+        assert!(UriResource::validate("https://example.com/\n").is_err());
+    }
+
+    // Checks that the URI must fit in the buffer.
+    #[test]
+    fn check_uri_max_len() {
+        let prefix = "https://";
+        let uri = format!("{}{}", prefix, "a".repeat(URI_MAX_LEN - prefix.len()));
+        assert!(UriResource::validate(&uri).is_ok());
+
+        let uri = format!("{}a", uri);
+        assert!(UriResource::validate(&uri).is_err());
+    }
+}
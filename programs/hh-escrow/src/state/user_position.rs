@@ -1,17 +1,105 @@
 use anchor_lang::prelude::*;
 
+use crate::error::ErrorCode;
+
+/// The size of the space reserved for future fields of [UserPosition].
+pub const USER_POSITION_RESERVED_LEN: usize = 64;
+
 /// Tracks the user's positions for a given market.
 #[account]
-#[derive(Default)]
 pub struct UserPosition {
+    /// The version of the account layout.
+    pub version: u8,
     /// The market for which we track positions.
     pub market: Pubkey,
     /// The amount the user has deposited into the yes side.
     pub yes_amount: u64,
     /// The amount the user has deposited into the no side.
     pub no_amount: u64,
+    /// The wallet that referred the user to the market, receiving a share of
+    /// the protocol fee on claims. The default public key if there is none.
+    pub referrer: Pubkey,
+    /// The stake of the position when it disputed the outcome of the market.
+    /// 0 if it did not dispute it, or was paid its share of the slashed
+    /// resolver bond.
+    pub dispute_stake: u64,
+    /// The lamports posted by the position to dispute the outcome, held by
+    /// this account until they are refunded or slashed.
+    pub dispute_bond: u64,
+    /// Space reserved for future fields.
+    pub reserved: [u8; USER_POSITION_RESERVED_LEN],
+}
+
+impl Default for UserPosition {
+    fn default() -> Self {
+        UserPosition {
+            version: UserPosition::VERSION,
+            market: Pubkey::default(),
+            yes_amount: 0,
+            no_amount: 0,
+            referrer: Pubkey::default(),
+            dispute_stake: 0,
+            dispute_bond: 0,
+            reserved: [0; USER_POSITION_RESERVED_LEN],
+        }
+    }
 }
 
 impl UserPosition {
-    pub const LEN: usize = 32 + 2 * 8;
+    /// The current version of the account layout.
+    pub const VERSION: u8 = 1;
+
+    pub const LEN: usize = 1 + 32 + 2 * 8 + 32 + 2 * 8 + USER_POSITION_RESERVED_LEN;
+
+    /// Returns the referrer of the position, if any.
+    pub fn referrer(&self) -> Option<Pubkey> {
+        if self.referrer == Pubkey::default() {
+            None
+        } else {
+            Some(self.referrer)
+        }
+    }
+
+    /// Records the referrer of the position. The referrer can only be set
+    /// before the first deposit, after which passing it again is a no-op.
+    pub fn set_referrer(&mut self, referrer: Pubkey) -> Result<()> {
+        if self.referrer == referrer {
+            return Ok(());
+        }
+        if self.referrer().is_some() || self.yes_amount != 0 || self.no_amount != 0 {
+            return Err(error!(ErrorCode::ReferrerImmutable));
+        }
+
+        self.referrer = referrer;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checks that the referrer is set on an empty position, and can't change
+    // once set or once the position has deposits.
+    #[test]
+    fn check_set_referrer() {
+        let referrer = Pubkey::new_unique();
+        let mut position = UserPosition::default();
+        assert_eq!(position.referrer(), None);
+
+        position.set_referrer(referrer).unwrap();
+        position.yes_amount = 1;
+
+        assert_eq!(position.referrer(), Some(referrer));
+        assert!(position.set_referrer(referrer).is_ok());
+        assert!(position.set_referrer(Pubkey::new_unique()).is_err());
+
+        let mut position = UserPosition {
+            no_amount: 1,
+            ..Default::default()
+        };
+
+        assert!(position.set_referrer(referrer).is_err());
+    }
 }
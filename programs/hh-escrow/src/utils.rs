@@ -1,5 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, Transfer};
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
+use anchor_spl::token::{self, InitializeAccount, Token, TokenAccount, Transfer};
+use spl_associated_token_account::get_associated_token_address;
+
+use common::traits::KeyRef;
+
+use crate::error::ErrorCode;
+use crate::state::{
+    GlobalState, Market, MarketRegistry, MarketRegistryPage, MintPolicy, UserPosition,
+};
 
 pub fn non_signer_transfer<'info>(
     token_program: &Program<'info, Token>,
@@ -20,12 +30,12 @@ pub fn non_signer_transfer<'info>(
     token::transfer(ctx, amount)
 }
 
-pub fn signer_transfer<'info, 'a, 'b, 'c>(
+pub fn signer_transfer<'info>(
     token_program: &Program<'info, Token>,
     from: &AccountInfo<'info>,
     to: &AccountInfo<'info>,
     authority: &AccountInfo<'info>,
-    signer_seeds: &'a [&'b [&'c [u8]]],
+    signer_seeds: &[&[&[u8]]],
     amount: u64,
 ) -> Result<()> {
     let ctx = CpiContext::new_with_signer(
@@ -39,3 +49,245 @@ pub fn signer_transfer<'info, 'a, 'b, 'c>(
     );
     token::transfer(ctx, amount)
 }
+
+/// Appends a newly created market to the tail page of a registry, which
+/// indexes markets by `key`.
+pub fn register_market<'info>(
+    registry: &mut Account<'info, MarketRegistry>,
+    page: &mut Account<'info, MarketRegistryPage>,
+    key: Pubkey,
+    market: Pubkey,
+) -> Result<()> {
+    let registry_key = registry.key();
+    registry.append(registry_key, key, page, market)
+}
+
+/// Removes a closed market from a page of a registry.
+pub fn unregister_market<'info>(
+    registry: &AccountInfo<'info>,
+    page: &AccountInfo<'info>,
+    market: &Pubkey,
+) -> Result<()> {
+    let mut registry = Account::<MarketRegistry>::try_from(registry)?;
+    let mut page = Account::<MarketRegistryPage>::try_from(page)?;
+    if page.registry != registry.key() {
+        return Err(error!(ErrorCode::IncorrectRegistryPage));
+    }
+
+    registry.remove(&mut page, market)?;
+    registry.exit(&crate::ID)?;
+    page.exit(&crate::ID)
+}
+
+/// Returns the share of `fee` owed to the referrer of a position, or 0 if it
+/// has no referrer.
+///
+/// `referrer_token_account` must be the referrer's associated token account
+/// for `token_mint`. A referrer without a token account must not block the
+/// payout, so their share is then kept as protocol fee instead.
+pub fn referral(
+    global_state: &GlobalState,
+    user_position: &UserPosition,
+    token_mint: &Pubkey,
+    referrer_token_account: &AccountInfo,
+    fee: u64,
+) -> Result<u64> {
+    let referrer = match user_position.referrer() {
+        Some(referrer) => referrer,
+        None => return Ok(0),
+    };
+
+    let key = get_associated_token_address(&referrer, token_mint);
+    if key != *referrer_token_account.key_ref() {
+        return Err(error!(ErrorCode::ReferrerAccountNotAssociated));
+    }
+
+    if *referrer_token_account.owner != token::ID || referrer_token_account.data_is_empty() {
+        return Ok(0);
+    }
+
+    Ok(global_state.referral_bps.fee(fee))
+}
+
+/// Creates a token account for `token_mint` owned by the market `authority`,
+/// at the PDA of `signer_seeds`, paid by `payer`.
+///
+/// Escrows that only some markets need are created this way instead of with
+/// `init`. As anyone can send lamports to the address beforehand, a funded
+/// account is topped up, allocated and assigned instead of created.
+#[allow(clippy::too_many_arguments)]
+pub fn create_escrow_token_account<'info>(
+    payer: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    token_mint: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    system_program: &Program<'info, System>,
+    rent: &Sysvar<'info, Rent>,
+) -> Result<()> {
+    let space = TokenAccount::LEN;
+    let lamports = rent.minimum_balance(space);
+    let signer = &[signer_seeds];
+
+    if escrow.lamports() == 0 {
+        let ctx = CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: payer.clone(),
+                to: escrow.clone(),
+            },
+            signer,
+        );
+        system_program::create_account(ctx, lamports, space as u64, &token::ID)?;
+    } else {
+        let top_up = lamports.saturating_sub(escrow.lamports());
+        if top_up > 0 {
+            let ctx = CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: escrow.clone(),
+                },
+            );
+            system_program::transfer(ctx, top_up)?;
+        }
+
+        let ctx = CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Allocate {
+                account_to_allocate: escrow.clone(),
+            },
+            signer,
+        );
+        system_program::allocate(ctx, space as u64)?;
+
+        let ctx = CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Assign {
+                account_to_assign: escrow.clone(),
+            },
+            signer,
+        );
+        system_program::assign(ctx, &token::ID)?;
+    }
+
+    let ctx = CpiContext::new(
+        token_program.to_account_info(),
+        InitializeAccount {
+            account: escrow.clone(),
+            mint: token_mint.clone(),
+            authority: authority.clone(),
+            rent: rent.to_account_info(),
+        },
+    );
+    token::initialize_account(ctx)
+}
+
+/// Escrows the creation bond of a newly created market, paid by `payer`.
+///
+/// The bond is taken in tokens from `payer_token_account` into the bond
+/// escrow if the mint policy sets one, and otherwise in lamports into the
+/// market account itself. The bond escrow is only created for a bond in
+/// tokens, at the PDA `[b"bond", market]` with the given bump.
+#[allow(clippy::too_many_arguments)]
+pub fn post_creation_bond<'info>(
+    market: &mut Account<'info, Market>,
+    global_state: &GlobalState,
+    mint_policy: &MintPolicy,
+    payer: &Signer<'info>,
+    payer_token_account: &AccountInfo<'info>,
+    (bond_token_account, bond_bump): (&AccountInfo<'info>, u8),
+    token_mint: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    system_program: &Program<'info, System>,
+    rent: &Sysvar<'info, Rent>,
+) -> Result<()> {
+    let (bond, in_mint) = mint_policy.creation_bond(global_state);
+    if bond > 0 && in_mint {
+        let market_key = market.key();
+        create_escrow_token_account(
+            payer,
+            bond_token_account,
+            &[b"bond", market_key.as_ref(), &[bond_bump]],
+            token_mint,
+            authority,
+            token_program,
+            system_program,
+            rent,
+        )?;
+        non_signer_transfer(
+            token_program,
+            payer_token_account,
+            bond_token_account,
+            payer,
+            bond,
+        )?;
+    } else if bond > 0 {
+        let ctx = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: market.to_account_info(),
+            },
+        );
+        system_program::transfer(ctx, bond)?;
+    }
+
+    market.creation_bond = bond;
+    market.creation_bond_in_mint = in_mint;
+    market.creation_bond_payer = payer.key();
+
+    Ok(())
+}
+
+/// Migrates an unversioned account in place to its versioned layout.
+///
+/// The unversioned data after the discriminator is converted by `migrate`,
+/// which fails with [ErrorCode::UnknownAccountLayout] if it does not match a
+/// known layout. The account is then reallocated to `8 + len` bytes, with the
+/// payer topping up the rent-exempt balance, and the versioned account is
+/// written over it.
+pub fn migrate_account<'info, T: AccountSerialize + Discriminator + Owner>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    len: usize,
+    migrate: impl FnOnce(&[u8]) -> Result<T>,
+) -> Result<()> {
+    if account.owner != &T::owner() {
+        return Err(error!(ErrorCode::UnknownAccountLayout));
+    }
+
+    let migrated = {
+        let data = account.try_borrow_data()?;
+        if data.len() < 8 || data[..8] != T::discriminator() {
+            return Err(error!(ErrorCode::UnknownAccountLayout));
+        }
+        if data.len() == 8 + len {
+            return Err(error!(ErrorCode::AlreadyMigrated));
+        }
+
+        migrate(&data[8..])?
+    };
+
+    let rent = Rent::get()?.minimum_balance(8 + len);
+    let top_up = rent.saturating_sub(account.lamports());
+    if top_up > 0 {
+        let ctx = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: account.clone(),
+            },
+        );
+        system_program::transfer(ctx, top_up)?;
+    }
+
+    account.realloc(8 + len, true)?;
+    let mut data = account.try_borrow_mut_data()?;
+    migrated.try_serialize(&mut &mut data[..])?;
+
+    Ok(())
+}
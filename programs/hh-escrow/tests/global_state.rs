@@ -0,0 +1,247 @@
+mod utils;
+
+use solana_program_test::tokio;
+use solana_sdk::bpf_loader_upgradeable;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use hh_client::{escrow, pda};
+use hh_escrow::error::ErrorCode;
+use hh_escrow::instructions::{
+    InitializeGlobalStateParams, SetGlobalStateParams, SetMarketPolicyParams, SetMintPolicyParams,
+};
+use hh_escrow::state::{GlobalState, MarketPolicy, MintPolicy};
+
+use crate::utils::{assert_error, program_test, raw_account, TestEnv, CRANK_TIP_BPS, FEE_CUT_BPS};
+
+/// Encodes `UpgradeableLoaderState::ProgramData` the way bincode does.
+fn program_data(upgrade_authority: &Pubkey) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&3u32.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    data.push(1);
+    data.extend_from_slice(upgrade_authority.as_ref());
+
+    data
+}
+
+// Test that the global state can't be initialized when the program isn't
+// deployed through the upgradeable loader.
+//
+// Programs added with `processor!` are builtins, so the success path needs a
+// real deployment and is covered by the TypeScript tests instead.
+#[tokio::test]
+async fn test_initialize_global_state() {
+    let authority = Keypair::new();
+    let mut test = program_test();
+    test.add_account(
+        pda::program_data(),
+        raw_account(
+            program_data(&authority.pubkey()),
+            bpf_loader_upgradeable::ID,
+        ),
+    );
+    let mut env = TestEnv::start_uninitialized(test).await;
+
+    let ix = escrow::initialize_global_state(
+        &authority.pubkey(),
+        &env.creator(),
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        InitializeGlobalStateParams {
+            protocol_fee_bps: FEE_CUT_BPS,
+            crank_tip_bps: CRANK_TIP_BPS,
+        },
+    );
+    assert_error(
+        env.process(&[ix], &[&authority]).await,
+        ErrorCode::InvalidProgramData,
+    );
+    assert!(env.account(&pda::global_state()).await.is_none());
+}
+
+// Test that only the owner updates the global state, within fee bounds, and
+// that settings which are not passed are left unchanged.
+#[tokio::test]
+async fn test_set_global_state() {
+    let mut env = TestEnv::new().await;
+    let new_owner = Keypair::new();
+    let new_fee_wallet = Pubkey::new_unique();
+    let new_arbiter = Pubkey::new_unique();
+    let params = |new_fee_cut_bps, new_min_resolution_window| SetGlobalStateParams {
+        new_owner: new_owner.pubkey(),
+        new_fee_cut_bps,
+        new_fee_wallet,
+        new_crank_tip_bps: Some(0),
+        new_referral_bps: Some(2_500),
+        new_min_resolution_window: Some(new_min_resolution_window),
+        new_max_resolution_window: Some(86_400),
+        new_creation_bond_lamports: Some(5_000),
+        new_arbiter: Some(new_arbiter),
+        new_dispute_bond_lamports: Some(10_000),
+        new_template_resolver_program: None,
+    };
+
+    let ix = escrow::set_global_state(&new_owner.pubkey(), params(200, 3_600));
+    assert_error(
+        env.process(&[ix], &[&new_owner]).await,
+        ErrorCode::IncorrectGlobalStateOwner,
+    );
+
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let ix = escrow::set_global_state(&owner.pubkey(), params(10_001, 3_600));
+    assert_error(env.process(&[ix], &[&owner]).await, ErrorCode::FeeTooHigh);
+
+    let ix = escrow::set_global_state(&owner.pubkey(), params(200, 86_401));
+    assert_error(
+        env.process(&[ix], &[&owner]).await,
+        ErrorCode::InvalidResolutionWindowBounds,
+    );
+
+    let ix = escrow::set_global_state(&owner.pubkey(), params(200, 3_600));
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let global_state: GlobalState = env.decode(&pda::global_state()).await;
+    assert_eq!(global_state.owner, new_owner.pubkey());
+    assert_eq!(global_state.fee_wallet, new_fee_wallet);
+    assert_eq!(global_state.fee_cut_bps.get(), 200);
+    assert_eq!(global_state.crank_tip_bps.get(), 0);
+    assert_eq!(global_state.referral_bps.get(), 2_500);
+    assert_eq!(global_state.resolution_window_bounds(), (3_600, 86_400));
+    assert_eq!(global_state.creation_bond_lamports, 5_000);
+    assert_eq!(global_state.arbiter, new_arbiter);
+    assert_eq!(global_state.dispute_bond_lamports, 10_000);
+
+    let ix = escrow::set_global_state(
+        &new_owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: new_owner.pubkey(),
+            new_fee_cut_bps: 300,
+            new_fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: None,
+            new_min_resolution_window: None,
+            new_max_resolution_window: Some(3_599),
+            new_creation_bond_lamports: None,
+            new_arbiter: None,
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: None,
+        },
+    );
+    assert_error(
+        env.process(&[ix], &[&new_owner]).await,
+        ErrorCode::InvalidResolutionWindowBounds,
+    );
+
+    let ix = escrow::set_global_state(
+        &new_owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: new_owner.pubkey(),
+            new_fee_cut_bps: 300,
+            new_fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: None,
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: None,
+            new_arbiter: None,
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: None,
+        },
+    );
+    env.process(&[ix], &[&new_owner]).await.unwrap();
+
+    let global_state: GlobalState = env.decode(&pda::global_state()).await;
+    assert_eq!(global_state.fee_cut_bps.get(), 300);
+    assert_eq!(global_state.referral_bps.get(), 2_500);
+    assert_eq!(global_state.resolution_window_bounds(), (3_600, 86_400));
+    assert_eq!(global_state.creation_bond_lamports, 5_000);
+    assert_eq!(global_state.arbiter, new_arbiter);
+    assert_eq!(global_state.dispute_bond_lamports, 10_000);
+}
+
+// Test that only the owner sets the market and mint policies, and that a
+// market policy with a minimum above its maximum is refused.
+#[tokio::test]
+async fn test_set_policies() {
+    let mut env = TestEnv::new().await;
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let wrong_owner = Keypair::new();
+    let mint = env.mint.pubkey();
+    let policy = MarketPolicy {
+        min_close_delay: 60,
+        max_close_delay: 86_400,
+        max_expiry_gap: 3_600,
+        min_resolution_delay: 600,
+        max_resolution_delay: 7_200,
+        max_uri_len: 64,
+    };
+
+    let ix = escrow::set_market_policy(
+        &wrong_owner.pubkey(),
+        SetMarketPolicyParams {
+            new_market_policy: policy,
+        },
+    );
+    assert_error(
+        env.process(&[ix], &[&wrong_owner]).await,
+        ErrorCode::IncorrectGlobalStateOwner,
+    );
+
+    let ix = escrow::set_market_policy(
+        &owner.pubkey(),
+        SetMarketPolicyParams {
+            new_market_policy: MarketPolicy {
+                min_close_delay: 86_401,
+                ..policy
+            },
+        },
+    );
+    assert_error(
+        env.process(&[ix], &[&owner]).await,
+        ErrorCode::InvalidMarketPolicy,
+    );
+
+    let ix = escrow::set_market_policy(
+        &owner.pubkey(),
+        SetMarketPolicyParams {
+            new_market_policy: policy,
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let global_state: GlobalState = env.decode(&pda::global_state()).await;
+    assert_eq!(global_state.market_policy, policy);
+
+    let ix = escrow::set_mint_policy(
+        &wrong_owner.pubkey(),
+        &env.creator(),
+        &mint,
+        SetMintPolicyParams {
+            new_min_fill: 100,
+            new_creation_bond: 0,
+        },
+    );
+    assert_error(
+        env.process(&[ix], &[&wrong_owner]).await,
+        ErrorCode::IncorrectGlobalStateOwner,
+    );
+
+    for min_fill in [100, 200] {
+        let ix = escrow::set_mint_policy(
+            &owner.pubkey(),
+            &env.creator(),
+            &mint,
+            SetMintPolicyParams {
+                new_min_fill: min_fill,
+                new_creation_bond: min_fill * 10,
+            },
+        );
+        env.process(&[ix], &[&owner]).await.unwrap();
+
+        let mint_policy: MintPolicy = env.decode(&pda::mint_policy(&mint)).await;
+        assert_eq!(mint_policy.token_mint, mint);
+        assert_eq!(mint_policy.min_fill, min_fill);
+        assert_eq!(mint_policy.creation_bond, min_fill * 10);
+    }
+}
@@ -0,0 +1,1583 @@
+mod utils;
+
+use solana_program_test::tokio;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transport::TransportError;
+use spl_associated_token_account::get_associated_token_address;
+
+use common::merkle;
+use hh_client::escrow::{self, RegistryPages};
+use hh_client::pda;
+use hh_escrow::error::ErrorCode;
+use hh_escrow::instructions::{
+    ArbitrateParams, DepositParams, InitializeMarketParams, SetAllowlistRootParams,
+    SetGlobalStateParams, SetMarketPolicyParams, SetMintPolicyParams, SetResolverParams,
+};
+use hh_escrow::state::{MarketCounter, MarketPolicy, MarketRegistry, Outcome};
+
+use crate::utils::{
+    add_fixture, assert_error, program_test, TestEnv, FEE_CUT_BPS, MAX_RESOLUTION_WINDOW,
+    MIN_RESOLUTION_WINDOW, NO_AMOUNT, RESOLUTION_DELAY, RESOLUTION_WINDOW, START_TS, YES_AMOUNT,
+};
+
+const CLOSE_TS: u64 = START_TS + 3_600;
+const EXPIRY_TS: u64 = START_TS + 7_200;
+
+async fn claim(
+    env: &mut TestEnv,
+    user: &Keypair,
+    destination: &Pubkey,
+    market: &Pubkey,
+) -> Result<(), TransportError> {
+    let ix = escrow::claim(
+        &user.pubkey(),
+        market,
+        &env.mint.pubkey(),
+        destination,
+        None,
+    );
+    env.process(&[ix], &[user]).await
+}
+
+async fn collect_fees(
+    env: &mut TestEnv,
+    collector: &Keypair,
+    markets: &[(Pubkey, Outcome)],
+) -> Result<(), TransportError> {
+    let ix = escrow::collect_fees(
+        &collector.pubkey(),
+        &env.fee_wallet,
+        &env.mint.pubkey(),
+        markets,
+    );
+    env.process(&[ix], &[collector]).await
+}
+
+async fn withdraw(
+    env: &mut TestEnv,
+    user: &Keypair,
+    destination: &Pubkey,
+    market: &Pubkey,
+) -> Result<(), TransportError> {
+    let ix = escrow::withdraw(&user.pubkey(), market, &env.mint.pubkey(), destination);
+    env.process(&[ix], &[user]).await
+}
+
+async fn settle_position(
+    env: &mut TestEnv,
+    cranker: &Keypair,
+    cranker_token_account: &Pubkey,
+    user: &Pubkey,
+    market: &Pubkey,
+) -> Result<(), TransportError> {
+    let ix = escrow::settle_position(
+        &cranker.pubkey(),
+        cranker_token_account,
+        user,
+        market,
+        &env.mint.pubkey(),
+        None,
+    );
+    env.process(&[ix], &[cranker]).await
+}
+
+/// Initializes a market filled by one user on each side.
+async fn filled_market(
+    env: &mut TestEnv,
+    resolver: &Pubkey,
+) -> (Pubkey, (Keypair, Pubkey), (Keypair, Pubkey)) {
+    let market = env.init_market(env.market_params(resolver)).await;
+
+    let (yes_user, yes_account) = env.user(YES_AMOUNT).await;
+    let (no_user, no_account) = env.user(NO_AMOUNT).await;
+    env.deposit(&yes_user, &yes_account, &market, (YES_AMOUNT, 0))
+        .await;
+    env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT))
+        .await;
+
+    (market, (yes_user, yes_account), (no_user, no_account))
+}
+
+// Test that a market is initialized and registered with its creator and
+// token mint, and that a past close timestamp is refused.
+#[tokio::test]
+async fn test_initialize_market() {
+    let mut env = TestEnv::new().await;
+    let resolver = Pubkey::new_unique();
+
+    let mut params = env.market_params(&resolver);
+    params.close_ts = START_TS - 1;
+    assert_error(
+        env.try_init_market(&Keypair::new(), params).await,
+        ErrorCode::InvalidCloseTimestamp,
+    );
+
+    let market = env.init_market(env.market_params(&resolver)).await;
+    let info = env.market(&market).await;
+
+    assert_eq!(info.creator, env.creator());
+    assert_eq!(info.resolver, resolver);
+    assert_eq!(info.token_mint, env.mint.pubkey());
+    assert_eq!(info.yes_token_account, pda::yes_token_account(&market));
+    assert_eq!(info.close_ts, CLOSE_TS);
+    assert_eq!(info.outcome, Outcome::Open);
+
+    // Without a creation bond, there is no bond escrow.
+    assert!(env
+        .account(&pda::bond_token_account(&market))
+        .await
+        .is_none());
+
+    let registry: MarketRegistry = env.decode(&pda::mint_registry(&env.mint.pubkey())).await;
+    assert_eq!(registry.market_count, 1);
+}
+
+// Test that the resolution window must be within the global bounds and cover
+// the resolution delay, and that an unresolved market is invalid once it
+// passes.
+#[tokio::test]
+async fn test_resolution_window() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+
+    for window in [0, MIN_RESOLUTION_WINDOW - 1, MAX_RESOLUTION_WINDOW + 1] {
+        let mut params = env.market_params(&resolver.pubkey());
+        params.max_resolution_window = window;
+        assert_error(
+            env.try_init_market(&Keypair::new(), params).await,
+            ErrorCode::InvalidResolutionWindow,
+        );
+    }
+
+    let mut params = env.market_params(&resolver.pubkey());
+    params.resolution_delay = RESOLUTION_WINDOW + 1;
+    assert_error(
+        env.try_init_market(&Keypair::new(), params).await,
+        ErrorCode::ResolutionDelayTooLong,
+    );
+
+    let (market, (yes_user, _), _) = filled_market(&mut env, &resolver.pubkey()).await;
+    assert_eq!(
+        env.market(&market).await.max_resolution_window,
+        RESOLUTION_WINDOW
+    );
+
+    env.set_time(EXPIRY_TS + RESOLUTION_WINDOW as u64 - 1).await;
+    assert_error(
+        claim(&mut env, &yes_user, &yes_user.pubkey(), &market).await,
+        ErrorCode::NotFinalized,
+    );
+
+    env.set_time(EXPIRY_TS + RESOLUTION_WINDOW as u64).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    let info = env.market(&market).await;
+    assert!(info.finalized);
+    assert_eq!(info.outcome, Outcome::Invalid);
+}
+
+/// A change to otherwise valid market parameters, and the error it causes.
+type PolicyCase = (fn(&mut InitializeMarketParams), ErrorCode);
+
+// Test that each limit of the market policy and the mint policy is enforced
+// on new markets.
+#[tokio::test]
+async fn test_market_policy() {
+    let mut env = TestEnv::new().await;
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let resolver = Pubkey::new_unique();
+
+    let ix = escrow::set_market_policy(
+        &owner.pubkey(),
+        SetMarketPolicyParams {
+            new_market_policy: MarketPolicy {
+                min_close_delay: 1_800,
+                max_close_delay: 86_400,
+                max_expiry_gap: 3_600,
+                min_resolution_delay: 600,
+                max_resolution_delay: 7_200,
+                max_uri_len: 64,
+            },
+        },
+    );
+    let mint_ix = escrow::set_mint_policy(
+        &owner.pubkey(),
+        &env.creator(),
+        &env.mint.pubkey(),
+        SetMintPolicyParams {
+            new_min_fill: YES_AMOUNT,
+            new_creation_bond: 0,
+        },
+    );
+    env.process(&[ix, mint_ix], &[&owner]).await.unwrap();
+
+    let cases: [PolicyCase; 7] = [
+        (|p| p.close_ts = START_TS + 1_799, ErrorCode::CloseTooSoon),
+        (
+            |p| {
+                p.close_ts = START_TS + 86_401;
+                p.expiry_ts = p.close_ts;
+            },
+            ErrorCode::CloseTooLate,
+        ),
+        (|p| p.expiry_ts = CLOSE_TS + 3_601, ErrorCode::ExpiryTooLate),
+        (
+            |p| p.resolution_delay = 599,
+            ErrorCode::ResolutionDelayTooShort,
+        ),
+        (
+            |p| p.resolution_delay = 7_201,
+            ErrorCode::ResolutionDelayAbovePolicy,
+        ),
+        (|p| p.yes_amount = YES_AMOUNT - 1, ErrorCode::FillTooSmall),
+        (
+            |p| p.uri = format!("https://{}", "a".repeat(57)),
+            ErrorCode::UriTooLong,
+        ),
+    ];
+    for (modify, error) in cases {
+        let mut params = env.market_params(&resolver);
+        modify(&mut params);
+        assert_error(env.try_init_market(&Keypair::new(), params).await, error);
+    }
+
+    let params = env.market_params(&resolver);
+    env.init_market(params).await;
+}
+
+// Test that PDA markets are created at consecutive nonces.
+#[tokio::test]
+async fn test_initialize_market_pda() {
+    let mut env = TestEnv::new().await;
+    let resolver = Pubkey::new_unique();
+    let creator = env.creator();
+    let mint = env.mint.pubkey();
+
+    for nonce in 0..2 {
+        let ix = escrow::initialize_market_pda(
+            &creator,
+            nonce,
+            &mint,
+            RegistryPages::default(),
+            env.market_params(&resolver),
+        );
+        env.process(&[ix], &[]).await.unwrap();
+    }
+
+    let counter: MarketCounter = env.decode(&pda::market_counter(&creator)).await;
+    assert_eq!(counter.next_nonce, 2);
+    assert_eq!(env.market(&pda::market(&creator, 1)).await.creator, creator);
+
+    let ix = escrow::initialize_market_pda(
+        &creator,
+        5,
+        &mint,
+        RegistryPages::default(),
+        env.market_params(&resolver),
+    );
+    // Skipping a nonce fails, as the program can't sign for the account.
+    assert!(env.process(&[ix], &[]).await.is_err());
+    assert!(env.account(&pda::market(&creator, 5)).await.is_none());
+}
+
+// Test a market resolving to yes: deposits stop at the close, the outcome
+// can only be set after the expiry, and claims wait for the resolution
+// delay.
+#[tokio::test]
+async fn test_claim() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let (market, (yes_user, yes_account), (no_user, no_account)) =
+        filled_market(&mut env, &resolver.pubkey()).await;
+
+    env.set_time(CLOSE_TS).await;
+    assert_error(
+        env.try_deposit(&yes_user, &yes_account, &market, (0, 1), vec![])
+            .await,
+        ErrorCode::MarketClosed,
+    );
+    assert_error(
+        env.update_state(&market, &resolver, Outcome::Yes).await,
+        ErrorCode::InvalidTransition,
+    );
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    assert_error(
+        claim(&mut env, &yes_user, &yes_user.pubkey(), &market).await,
+        ErrorCode::NotFinalized,
+    );
+
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+    claim(&mut env, &yes_user, &yes_user.pubkey(), &market)
+        .await
+        .unwrap();
+    claim(&mut env, &no_user, &no_user.pubkey(), &market)
+        .await
+        .unwrap();
+
+    // The winnings are the whole no side, minus a 1% fee left in the escrow
+    // until it is collected.
+    assert_eq!(env.balance(&yes_account).await, YES_AMOUNT + 1_980);
+    assert_eq!(env.balance(&no_account).await, 0);
+    assert_eq!(env.balance(&pda::yes_token_account(&market)).await, 0);
+    assert_eq!(env.balance(&pda::no_token_account(&market)).await, 20);
+
+    let info = env.market(&market).await;
+    assert!(info.finalized);
+    assert_eq!(info.outcome, Outcome::Yes);
+    assert_eq!(info.accrued_fees, 20);
+    let position = env.user_position(&yes_user.pubkey(), &market).await;
+    assert_eq!(position.yes_amount, 0);
+}
+
+// Test that winnings can be claimed into another wallet, whose associated
+// token account is created by the claim.
+#[tokio::test]
+async fn test_claim_to_destination() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let (market, (yes_user, yes_account), _) = filled_market(&mut env, &resolver.pubkey()).await;
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+
+    let cold_wallet = Pubkey::new_unique();
+    let cold_account = get_associated_token_address(&cold_wallet, &env.mint.pubkey());
+    assert!(env.account(&cold_account).await.is_none());
+
+    // The user pays the rent of the new token account.
+    let ix = system_instruction::transfer(&env.creator(), &yes_user.pubkey(), 10_000_000);
+    env.process(&[ix], &[]).await.unwrap();
+    claim(&mut env, &yes_user, &cold_wallet, &market)
+        .await
+        .unwrap();
+
+    assert_eq!(env.balance(&cold_account).await, YES_AMOUNT + 1_980);
+    assert_eq!(env.balance(&yes_account).await, 0);
+}
+
+// Test that the referrer of a position is fixed by the first deposit, and
+// receives its share of the fee when the position is claimed.
+#[tokio::test]
+async fn test_referral() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let market = env.init_market(env.market_params(&resolver.pubkey())).await;
+    let referrer = Pubkey::new_unique();
+    let referrer_account = env.create_token_account(&referrer).await;
+
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let ix = escrow::set_global_state(
+        &owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: owner.pubkey(),
+            new_fee_cut_bps: FEE_CUT_BPS,
+            new_fee_wallet: env.fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: Some(5_000),
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: None,
+            new_arbiter: None,
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: None,
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let deposit = |user: &Keypair, token_account, yes_amount, no_amount, referrer| {
+        escrow::deposit(
+            &user.pubkey(),
+            &market,
+            token_account,
+            DepositParams {
+                yes_amount,
+                no_amount,
+                allow_partial: false,
+                proof: vec![],
+                referrer,
+            },
+        )
+    };
+
+    let (yes_user, yes_account) = env.user(YES_AMOUNT).await;
+    env.init_user_position(&yes_user, &market).await;
+    let ix = deposit(&yes_user, &yes_account, 1, 0, Some(yes_user.pubkey()));
+    assert_error(
+        env.process(&[ix], &[&yes_user]).await,
+        ErrorCode::InvalidReferrer,
+    );
+    let ix = deposit(&yes_user, &yes_account, 1, 0, Some(referrer));
+    env.process(&[ix], &[&yes_user]).await.unwrap();
+    let ix = deposit(&yes_user, &yes_account, 1, 0, Some(Pubkey::new_unique()));
+    assert_error(
+        env.process(&[ix], &[&yes_user]).await,
+        ErrorCode::ReferrerImmutable,
+    );
+    let ix = deposit(&yes_user, &yes_account, YES_AMOUNT - 1, 0, None);
+    env.process(&[ix], &[&yes_user]).await.unwrap();
+    assert_eq!(
+        env.user_position(&yes_user.pubkey(), &market)
+            .await
+            .referrer(),
+        Some(referrer)
+    );
+
+    let (no_user, no_account) = env.user(NO_AMOUNT).await;
+    env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT))
+        .await;
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+
+    let mint = env.mint.pubkey();
+    let ix = escrow::claim(&yes_user.pubkey(), &market, &mint, &yes_user.pubkey(), None);
+    assert_error(
+        env.process(&[ix], &[&yes_user]).await,
+        ErrorCode::ReferrerAccountNotAssociated,
+    );
+    let ix = escrow::claim(
+        &yes_user.pubkey(),
+        &market,
+        &mint,
+        &yes_user.pubkey(),
+        Some(&referrer),
+    );
+    env.process(&[ix], &[&yes_user]).await.unwrap();
+
+    // Half of the fee of 20 goes to the referrer, the rest is accrued.
+    assert_eq!(env.balance(&yes_account).await, YES_AMOUNT + 1_980);
+    assert_eq!(env.balance(&referrer_account).await, 10);
+    assert_eq!(env.market(&market).await.accrued_fees, 10);
+}
+
+// Test that settling a referred position pays the referrer their share of the
+// fee after the tip, and accrues the rest.
+#[tokio::test]
+async fn test_settle_position_referral() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let market = env.init_market(env.market_params(&resolver.pubkey())).await;
+    let referrer = Pubkey::new_unique();
+    let referrer_account = env.create_token_account(&referrer).await;
+
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let ix = escrow::set_global_state(
+        &owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: owner.pubkey(),
+            new_fee_cut_bps: FEE_CUT_BPS,
+            new_fee_wallet: env.fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: Some(5_000),
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: None,
+            new_arbiter: None,
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: None,
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let (yes_user, yes_account) = env.user(YES_AMOUNT).await;
+    env.init_user_position(&yes_user, &market).await;
+    let ix = escrow::deposit(
+        &yes_user.pubkey(),
+        &market,
+        &yes_account,
+        DepositParams {
+            yes_amount: YES_AMOUNT,
+            no_amount: 0,
+            allow_partial: false,
+            proof: vec![],
+            referrer: Some(referrer),
+        },
+    );
+    env.process(&[ix], &[&yes_user]).await.unwrap();
+    let (no_user, no_account) = env.user(NO_AMOUNT).await;
+    env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT))
+        .await;
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+
+    let cranker = Keypair::new();
+    let cranker_account = env.create_token_account(&cranker.pubkey()).await;
+    let mint = env.mint.pubkey();
+    let settle = |referrer| {
+        escrow::settle_position(
+            &cranker.pubkey(),
+            &cranker_account,
+            &yes_user.pubkey(),
+            &market,
+            &mint,
+            referrer,
+        )
+    };
+    assert_error(
+        env.process(&[settle(None)], &[&cranker]).await,
+        ErrorCode::ReferrerAccountNotAssociated,
+    );
+    env.process(&[settle(Some(&referrer))], &[&cranker])
+        .await
+        .unwrap();
+
+    // The fee of 20 is split into a tip of 2, a referral of 10 and 8 accrued.
+    assert_eq!(env.balance(&yes_account).await, YES_AMOUNT + 1_980);
+    assert_eq!(env.balance(&cranker_account).await, 2);
+    assert_eq!(env.balance(&referrer_account).await, 10);
+    assert_eq!(env.market(&market).await.accrued_fees, 8);
+}
+
+// Test that fees accrued by claims on markets resolving to either side are
+// collected in one instruction, only by the fee wallet or the owner, and that
+// markets that were not migrated are refused.
+#[tokio::test]
+async fn test_collect_fees() {
+    let mut test = program_test();
+    let market_v0 = add_fixture(&mut test, "market_v0");
+    let mut env = TestEnv::start(test).await;
+    let resolver = Keypair::new();
+    let (yes_market, (yes_user, _), _) = filled_market(&mut env, &resolver.pubkey()).await;
+    let (no_market, _, (no_user, _)) = filled_market(&mut env, &resolver.pubkey()).await;
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&yes_market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.update_state(&no_market, &resolver, Outcome::No)
+        .await
+        .unwrap();
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+    claim(&mut env, &yes_user, &yes_user.pubkey(), &yes_market)
+        .await
+        .unwrap();
+    claim(&mut env, &no_user, &no_user.pubkey(), &no_market)
+        .await
+        .unwrap();
+    assert_eq!(env.market(&yes_market).await.accrued_fees, 20);
+    assert_eq!(env.market(&no_market).await.accrued_fees, 10);
+
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let other = Keypair::new();
+    assert_error(
+        collect_fees(&mut env, &other, &[(yes_market, Outcome::Yes)]).await,
+        ErrorCode::IncorrectFeeCollector,
+    );
+    assert_error(
+        collect_fees(&mut env, &owner, &[(yes_market, Outcome::No)]).await,
+        ErrorCode::IncorrectFeeEscrow,
+    );
+    assert_error(
+        collect_fees(&mut env, &owner, &[(market_v0, Outcome::Yes)]).await,
+        ErrorCode::UnknownAccountLayout,
+    );
+
+    collect_fees(
+        &mut env,
+        &owner,
+        &[(yes_market, Outcome::Yes), (no_market, Outcome::No)],
+    )
+    .await
+    .unwrap();
+
+    let fee_account = get_associated_token_address(&env.fee_wallet, &env.mint.pubkey());
+    assert_eq!(env.balance(&fee_account).await, 30);
+    assert_eq!(env.balance(&pda::no_token_account(&yes_market)).await, 0);
+    assert_eq!(env.balance(&pda::yes_token_account(&no_market)).await, 0);
+    assert_eq!(env.market(&yes_market).await.accrued_fees, 0);
+    assert_eq!(env.market(&no_market).await.accrued_fees, 0);
+}
+
+// Test that a cranker settles the positions of a market resolving to no, and
+// receives a share of the fee.
+#[tokio::test]
+async fn test_settle_position() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let (market, (yes_user, yes_account), (no_user, no_account)) =
+        filled_market(&mut env, &resolver.pubkey()).await;
+
+    let cranker = Keypair::new();
+    let cranker_account = env.create_token_account(&cranker.pubkey()).await;
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&market, &resolver, Outcome::No)
+        .await
+        .unwrap();
+    assert_error(
+        settle_position(
+            &mut env,
+            &cranker,
+            &cranker_account,
+            &no_user.pubkey(),
+            &market,
+        )
+        .await,
+        ErrorCode::NotFinalized,
+    );
+
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+    settle_position(
+        &mut env,
+        &cranker,
+        &cranker_account,
+        &no_user.pubkey(),
+        &market,
+    )
+    .await
+    .unwrap();
+    settle_position(
+        &mut env,
+        &cranker,
+        &cranker_account,
+        &yes_user.pubkey(),
+        &market,
+    )
+    .await
+    .unwrap();
+
+    // The fee of 10 is split into a tip of 1 and a protocol fee of 9, which
+    // is accrued on the market.
+    assert_eq!(env.balance(&no_account).await, NO_AMOUNT + 990);
+    assert_eq!(env.balance(&yes_account).await, 0);
+    assert_eq!(env.balance(&cranker_account).await, 1);
+    assert_eq!(env.balance(&pda::yes_token_account(&market)).await, 9);
+    assert_eq!(env.market(&market).await.accrued_fees, 9);
+}
+
+// Test that claims and settlements both succeed while the fee wallet has no
+// token account for the mint, as their fees are accrued on the market.
+#[tokio::test]
+async fn test_payout_without_fee_account() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let (claimed_market, (yes_user, yes_account), _) =
+        filled_market(&mut env, &resolver.pubkey()).await;
+    let (settled_market, (settled_user, settled_account), _) =
+        filled_market(&mut env, &resolver.pubkey()).await;
+
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let fee_wallet = Pubkey::new_unique();
+    let ix = escrow::set_global_state(
+        &owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: owner.pubkey(),
+            new_fee_cut_bps: FEE_CUT_BPS,
+            new_fee_wallet: fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: None,
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: None,
+            new_arbiter: None,
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: None,
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let cranker = Keypair::new();
+    let cranker_account = env.create_token_account(&cranker.pubkey()).await;
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&claimed_market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.update_state(&settled_market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+
+    claim(&mut env, &yes_user, &yes_user.pubkey(), &claimed_market)
+        .await
+        .unwrap();
+    settle_position(
+        &mut env,
+        &cranker,
+        &cranker_account,
+        &settled_user.pubkey(),
+        &settled_market,
+    )
+    .await
+    .unwrap();
+
+    let fee_account = get_associated_token_address(&fee_wallet, &env.mint.pubkey());
+    assert!(env.account(&fee_account).await.is_none());
+    // Each payout has a fee of 20, of which the cranker gets 2 when settling.
+    assert_eq!(env.balance(&yes_account).await, YES_AMOUNT + 1_980);
+    assert_eq!(env.balance(&settled_account).await, YES_AMOUNT + 1_980);
+    assert_eq!(env.balance(&cranker_account).await, 2);
+    assert_eq!(env.market(&claimed_market).await.accrued_fees, 20);
+    assert_eq!(env.market(&settled_market).await.accrued_fees, 18);
+}
+
+// Test that the last payout on the winning side receives the rounding dust,
+// whether it is claimed or settled, leaving only the accrued fees in the
+// escrows.
+#[tokio::test]
+async fn test_payout_remainder() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let mut params = env.market_params(&resolver.pubkey());
+    params.yes_amount = 3;
+    let market = env.init_market(params).await;
+
+    let mut yes_users = Vec::new();
+    for _ in 0..3 {
+        let (user, account) = env.user(1).await;
+        env.deposit(&user, &account, &market, (1, 0)).await;
+        yes_users.push((user, account));
+    }
+    let (no_user, no_account) = env.user(NO_AMOUNT).await;
+    env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT))
+        .await;
+
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+
+    // The first two claims receive a third of the no side, rounded down.
+    for (user, account) in &yes_users[..2] {
+        claim(&mut env, user, &user.pubkey(), &market)
+            .await
+            .unwrap();
+        assert_eq!(env.balance(account).await, 1 + 666 - 7);
+    }
+
+    // The last position receives the remaining 668, minus a fee of 7 of
+    // which the cranker gets 1.
+    let (cranker, cranker_account) = env.user(0).await;
+    let (user, account) = &yes_users[2];
+    settle_position(
+        &mut env,
+        &cranker,
+        &cranker_account,
+        &user.pubkey(),
+        &market,
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.balance(account).await, 1 + 668 - 7);
+    assert_eq!(env.balance(&cranker_account).await, 1);
+
+    // The fees of the two claims are accrued, as is the settled fee less the
+    // tip.
+    assert_eq!(env.balance(&pda::yes_token_account(&market)).await, 0);
+    assert_eq!(env.balance(&pda::no_token_account(&market)).await, 20);
+
+    let info = env.market(&market).await;
+    assert_eq!(info.claimed_stakes, 3);
+    assert_eq!(info.paid_winnings, NO_AMOUNT);
+    assert_eq!(info.accrued_fees, 20);
+}
+
+// Test that deposits into a market that failed to fill are refunded, either
+// withdrawn by the user or settled by a cranker.
+#[tokio::test]
+async fn test_withdraw() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let market = env.init_market(env.market_params(&resolver.pubkey())).await;
+
+    let (user, token_account) = env.user(400).await;
+    let (other, other_account) = env.user(500).await;
+    env.deposit(&user, &token_account, &market, (400, 0)).await;
+    env.deposit(&other, &other_account, &market, (0, 500)).await;
+    assert_error(
+        withdraw(&mut env, &user, &user.pubkey(), &market).await,
+        ErrorCode::MarketNotInvalid,
+    );
+
+    // Anyone can finalize the market once it closes unfilled.
+    env.set_time(CLOSE_TS).await;
+    env.update_state(&market, &user, Outcome::Open)
+        .await
+        .unwrap();
+    assert_eq!(env.market(&market).await.outcome, Outcome::Invalid);
+
+    withdraw(&mut env, &user, &user.pubkey(), &market)
+        .await
+        .unwrap();
+    assert_eq!(env.balance(&token_account).await, 400);
+
+    let cranker = Keypair::new();
+    let cranker_account = env.create_token_account(&cranker.pubkey()).await;
+    settle_position(
+        &mut env,
+        &cranker,
+        &cranker_account,
+        &other.pubkey(),
+        &market,
+    )
+    .await
+    .unwrap();
+    assert_eq!(env.balance(&other_account).await, 500);
+    assert_eq!(env.balance(&cranker_account).await, 0);
+}
+
+// Test the outcome transitions allowed before and after the expiry.
+#[tokio::test]
+async fn test_update_state() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let (market, _, _) = filled_market(&mut env, &resolver.pubkey()).await;
+
+    assert_error(
+        env.update_state(&market, &Keypair::new(), Outcome::Invalid)
+            .await,
+        ErrorCode::IncorrectResolver,
+    );
+    assert_error(
+        env.update_state(&market, &resolver, Outcome::Yes).await,
+        ErrorCode::InvalidTransition,
+    );
+
+    env.update_state(&market, &resolver, Outcome::Invalid)
+        .await
+        .unwrap();
+    let info = env.market(&market).await;
+    assert_eq!(info.outcome, Outcome::Invalid);
+    assert_eq!(info.outcome_ts, START_TS);
+
+    env.update_state(&market, &resolver, Outcome::Open)
+        .await
+        .unwrap();
+    let info = env.market(&market).await;
+    assert_eq!(info.outcome, Outcome::Open);
+    assert_eq!(info.outcome_ts, 0);
+
+    env.set_time(EXPIRY_TS).await;
+    assert_error(
+        env.update_state(&market, &resolver, Outcome::Open).await,
+        ErrorCode::InvalidTransition,
+    );
+    env.update_state(&market, &resolver, Outcome::No)
+        .await
+        .unwrap();
+
+    // The outcome can change until the resolution delay passes.
+    env.set_time(EXPIRY_TS + 60).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+
+    env.set_time(EXPIRY_TS + 60 + RESOLUTION_DELAY as u64).await;
+    env.update_state(&market, &resolver, Outcome::No)
+        .await
+        .unwrap();
+    let info = env.market(&market).await;
+    assert!(info.finalized);
+    assert_eq!(info.outcome, Outcome::Yes);
+}
+
+// Test that a market allowing early resolution can be resolved to a side
+// before the expiry, which closes deposits, and still waits for the
+// resolution delay.
+#[tokio::test]
+async fn test_early_resolution() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let mut params = env.market_params(&resolver.pubkey());
+    params.allow_early_resolution = true;
+    let market = env.init_market(params).await;
+
+    let (yes_user, yes_account) = env.user(YES_AMOUNT).await;
+    let (no_user, no_account) = env.user(NO_AMOUNT).await;
+    env.deposit(&yes_user, &yes_account, &market, (YES_AMOUNT, 0))
+        .await;
+    env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT - 1))
+        .await;
+
+    // A side cannot be filled once the outcome is set.
+    let resolved_ts = START_TS + 60;
+    env.set_time(resolved_ts).await;
+    env.update_state(&market, &resolver, Outcome::No)
+        .await
+        .unwrap();
+    let info = env.market(&market).await;
+    assert_eq!(info.outcome, Outcome::No);
+    assert_eq!(info.outcome_ts, resolved_ts);
+    assert_eq!(info.close_ts, resolved_ts);
+    assert_error(
+        env.try_deposit(&no_user, &no_account, &market, (0, 1), vec![])
+            .await,
+        ErrorCode::MarketClosed,
+    );
+
+    // The unfilled market is invalid as of the early resolution.
+    env.update_state(&market, &resolver, Outcome::No)
+        .await
+        .unwrap();
+    let info = env.market(&market).await;
+    assert!(info.finalized);
+    assert_eq!(info.outcome, Outcome::Invalid);
+
+    // A filled market resolves after the resolution delay.
+    let mut params = env.market_params(&resolver.pubkey());
+    params.allow_early_resolution = true;
+    let market = env.init_market(params).await;
+    env.mint_to(&yes_account, YES_AMOUNT).await;
+    env.mint_to(&no_account, NO_AMOUNT).await;
+    env.deposit(&yes_user, &yes_account, &market, (YES_AMOUNT, 0))
+        .await;
+    env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT))
+        .await;
+
+    env.update_state(&market, &resolver, Outcome::No)
+        .await
+        .unwrap();
+    assert_error(
+        claim(&mut env, &no_user, &no_user.pubkey(), &market).await,
+        ErrorCode::NotFinalized,
+    );
+
+    // The early outcome can't be changed before the expiry.
+    for outcome in [Outcome::Yes, Outcome::Invalid] {
+        assert_error(
+            env.update_state(&market, &resolver, outcome).await,
+            ErrorCode::InvalidTransition,
+        );
+    }
+
+    env.set_time(resolved_ts + RESOLUTION_DELAY as u64).await;
+    claim(&mut env, &no_user, &no_user.pubkey(), &market)
+        .await
+        .unwrap();
+    let info = env.market(&market).await;
+    assert!(info.finalized);
+    assert_eq!(info.outcome, Outcome::No);
+    assert!(info.expiry_ts > resolved_ts + RESOLUTION_DELAY as u64);
+
+    // Resolving after deposits closed keeps the close timestamp.
+    let close_ts = resolved_ts + RESOLUTION_DELAY as u64 + 60;
+    let mut params = env.market_params(&resolver.pubkey());
+    params.close_ts = close_ts;
+    params.allow_early_resolution = true;
+    let market = env.init_market(params).await;
+    env.set_time(close_ts + 60).await;
+    env.update_state(&market, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    assert_eq!(env.market(&market).await.close_ts, close_ts);
+}
+
+// Test that deposits wait for the resolver to acknowledge the market.
+#[tokio::test]
+async fn test_resolver_acknowledge() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let mut params = env.market_params(&resolver.pubkey());
+    params.require_acknowledgement = true;
+    let market = env.init_market(params).await;
+
+    let (user, token_account) = env.user(YES_AMOUNT).await;
+    env.init_user_position(&user, &market).await;
+    assert_error(
+        env.try_deposit(&user, &token_account, &market, (YES_AMOUNT, 0), vec![])
+            .await,
+        ErrorCode::NotAcknowledged,
+    );
+
+    let wrong_resolver = Keypair::new();
+    let ix = escrow::resolver_acknowledge(&market, &wrong_resolver.pubkey());
+    assert_error(
+        env.process(&[ix], &[&wrong_resolver]).await,
+        ErrorCode::IncorrectResolver,
+    );
+
+    // No bond accounts are needed without a resolver bond.
+    let ix = escrow::resolver_acknowledge(&market, &resolver.pubkey());
+    env.process(&[ix], &[&resolver]).await.unwrap();
+    assert!(env.market(&market).await.acknowledged);
+    assert!(env
+        .account(&pda::resolver_bond_token_account(&market))
+        .await
+        .is_none());
+
+    env.try_deposit(&user, &token_account, &market, (YES_AMOUNT, 0), vec![])
+        .await
+        .unwrap();
+}
+
+// Test that only allowlisted users can deposit into a private market, and
+// that the allowlist is frozen once the market closes.
+#[tokio::test]
+async fn test_set_allowlist_root() {
+    let mut env = TestEnv::new().await;
+    let market = env
+        .init_market(env.market_params(&Pubkey::new_unique()))
+        .await;
+
+    let (allowed, allowed_account) = env.user(YES_AMOUNT).await;
+    let (other, other_account) = env.user(YES_AMOUNT).await;
+    let params = || SetAllowlistRootParams {
+        allowlist_root: Some(merkle::hash_leaf(allowed.pubkey().as_ref())),
+    };
+
+    let ix = escrow::set_allowlist_root(&market, &other.pubkey(), params());
+    assert_error(
+        env.process(&[ix], &[&other]).await,
+        ErrorCode::IncorrectCreator,
+    );
+
+    let ix = escrow::set_allowlist_root(&market, &env.creator(), params());
+    env.process(&[ix], &[]).await.unwrap();
+
+    env.init_user_position(&other, &market).await;
+    assert_error(
+        env.try_deposit(&other, &other_account, &market, (100, 0), vec![])
+            .await,
+        ErrorCode::NotAllowlisted,
+    );
+    // The root of a single leaf tree is the leaf, so the proof is empty.
+    env.deposit(&allowed, &allowed_account, &market, (100, 0))
+        .await;
+
+    env.set_time(CLOSE_TS).await;
+    let ix = escrow::set_allowlist_root(&market, &env.creator(), params());
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::MarketClosed);
+}
+
+// Test that the creator can replace the resolver until someone else deposits.
+#[tokio::test]
+async fn test_set_resolver() {
+    let mut env = TestEnv::new().await;
+    let market = env
+        .init_market(env.market_params(&Pubkey::new_unique()))
+        .await;
+    let resolver = Pubkey::new_unique();
+
+    let other = Keypair::new();
+    let ix = escrow::set_resolver(&market, &other.pubkey(), SetResolverParams { resolver });
+    assert_error(
+        env.process(&[ix], &[&other]).await,
+        ErrorCode::IncorrectCreator,
+    );
+
+    let ix = escrow::set_resolver(&market, &env.creator(), SetResolverParams { resolver });
+    env.process(&[ix], &[]).await.unwrap();
+    assert_eq!(env.market(&market).await.resolver, resolver);
+
+    let (user, token_account) = env.user(100).await;
+    env.deposit(&user, &token_account, &market, (100, 0)).await;
+
+    let ix = escrow::set_resolver(
+        &market,
+        &env.creator(),
+        SetResolverParams {
+            resolver: Pubkey::new_unique(),
+        },
+    );
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::DepositsExist);
+}
+
+// Test that a market is only closed once finalized, with its user positions
+// closed, that tokens left in its escrows are swept to the fee wallet, and
+// that it is removed from the registries.
+#[tokio::test]
+async fn test_close_market() {
+    let mut env = TestEnv::new().await;
+    let market = env
+        .init_market(env.market_params(&Pubkey::new_unique()))
+        .await;
+    let close_market = |env: &TestEnv| {
+        escrow::close_market(
+            &market,
+            &env.creator(),
+            &env.mint.pubkey(),
+            &env.fee_wallet,
+            RegistryPages::default(),
+        )
+    };
+
+    let (user, token_account) = env.user(100).await;
+    env.deposit(&user, &token_account, &market, (100, 0)).await;
+
+    let ix = close_market(&env);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::NotFinalized);
+
+    env.set_time(CLOSE_TS).await;
+    let ix = close_market(&env);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::OpenPositions);
+
+    env.update_state(&market, &user, Outcome::Open)
+        .await
+        .unwrap();
+    let ix = escrow::close_user_position(&user.pubkey(), &market);
+    assert_error(
+        env.process(&[ix], &[&user]).await,
+        ErrorCode::PositionNotEmpty,
+    );
+    withdraw(&mut env, &user, &user.pubkey(), &market)
+        .await
+        .unwrap();
+
+    let ix = close_market(&env);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::OpenPositions);
+
+    let ix = escrow::close_user_position(&user.pubkey(), &market);
+    env.process(&[ix], &[&user]).await.unwrap();
+    assert!(env
+        .account(&pda::user_position(&user.pubkey(), &market))
+        .await
+        .is_none());
+    assert_eq!(env.market(&market).await.open_positions, 0);
+
+    // Migrated markets also wait for the positions that were not migrated.
+    let mut info = env.market(&market).await;
+    info.unmigrated_positions = 1;
+    env.write(&market, &info).await;
+    let ix = close_market(&env);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::OpenPositions);
+    info.unmigrated_positions = 0;
+    env.write(&market, &info).await;
+
+    // Tokens sent to an escrow belong to no position, and are swept to the
+    // fee wallet.
+    env.mint_to(&pda::yes_token_account(&market), 5).await;
+    let ix = close_market(&env);
+    env.process(&[ix], &[]).await.unwrap();
+
+    assert!(env.account(&market).await.is_none());
+    let fee_account = get_associated_token_address(&env.fee_wallet, &env.mint.pubkey());
+    assert_eq!(env.balance(&fee_account).await, 5);
+    assert!(env
+        .account(&pda::yes_token_account(&market))
+        .await
+        .is_none());
+
+    let registry: MarketRegistry = env.decode(&pda::creator_registry(&env.creator())).await;
+    assert_eq!(registry.market_count, 0);
+}
+
+// Test that markets created before registries are closed without being
+// removed from them.
+#[tokio::test]
+async fn test_close_unregistered_market() {
+    let mut env = TestEnv::new().await;
+    let market = env
+        .init_market(env.market_params(&Pubkey::new_unique()))
+        .await;
+    let mut info = env.market(&market).await;
+    assert!(info.registered);
+    info.registered = false;
+    env.write(&market, &info).await;
+
+    env.set_time(CLOSE_TS).await;
+    let ix = escrow::close_market(
+        &market,
+        &env.creator(),
+        &env.mint.pubkey(),
+        &env.fee_wallet,
+        RegistryPages::default(),
+    );
+    env.process(&[ix], &[]).await.unwrap();
+
+    assert!(env.account(&market).await.is_none());
+    let registry: MarketRegistry = env.decode(&pda::creator_registry(&env.creator())).await;
+    assert_eq!(registry.market_count, 1);
+}
+
+// Test that the creation bond is escrowed in the token mint or in lamports,
+// refunded once the market resolves to a side, and forfeited to the fee
+// wallet when the market is invalid because it was not filled.
+#[tokio::test]
+async fn test_creation_bond() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let creator_account = env.create_token_account(&env.creator()).await;
+    env.mint_to(&creator_account, 600).await;
+    let fee_account = get_associated_token_address(&env.fee_wallet, &env.mint.pubkey());
+    let settle_creation_bond = |env: &TestEnv, market| {
+        escrow::settle_creation_bond(market, &env.mint.pubkey(), &env.creator(), &env.fee_wallet)
+    };
+    let close_market = |env: &TestEnv, market| {
+        escrow::close_market(
+            market,
+            &env.creator(),
+            &env.mint.pubkey(),
+            &env.fee_wallet,
+            RegistryPages::default(),
+        )
+    };
+
+    // Markets denominated in a mint with a token bond escrow it.
+    let ix = escrow::set_mint_policy(
+        &owner.pubkey(),
+        &env.creator(),
+        &env.mint.pubkey(),
+        SetMintPolicyParams {
+            new_min_fill: 0,
+            new_creation_bond: 300,
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let (filled, _, _) = filled_market(&mut env, &resolver.pubkey()).await;
+
+    // The bond escrow is created even if it was sent lamports beforehand.
+    let unfilled = Keypair::new();
+    let ix = system_instruction::transfer(
+        &env.creator(),
+        &pda::bond_token_account(&unfilled.pubkey()),
+        Rent::default().minimum_balance(0),
+    );
+    env.process(&[ix], &[]).await.unwrap();
+    env.try_init_market(&unfilled, env.market_params(&resolver.pubkey()))
+        .await
+        .unwrap();
+    let unfilled = unfilled.pubkey();
+    let info = env.market(&unfilled).await;
+    assert_eq!(info.creation_bond, 300);
+    assert!(info.creation_bond_in_mint);
+    assert_eq!(info.creation_bond_payer, env.creator());
+    assert_eq!(env.balance(&pda::bond_token_account(&unfilled)).await, 300);
+    assert_eq!(env.balance(&creator_account).await, 0);
+
+    // Otherwise the bond of the global state is escrowed in lamports.
+    let ix = escrow::set_mint_policy(
+        &owner.pubkey(),
+        &env.creator(),
+        &env.mint.pubkey(),
+        SetMintPolicyParams {
+            new_min_fill: 0,
+            new_creation_bond: 0,
+        },
+    );
+    let global_ix = escrow::set_global_state(
+        &owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: owner.pubkey(),
+            new_fee_cut_bps: FEE_CUT_BPS,
+            new_fee_wallet: env.fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: None,
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: Some(10_000_000),
+            new_arbiter: None,
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: None,
+        },
+    );
+    env.process(&[ix, global_ix], &[&owner]).await.unwrap();
+
+    let sol_market = env.init_market(env.market_params(&resolver.pubkey())).await;
+    let info = env.market(&sol_market).await;
+    assert_eq!(info.creation_bond, 10_000_000);
+    assert!(!info.creation_bond_in_mint);
+    assert!(env
+        .account(&pda::bond_token_account(&sol_market))
+        .await
+        .is_none());
+    let market_lamports = env.account(&sol_market).await.unwrap().lamports;
+
+    let ix = settle_creation_bond(&env, &filled);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::NotFinalized);
+
+    // Unfilled markets forfeit their bond, which must be settled before the
+    // market is closed.
+    env.set_time(CLOSE_TS).await;
+    for market in [unfilled, sol_market] {
+        env.update_state(&market, &resolver, Outcome::Open)
+            .await
+            .unwrap();
+    }
+    let ix = close_market(&env, &unfilled);
+    assert_error(
+        env.process(&[ix], &[]).await,
+        ErrorCode::CreationBondNotSettled,
+    );
+
+    let ix = settle_creation_bond(&env, &unfilled);
+    env.process(&[ix], &[]).await.unwrap();
+    assert_eq!(env.balance(&fee_account).await, 300);
+    assert_eq!(env.market(&unfilled).await.creation_bond, 0);
+
+    let ix = settle_creation_bond(&env, &unfilled);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::NoCreationBond);
+
+    let ix = close_market(&env, &unfilled);
+    env.process(&[ix], &[]).await.unwrap();
+    assert!(env
+        .account(&pda::bond_token_account(&unfilled))
+        .await
+        .is_none());
+
+    let ix = settle_creation_bond(&env, &sol_market);
+    env.process(&[ix], &[]).await.unwrap();
+    let fee_wallet = env.fee_wallet;
+    let account = env.account(&fee_wallet).await.unwrap();
+    assert_eq!(account.lamports, 10_000_000);
+    let account = env.account(&sol_market).await.unwrap();
+    assert_eq!(account.lamports, market_lamports - 10_000_000);
+
+    // Markets resolving to a side refund their bond to the creator.
+    env.set_time(EXPIRY_TS).await;
+    env.update_state(&filled, &resolver, Outcome::Yes)
+        .await
+        .unwrap();
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+
+    let ix = settle_creation_bond(&env, &filled);
+    env.process(&[ix], &[]).await.unwrap();
+    assert_eq!(env.balance(&creator_account).await, 300);
+    assert_eq!(env.balance(&fee_account).await, 300);
+}
+
+// Test that a resolver bond is posted on acknowledgement, refunded once the
+// market finalizes, and slashed to the disputing users when the arbiter
+// overturns the outcome.
+#[tokio::test]
+async fn test_resolver_bond() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let arbiter = Keypair::new();
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let creator_account = env.create_token_account(&env.creator()).await;
+    env.mint_to(&creator_account, 200).await;
+
+    let ix = escrow::set_global_state(
+        &owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: owner.pubkey(),
+            new_fee_cut_bps: FEE_CUT_BPS,
+            new_fee_wallet: env.fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: None,
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: None,
+            new_arbiter: Some(arbiter.pubkey()),
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: None,
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let mut params = env.market_params(&resolver.pubkey());
+    params.resolver_bond = 100;
+    assert_error(
+        env.try_init_market(&Keypair::new(), params.clone()).await,
+        ErrorCode::ResolverBondRequiresAcknowledgement,
+    );
+
+    // Both markets are acknowledged, posting the bond once, and filled.
+    params.require_acknowledgement = true;
+    let slashed = env.init_market(params.clone()).await;
+    let refunded = env.init_market(params).await;
+    let (yes_user, yes_account) = env.user(2 * YES_AMOUNT).await;
+    let (no_user, no_account) = env.user(2 * NO_AMOUNT).await;
+    let ix = escrow::resolver_acknowledge(&slashed, &resolver.pubkey());
+    assert_error(
+        env.process(&[ix], &[&resolver]).await,
+        ErrorCode::InvalidResolverBondAccounts,
+    );
+    for market in [slashed, refunded, slashed] {
+        let mut ix = escrow::resolver_acknowledge(&market, &resolver.pubkey());
+        ix.accounts.extend(escrow::resolver_bond_accounts(
+            &market,
+            &env.creator(),
+            &env.mint.pubkey(),
+        ));
+        env.process(&[ix], &[&resolver]).await.unwrap();
+    }
+    for market in [slashed, refunded] {
+        let info = env.market(&market).await;
+        assert_eq!(info.resolver_bond_payer, env.creator());
+        assert_eq!(
+            env.balance(&pda::resolver_bond_token_account(&market))
+                .await,
+            100
+        );
+
+        env.deposit(&yes_user, &yes_account, &market, (YES_AMOUNT, 0))
+            .await;
+        env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT))
+            .await;
+    }
+    assert_eq!(env.balance(&creator_account).await, 0);
+
+    let ix = escrow::dispute_outcome(&no_user.pubkey(), &slashed);
+    assert_error(
+        env.process(&[ix], &[&no_user]).await,
+        ErrorCode::OutcomeNotSet,
+    );
+
+    // A disputed outcome waits for the arbiter, and can't be updated by the
+    // resolver.
+    env.set_time(EXPIRY_TS).await;
+    for market in [slashed, refunded] {
+        env.update_state(&market, &resolver, Outcome::Yes)
+            .await
+            .unwrap();
+    }
+    let ix = escrow::dispute_outcome(&no_user.pubkey(), &slashed);
+    env.process(&[ix], &[&no_user]).await.unwrap();
+    let ix = escrow::dispute_outcome(&no_user.pubkey(), &slashed);
+    assert_error(
+        env.process(&[ix], &[&no_user]).await,
+        ErrorCode::AlreadyDisputed,
+    );
+    assert_error(
+        env.update_state(&slashed, &resolver, Outcome::No).await,
+        ErrorCode::MarketDisputed,
+    );
+
+    env.set_time(EXPIRY_TS + RESOLUTION_DELAY as u64).await;
+    assert_error(
+        claim(&mut env, &yes_user, &yes_user.pubkey(), &slashed).await,
+        ErrorCode::NotFinalized,
+    );
+
+    let arbitrate = |arbiter: &Keypair| {
+        escrow::arbitrate(
+            &arbiter.pubkey(),
+            &slashed,
+            ArbitrateParams {
+                outcome: Outcome::No,
+            },
+        )
+    };
+    let ix = arbitrate(&resolver);
+    assert_error(
+        env.process(&[ix], &[&resolver]).await,
+        ErrorCode::IncorrectArbiter,
+    );
+    let ix = arbitrate(&arbiter);
+    env.process(&[ix], &[&arbiter]).await.unwrap();
+    let info = env.market(&slashed).await;
+    assert!(info.finalized);
+    assert!(info.resolver_slashed);
+    assert_eq!(info.outcome, Outcome::No);
+
+    // The overturned resolver's bond goes to the disputing user.
+    let ix = escrow::settle_resolver_bond(&slashed, &env.mint.pubkey(), &env.creator());
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::ResolverSlashed);
+    let ix = escrow::claim_slashed_bond(&yes_user.pubkey(), &slashed, &env.mint.pubkey());
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::NotDisputed);
+    let ix = escrow::claim_slashed_bond(&no_user.pubkey(), &slashed, &env.mint.pubkey());
+    env.process(&[ix], &[]).await.unwrap();
+    assert_eq!(env.balance(&no_account).await, 100);
+
+    // The other bond is refunded to its payer.
+    let ix = escrow::settle_resolver_bond(&refunded, &env.mint.pubkey(), &env.creator());
+    env.process(&[ix], &[]).await.unwrap();
+    assert_eq!(env.balance(&creator_account).await, 100);
+    let ix = escrow::settle_resolver_bond(&refunded, &env.mint.pubkey(), &env.creator());
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::NoResolverBond);
+}
+
+// Test that disputing an outcome posts the dispute bond of the global state,
+// which is forfeited to the fee wallet when the arbiter upholds the outcome,
+// and refunded to the user when the arbiter overturns it.
+#[tokio::test]
+async fn test_dispute_bond() {
+    let mut env = TestEnv::new().await;
+    let resolver = Keypair::new();
+    let arbiter = Keypair::new();
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+
+    let ix = escrow::set_global_state(
+        &owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: owner.pubkey(),
+            new_fee_cut_bps: FEE_CUT_BPS,
+            new_fee_wallet: env.fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: None,
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: None,
+            new_arbiter: Some(arbiter.pubkey()),
+            new_dispute_bond_lamports: Some(10_000_000),
+            new_template_resolver_program: None,
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+
+    let (upheld, _, (upheld_user, _)) = filled_market(&mut env, &resolver.pubkey()).await;
+    let (overturned, _, (overturned_user, _)) = filled_market(&mut env, &resolver.pubkey()).await;
+
+    // A user without the lamports for the bond can't dispute.
+    env.set_time(EXPIRY_TS).await;
+    for market in [upheld, overturned] {
+        env.update_state(&market, &resolver, Outcome::Yes)
+            .await
+            .unwrap();
+    }
+    let ix = escrow::dispute_outcome(&upheld_user.pubkey(), &upheld);
+    assert!(env.process(&[ix], &[&upheld_user]).await.is_err());
+
+    for (user, market) in [(&upheld_user, upheld), (&overturned_user, overturned)] {
+        let ix = system_instruction::transfer(&env.creator(), &user.pubkey(), 10_000_000);
+        env.process(&[ix], &[]).await.unwrap();
+        let ix = escrow::dispute_outcome(&user.pubkey(), &market);
+        env.process(&[ix], &[user]).await.unwrap();
+
+        let position = env.user_position(&user.pubkey(), &market).await;
+        assert_eq!(position.dispute_bond, 10_000_000);
+        assert!(env.account(&user.pubkey()).await.is_none());
+    }
+
+    let ix = escrow::settle_dispute_bond(&upheld_user.pubkey(), &upheld, &env.fee_wallet);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::NotFinalized);
+
+    for (market, outcome) in [(upheld, Outcome::Yes), (overturned, Outcome::No)] {
+        let ix = escrow::arbitrate(&arbiter.pubkey(), &market, ArbitrateParams { outcome });
+        env.process(&[ix], &[&arbiter]).await.unwrap();
+    }
+    assert!(env.market(&upheld).await.dispute_upheld);
+    assert!(!env.market(&overturned).await.dispute_upheld);
+
+    // The bond of an upheld outcome goes to the fee wallet.
+    let position_lamports = env
+        .account(&pda::user_position(&upheld_user.pubkey(), &upheld))
+        .await
+        .unwrap()
+        .lamports;
+    let ix = escrow::settle_dispute_bond(&upheld_user.pubkey(), &upheld, &env.fee_wallet);
+    env.process(&[ix], &[]).await.unwrap();
+    let fee_wallet = env.fee_wallet;
+    let account = env.account(&fee_wallet).await.unwrap();
+    assert_eq!(account.lamports, 10_000_000);
+    let account = env
+        .account(&pda::user_position(&upheld_user.pubkey(), &upheld))
+        .await
+        .unwrap();
+    assert_eq!(account.lamports, position_lamports - 10_000_000);
+    let position = env.user_position(&upheld_user.pubkey(), &upheld).await;
+    assert_eq!(position.dispute_bond, 0);
+
+    let ix = escrow::settle_dispute_bond(&upheld_user.pubkey(), &upheld, &env.fee_wallet);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::NoDisputeBond);
+
+    // The bond of an overturned outcome is refunded to the user.
+    let ix = escrow::settle_dispute_bond(&overturned_user.pubkey(), &overturned, &env.fee_wallet);
+    env.process(&[ix], &[]).await.unwrap();
+    let account = env.account(&overturned_user.pubkey()).await.unwrap();
+    assert_eq!(account.lamports, 10_000_000);
+    let account = env.account(&fee_wallet).await.unwrap();
+    assert_eq!(account.lamports, 10_000_000);
+}
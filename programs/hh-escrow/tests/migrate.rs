@@ -0,0 +1,178 @@
+mod utils;
+
+use solana_program_test::tokio;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use hh_client::{escrow, pda};
+use hh_escrow::error::ErrorCode;
+use hh_escrow::state::{
+    Bps, GlobalState, GlobalStateV0, Market, MarketV0, Outcome, UriResourceV0, UserPosition,
+    UserPositionV0, URI_MAX_LEN, URI_MAX_LEN_V0,
+};
+
+use crate::utils::{add_fixture, assert_error, program_test, unversioned, TestEnv, FEE_CUT_BPS};
+
+// Test that markets and user positions in the unversioned layout, as loaded
+// by the local validator, are migrated once, keeping their fields, and that
+// the market counts its migrated positions.
+#[tokio::test]
+async fn test_migrate_market_and_user_position() {
+    let mut test = program_test();
+    let market = add_fixture(&mut test, "market_v0");
+    let user_position = add_fixture(&mut test, "user_position_v0");
+    let extra_position = Pubkey::new_unique();
+    test.add_account(
+        extra_position,
+        unversioned::<UserPosition>(&UserPositionV0 {
+            market,
+            yes_amount: 0,
+            no_amount: 0,
+        }),
+    );
+    let mut env = TestEnv::start(test).await;
+    let payer = env.creator();
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+
+    // Only the owner of the global state vouches for the position count.
+    let ix = escrow::migrate_market(&market, &payer, &payer, 1);
+    assert_error(
+        env.process(&[ix], &[]).await,
+        ErrorCode::IncorrectGlobalStateOwner,
+    );
+
+    let ix = escrow::migrate_market(&market, &owner.pubkey(), &payer, 1);
+    env.process(&[ix], &[&owner]).await.unwrap();
+    let info = env.market(&market).await;
+    assert_eq!(info.version, Market::VERSION);
+    assert_eq!(info.creator, Pubkey::new_from_array([1; 32]));
+    assert_eq!(info.resolver, Pubkey::new_from_array([2; 32]));
+    assert_eq!(info.yes_amount, 1_000);
+    assert_eq!(info.metadata.version, 0);
+    assert_eq!(
+        info.metadata.uri.as_str(),
+        Some("https://example.com/market.json"),
+    );
+
+    assert_eq!(info.unmigrated_positions, 1);
+    assert_eq!(info.open_positions, 0);
+
+    let ix = escrow::migrate_market(&market, &owner.pubkey(), &payer, 1);
+    assert_error(
+        env.process(&[ix], &[&owner]).await,
+        ErrorCode::AlreadyMigrated,
+    );
+
+    // The position is counted by its own market.
+    let other_market = env.init_market(env.market_params(&payer)).await;
+    let ix = escrow::migrate_user_position(&user_position, &other_market, &payer);
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::IncorrectMarket);
+
+    let ix = escrow::migrate_user_position(&user_position, &market, &payer);
+    env.process(&[ix], &[]).await.unwrap();
+    let info: UserPosition = env.decode(&user_position).await;
+    assert_eq!(info.version, UserPosition::VERSION);
+    assert_eq!(info.market, market);
+    assert_eq!(info.yes_amount, 10);
+    assert_eq!(info.no_amount, 20);
+    let info = env.market(&market).await;
+    assert_eq!(info.unmigrated_positions, 0);
+    assert_eq!(info.open_positions, 1);
+
+    // Positions beyond the count taken at migration are refused.
+    let ix = escrow::migrate_user_position(&extra_position, &market, &payer);
+    assert_error(
+        env.process(&[ix], &[]).await,
+        ErrorCode::NoUnmigratedPositions,
+    );
+
+    // A market is not a user position.
+    let ix = escrow::migrate_user_position(&market, &market, &payer);
+    assert_error(
+        env.process(&[ix], &[]).await,
+        ErrorCode::UnknownAccountLayout,
+    );
+}
+
+// Test that the global state in the unversioned layout is migrated, with
+// the fields added since taking their default value.
+#[tokio::test]
+async fn test_migrate_global_state() {
+    let owner = Keypair::new();
+    let fee_wallet = Pubkey::new_unique();
+    let account = unversioned::<GlobalState>(&GlobalStateV0 {
+        owner: owner.pubkey(),
+        fee_cut_bps: Bps::new(FEE_CUT_BPS).unwrap(),
+        fee_wallet,
+    });
+
+    let mut env = TestEnv::start_with(program_test(), owner, fee_wallet, account).await;
+
+    let ix = escrow::migrate_global_state(&env.creator());
+    env.process(&[ix], &[]).await.unwrap();
+
+    let info: GlobalState = env.decode(&pda::global_state()).await;
+    assert_eq!(info.version, GlobalState::VERSION);
+    assert_eq!(info.owner, env.owner.pubkey());
+    assert_eq!(info.fee_cut_bps.get(), FEE_CUT_BPS);
+    assert_eq!(info.fee_wallet, fee_wallet);
+    assert_eq!(info.crank_tip_bps.get(), 0);
+    assert_eq!(info.max_resolution_window, 0);
+}
+
+fn market_v0(creator: Pubkey, uri: &str) -> MarketV0 {
+    MarketV0 {
+        creator,
+        resolver: Pubkey::new_unique(),
+        token_mint: Pubkey::new_unique(),
+        yes_token_account: Pubkey::new_unique(),
+        no_token_account: Pubkey::new_unique(),
+        yes_amount: 5,
+        yes_filled: 0,
+        no_amount: 7,
+        no_filled: 0,
+        close_ts: 0,
+        expiry_ts: 0,
+        outcome_ts: 0,
+        resolution_delay: 0,
+        outcome: Outcome::Open,
+        finalized: false,
+        yes_account_bump: 0,
+        no_account_bump: 0,
+        acknowledged: true,
+        uri: uri_v0(uri),
+    }
+}
+
+fn uri_v0(uri: &str) -> UriResourceV0 {
+    let mut bytes = [0; URI_MAX_LEN_V0];
+    bytes[..uri.len()].copy_from_slice(uri.as_bytes());
+
+    UriResourceV0 {
+        len: uri.len() as u16,
+        uri: bytes,
+    }
+}
+
+// Test that a market whose URI does not fit the versioned layout fails to
+// migrate, rather than losing its URI.
+#[tokio::test]
+async fn test_migrate_market_long_uri() {
+    let mut test = program_test();
+    let market = Pubkey::new_unique();
+    let uri = format!("https://example.com/{}", "a".repeat(URI_MAX_LEN));
+    test.add_account(
+        market,
+        unversioned::<Market>(&market_v0(Pubkey::new_unique(), &uri)),
+    );
+    let mut env = TestEnv::start(test).await;
+
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let ix = escrow::migrate_market(&market, &owner.pubkey(), &env.creator(), 0);
+    assert_error(
+        env.process(&[ix], &[&owner]).await,
+        ErrorCode::UriTooLongToMigrate,
+    );
+    let account = env.account(&market).await.unwrap();
+    assert_eq!(account.data.len(), 8 + MarketV0::LEN);
+}
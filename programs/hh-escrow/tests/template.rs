@@ -0,0 +1,152 @@
+mod utils;
+
+use solana_program_test::tokio;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use hh_client::escrow::{self, RegistryPages};
+use hh_client::pda;
+use hh_escrow::error::ErrorCode;
+use hh_escrow::instructions::InitializeMarketTemplateParams;
+use hh_escrow::state::{DepositLimits, MarketTemplate};
+
+use crate::utils::{
+    assert_error, TestEnv, NO_AMOUNT, RESOLUTION_DELAY, RESOLUTION_WINDOW, START_TS, YES_AMOUNT,
+};
+
+const CADENCE: u64 = 86_400;
+const CLOSE_TS: u64 = START_TS + 3_600;
+const EXPIRY_TS: u64 = START_TS + 7_200;
+
+fn template_params(cadence: u64) -> InitializeMarketTemplateParams {
+    InitializeMarketTemplateParams {
+        close_ts: CLOSE_TS,
+        expiry_ts: EXPIRY_TS,
+        cadence,
+        resolution_delay: RESOLUTION_DELAY,
+        yes_amount: YES_AMOUNT,
+        no_amount: NO_AMOUNT,
+        resolver: Pubkey::new_unique(),
+        resolver_program: None,
+        resolver_data: vec![],
+        uri_prefix: "https://example.com/daily/".to_string(),
+        title: "Daily market".to_string(),
+        category: *b"test\0\0\0\0\0\0\0\0\0\0\0\0",
+        yes_limits: DepositLimits::default(),
+        no_limits: DepositLimits::default(),
+        require_acknowledgement: false,
+        allow_early_resolution: false,
+        max_resolution_window: RESOLUTION_WINDOW,
+        resolver_bond: 0,
+    }
+}
+
+// Test that markets are spawned once per cadence, and that a spawn that is
+// late skips the missed periods.
+#[tokio::test]
+async fn test_spawn_market_from_template() {
+    let mut env = TestEnv::new().await;
+    let creator = env.creator();
+    let mint = env.mint.pubkey();
+    let template = Keypair::new();
+
+    let ix =
+        escrow::initialize_market_template(&template.pubkey(), &creator, &mint, template_params(0));
+    assert_error(
+        env.process(&[ix], &[&template]).await,
+        ErrorCode::InvalidTemplateCadence,
+    );
+
+    let ix = escrow::initialize_market_template(
+        &template.pubkey(),
+        &creator,
+        &mint,
+        template_params(CADENCE),
+    );
+    env.process(&[ix], &[&template]).await.unwrap();
+
+    let spawn = |index| {
+        escrow::spawn_market_from_template(
+            &template.pubkey(),
+            index,
+            &creator,
+            &mint,
+            &creator,
+            RegistryPages::default(),
+            None,
+        )
+    };
+
+    env.process(&[spawn(0)], &[]).await.unwrap();
+    let market = env.market(&pda::market(&template.pubkey(), 0)).await;
+    assert_eq!(market.close_ts, CLOSE_TS);
+    assert_eq!(market.expiry_ts, EXPIRY_TS);
+    assert_eq!(
+        market.metadata.uri.as_str(),
+        Some("https://example.com/daily/0")
+    );
+
+    assert_error(
+        env.process(&[spawn(1)], &[]).await,
+        ErrorCode::TemplateNotDue,
+    );
+
+    // Two periods after the first market closes, the next market closes in
+    // the third period.
+    env.set_time(CLOSE_TS + 2 * CADENCE + 1).await;
+    env.process(&[spawn(1)], &[]).await.unwrap();
+    let market = env.market(&pda::market(&template.pubkey(), 1)).await;
+    assert_eq!(market.close_ts, CLOSE_TS + 3 * CADENCE);
+    assert_eq!(market.expiry_ts, EXPIRY_TS + 3 * CADENCE);
+
+    let template: MarketTemplate = env.decode(&template.pubkey()).await;
+    assert_eq!(template.spawned, 2);
+    assert_eq!(template.close_ts, CLOSE_TS + 4 * CADENCE);
+}
+
+// Test that a template may only have the resolver program allowed by the
+// global state, and that spawned markets carry the template resolver bond
+// and wait for the resolver to acknowledge them.
+#[tokio::test]
+async fn test_template_resolver() {
+    let mut env = TestEnv::new().await;
+    let creator = env.creator();
+    let mint = env.mint.pubkey();
+    let template = Keypair::new();
+
+    let mut params = template_params(CADENCE);
+    params.resolver_program = Some(Pubkey::new_unique());
+    let ix = escrow::initialize_market_template(&template.pubkey(), &creator, &mint, params);
+    assert_error(
+        env.process(&[ix], &[&template]).await,
+        ErrorCode::ResolverProgramNotAllowed,
+    );
+
+    let mut params = template_params(CADENCE);
+    params.resolver_bond = 100;
+    let ix = escrow::initialize_market_template(&template.pubkey(), &creator, &mint, params);
+    assert_error(
+        env.process(&[ix], &[&template]).await,
+        ErrorCode::ResolverBondRequiresAcknowledgement,
+    );
+
+    let mut params = template_params(CADENCE);
+    params.require_acknowledgement = true;
+    params.resolver_bond = 100;
+    let ix = escrow::initialize_market_template(&template.pubkey(), &creator, &mint, params);
+    env.process(&[ix], &[&template]).await.unwrap();
+
+    let ix = escrow::spawn_market_from_template(
+        &template.pubkey(),
+        0,
+        &creator,
+        &mint,
+        &creator,
+        RegistryPages::default(),
+        None,
+    );
+    env.process(&[ix], &[]).await.unwrap();
+    let market = env.market(&pda::market(&template.pubkey(), 0)).await;
+    assert_eq!(market.resolver_bond, 100);
+    assert!(!market.acknowledged);
+}
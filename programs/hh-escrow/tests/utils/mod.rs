@@ -0,0 +1,440 @@
+//! Helpers shared by the integration tests, running the program natively in
+//! `solana-program-test` with a controllable clock.
+
+#![allow(dead_code)]
+
+pub mod native;
+
+use anchor_lang::{AccountDeserialize, AccountSerialize, AnchorSerialize, Discriminator, Owner};
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::clock::Clock;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_sdk::transport::TransportError;
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+
+use hh_client::escrow::{self, RegistryPages};
+use hh_client::pda;
+use hh_escrow::instructions::{DepositParams, InitializeMarketParams, UpdateStateParams};
+use hh_escrow::state::{Bps, DepositLimits, GlobalState, Market, Outcome, UserPosition};
+
+/// The unix timestamp the tests start at.
+pub const START_TS: u64 = 1_650_000_000;
+
+pub const YES_AMOUNT: u64 = 1_000;
+pub const NO_AMOUNT: u64 = 2_000;
+pub const RESOLUTION_DELAY: u32 = 3_600;
+pub const RESOLUTION_WINDOW: u32 = 86_400 * 7;
+
+/// The bounds on the resolution window of a new market.
+pub const MIN_RESOLUTION_WINDOW: u32 = 3_600;
+pub const MAX_RESOLUTION_WINDOW: u32 = 86_400 * 90;
+
+pub const FEE_CUT_BPS: u16 = 100;
+pub const CRANK_TIP_BPS: u16 = 1_000;
+
+/// The compute units requested by every transaction, offset by a nonce.
+const COMPUTE_UNITS: u32 = 200_000;
+
+/// Returns the test harness for the escrow program.
+///
+/// Every program runs natively, including the SPL programs, as the BPF builds
+/// bundled with `solana-program-test` need the JIT of `solana_rbpf` 0.2, which
+/// crashes on recent hosts.
+pub fn program_test() -> ProgramTest {
+    let mut test = ProgramTest::new("hh_escrow", hh_escrow::ID, crate::native!(hh_escrow::entry));
+    test.add_program(
+        "spl_token",
+        spl_token::ID,
+        crate::native!(spl_token::processor::Processor::process),
+    );
+    test.add_program(
+        "spl_associated_token_account",
+        spl_associated_token_account::ID,
+        crate::native!(spl_associated_token_account::processor::process_instruction),
+    );
+
+    test
+}
+
+/// Returns an account holding the serialized Anchor account.
+pub fn anchor_account<T: AccountSerialize + Owner>(account: &T) -> Account {
+    let mut data = Vec::new();
+    account.try_serialize(&mut data).unwrap();
+
+    raw_account(data, T::owner())
+}
+
+/// Returns a rent exempt account holding the data.
+pub fn raw_account(data: Vec<u8>, owner: Pubkey) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+/// Returns the global state used by the tests.
+pub fn global_state(owner: &Pubkey, fee_wallet: &Pubkey) -> GlobalState {
+    GlobalState {
+        owner: *owner,
+        fee_cut_bps: Bps::new(FEE_CUT_BPS).unwrap(),
+        fee_wallet: *fee_wallet,
+        crank_tip_bps: Bps::new(CRANK_TIP_BPS).unwrap(),
+        min_resolution_window: MIN_RESOLUTION_WINDOW,
+        max_resolution_window: MAX_RESOLUTION_WINDOW,
+        ..GlobalState::default()
+    }
+}
+
+/// Returns an account of type `T` holding data in a layout used before
+/// versioning, which had no version byte or reserved space.
+pub fn unversioned<T: Discriminator + Owner>(data: &impl AnchorSerialize) -> Account {
+    let mut unversioned = T::discriminator().to_vec();
+    data.serialize(&mut unversioned).unwrap();
+
+    raw_account(unversioned, T::owner())
+}
+
+/// Adds the account of a JSON fixture from `accounts/escrow`, as loaded by
+/// the local validator, and returns its address.
+pub fn add_fixture(test: &mut ProgramTest, name: &str) -> Pubkey {
+    let path = format!(
+        "{}/../../accounts/escrow/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name,
+    );
+    let fixture: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+    let address = fixture["pubkey"].as_str().unwrap().parse().unwrap();
+    let account = &fixture["account"];
+    test.add_account_with_base64_data(
+        address,
+        account["lamports"].as_u64().unwrap(),
+        account["owner"].as_str().unwrap().parse().unwrap(),
+        account["data"][0].as_str().unwrap(),
+    );
+
+    address
+}
+
+/// Asserts that the transaction failed with the given custom program error.
+pub fn assert_error(result: Result<(), TransportError>, error: impl Into<u32>) {
+    match result {
+        Err(TransportError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) => assert_eq!(code, error.into()),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+/// A running escrow program with an initialized global state and token mint.
+///
+/// The payer of the test context creates every market.
+pub struct TestEnv {
+    pub ctx: ProgramTestContext,
+    pub owner: Keypair,
+    pub fee_wallet: Pubkey,
+    pub mint: Keypair,
+    slot: u64,
+    nonce: u64,
+}
+
+impl TestEnv {
+    pub async fn new() -> TestEnv {
+        TestEnv::start(program_test()).await
+    }
+
+    /// Starts the given harness, after adding the global state to it.
+    pub async fn start(test: ProgramTest) -> TestEnv {
+        let owner = Keypair::new();
+        let fee_wallet = Pubkey::new_unique();
+        let global_state = anchor_account(&global_state(&owner.pubkey(), &fee_wallet));
+
+        TestEnv::start_with(test, owner, fee_wallet, global_state).await
+    }
+
+    /// Starts the given harness, after adding the given global state account
+    /// to it.
+    pub async fn start_with(
+        mut test: ProgramTest,
+        owner: Keypair,
+        fee_wallet: Pubkey,
+        global_state: Account,
+    ) -> TestEnv {
+        test.add_account(pda::global_state(), global_state);
+
+        TestEnv::launch(test, owner, fee_wallet).await
+    }
+
+    /// Starts the given harness without a global state.
+    pub async fn start_uninitialized(test: ProgramTest) -> TestEnv {
+        TestEnv::launch(test, Keypair::new(), Pubkey::new_unique()).await
+    }
+
+    async fn launch(test: ProgramTest, owner: Keypair, fee_wallet: Pubkey) -> TestEnv {
+        let ctx = test.start_with_context().await;
+        native::install_stubs();
+
+        let mut env = TestEnv {
+            ctx,
+            owner,
+            fee_wallet,
+            mint: Keypair::new(),
+            // The working bank of a new context is at slot 1.
+            slot: 1,
+            nonce: 0,
+        };
+        env.set_time(START_TS).await;
+        env.create_mint().await;
+        env.create_token_account(&fee_wallet).await;
+
+        env
+    }
+
+    pub fn creator(&self) -> Pubkey {
+        self.ctx.payer.pubkey()
+    }
+
+    /// Warps to a later slot, and overrides the clock to the timestamp.
+    pub async fn set_time(&mut self, unix_timestamp: u64) {
+        self.slot += 2;
+        self.ctx.warp_to_slot(self.slot).unwrap();
+
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp as i64;
+        self.ctx.set_sysvar(&clock);
+    }
+
+    /// Processes the instructions in a transaction paid by the context payer.
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransportError> {
+        // Make every transaction unique, so that retrying an instruction
+        // within the same slot is not rejected as a duplicate.
+        self.nonce += 1;
+        let mut instructions = instructions.to_vec();
+        instructions.push(ComputeBudgetInstruction::request_units(
+            COMPUTE_UNITS + self.nonce as u32,
+            0,
+        ));
+
+        let blockhash = self.ctx.banks_client.get_latest_blockhash().await?;
+        let mut all_signers = vec![&self.ctx.payer];
+        all_signers.extend(signers);
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&self.ctx.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn account(&mut self, key: &Pubkey) -> Option<Account> {
+        self.ctx.banks_client.get_account(*key).await.unwrap()
+    }
+
+    /// Overwrites an existing Anchor account with the given one, keeping its
+    /// lamports.
+    pub async fn write<T: AccountSerialize>(&mut self, key: &Pubkey, account: &T) {
+        let mut info = self.account(key).await.unwrap();
+        info.data.clear();
+        account.try_serialize(&mut info.data).unwrap();
+        self.ctx.set_account(key, &info.into());
+    }
+
+    pub async fn decode<T: AccountDeserialize>(&mut self, key: &Pubkey) -> T {
+        let account = self.account(key).await.unwrap();
+        hh_client::accounts::decode(&account.data).unwrap()
+    }
+
+    pub async fn market(&mut self, market: &Pubkey) -> Market {
+        self.decode(market).await
+    }
+
+    pub async fn user_position(&mut self, user: &Pubkey, market: &Pubkey) -> UserPosition {
+        self.decode(&pda::user_position(user, market)).await
+    }
+
+    //////////////////////////////////////////////////////////////////////////
+
+    async fn create_mint(&mut self) {
+        let mint = Keypair::new();
+        let payer = self.ctx.payer.pubkey();
+        let instructions = [
+            system_instruction::create_account(
+                &payer,
+                &mint.pubkey(),
+                Rent::default().minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &payer,
+                None,
+                0,
+            )
+            .unwrap(),
+        ];
+        self.process(&instructions, &[&mint]).await.unwrap();
+
+        self.mint = mint;
+    }
+
+    /// Creates the associated token account of the owner.
+    pub async fn create_token_account(&mut self, owner: &Pubkey) -> Pubkey {
+        let ix =
+            create_associated_token_account(&self.ctx.payer.pubkey(), owner, &self.mint.pubkey());
+        self.process(&[ix], &[]).await.unwrap();
+
+        get_associated_token_address(owner, &self.mint.pubkey())
+    }
+
+    pub async fn mint_to(&mut self, account: &Pubkey, amount: u64) {
+        let ix = spl_token::instruction::mint_to(
+            &spl_token::ID,
+            &self.mint.pubkey(),
+            account,
+            &self.ctx.payer.pubkey(),
+            &[],
+            amount,
+        )
+        .unwrap();
+        self.process(&[ix], &[]).await.unwrap();
+    }
+
+    pub async fn balance(&mut self, account: &Pubkey) -> u64 {
+        let account = self.account(account).await.unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    /// Creates a user holding `amount` tokens in their associated token
+    /// account.
+    pub async fn user(&mut self, amount: u64) -> (Keypair, Pubkey) {
+        let user = Keypair::new();
+        let token_account = self.create_token_account(&user.pubkey()).await;
+        self.mint_to(&token_account, amount).await;
+
+        (user, token_account)
+    }
+
+    //////////////////////////////////////////////////////////////////////////
+
+    /// Returns the parameters of a market closing in an hour and expiring in
+    /// two hours.
+    pub fn market_params(&self, resolver: &Pubkey) -> InitializeMarketParams {
+        InitializeMarketParams {
+            close_ts: START_TS + 3_600,
+            expiry_ts: START_TS + 7_200,
+            resolution_delay: RESOLUTION_DELAY,
+            yes_amount: YES_AMOUNT,
+            no_amount: NO_AMOUNT,
+            resolver: *resolver,
+            uri: "https://example.com/market.json".to_string(),
+            title: "Test market".to_string(),
+            category: *b"test\0\0\0\0\0\0\0\0\0\0\0\0",
+            content_hash: [0; 32],
+            yes_limits: DepositLimits::default(),
+            no_limits: DepositLimits::default(),
+            allowlist_root: None,
+            require_acknowledgement: false,
+            allow_early_resolution: false,
+            max_resolution_window: RESOLUTION_WINDOW,
+            resolver_bond: 0,
+        }
+    }
+
+    pub async fn try_init_market(
+        &mut self,
+        market: &Keypair,
+        params: InitializeMarketParams,
+    ) -> Result<(), TransportError> {
+        let ix = escrow::initialize_market(
+            &market.pubkey(),
+            &self.creator(),
+            &self.mint.pubkey(),
+            RegistryPages::default(),
+            params,
+        );
+        self.process(&[ix], &[market]).await
+    }
+
+    /// Initializes a market with the given parameters.
+    pub async fn init_market(&mut self, params: InitializeMarketParams) -> Pubkey {
+        let market = Keypair::new();
+        self.try_init_market(&market, params).await.unwrap();
+
+        market.pubkey()
+    }
+
+    pub async fn init_user_position(&mut self, user: &Keypair, market: &Pubkey) {
+        let ix = escrow::initialize_user_position(&user.pubkey(), &self.creator(), market);
+        self.process(&[ix], &[user]).await.unwrap();
+    }
+
+    pub async fn try_deposit(
+        &mut self,
+        user: &Keypair,
+        token_account: &Pubkey,
+        market: &Pubkey,
+        (yes_amount, no_amount): (u64, u64),
+        proof: Vec<[u8; 32]>,
+    ) -> Result<(), TransportError> {
+        let ix = escrow::deposit(
+            &user.pubkey(),
+            market,
+            token_account,
+            DepositParams {
+                yes_amount,
+                no_amount,
+                allow_partial: false,
+                proof,
+                referrer: None,
+            },
+        );
+        self.process(&[ix], &[user]).await
+    }
+
+    /// Initializes the position of the user, and deposits into the market.
+    pub async fn deposit(
+        &mut self,
+        user: &Keypair,
+        token_account: &Pubkey,
+        market: &Pubkey,
+        amounts: (u64, u64),
+    ) {
+        self.init_user_position(user, market).await;
+        self.try_deposit(user, token_account, market, amounts, vec![])
+            .await
+            .unwrap();
+    }
+
+    pub async fn update_state(
+        &mut self,
+        market: &Pubkey,
+        resolver: &Keypair,
+        outcome: Outcome,
+    ) -> Result<(), TransportError> {
+        let ix = escrow::update_state(market, &resolver.pubkey(), UpdateStateParams { outcome });
+        self.process(&[ix], &[resolver]).await
+    }
+}
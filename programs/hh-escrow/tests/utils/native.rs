@@ -0,0 +1,307 @@
+//! Native execution of programs in `solana-program-test` with the account
+//! resizing the BPF runtime supports.
+//!
+//! Natively, `solana-program-test` 1.9 hands programs account data that can't
+//! grow, and asserts that no CPI changes the size of an account. Programs are
+//! instead run on copies of the account data laid out as in the BPF input,
+//! with the length before the data and room to grow after it, and CPIs are
+//! invoked by stubs that resize the accounts of the caller.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Once;
+
+use solana_program_runtime::invoke_context::InvokeContext;
+use solana_program_runtime::stable_log;
+use solana_program_runtime::timings::ExecuteTimings;
+use solana_sdk::account::{ReadableAccount, WritableAccount};
+use solana_sdk::account_info::AccountInfo;
+use solana_sdk::entrypoint::{ProcessInstruction, ProgramResult, MAX_PERMITTED_DATA_INCREASE};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::{Message, SanitizedMessage};
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::program_stubs::{self, SyscallStubs};
+use solana_sdk::pubkey::Pubkey;
+
+thread_local! {
+    static INVOKE_CONTEXT: RefCell<Option<usize>> = const { RefCell::new(None) };
+}
+
+/// Records the invoke context of the running instruction, for the stubs.
+pub fn set_invoke_context(invoke_context: &mut InvokeContext) {
+    INVOKE_CONTEXT.with(|cell| cell.replace(Some(invoke_context as *mut _ as usize)));
+}
+
+fn invoke_context<'a, 'b>() -> &'a mut InvokeContext<'b> {
+    let ptr = INVOKE_CONTEXT.with(|cell| cell.borrow().expect("invoke context not set"));
+    // SAFETY: The context outlives the instruction that recorded it, and
+    // stubs are only called while that instruction runs on this thread.
+    unsafe { &mut *(ptr as *mut InvokeContext) }
+}
+
+/// Converts a program entrypoint into a native processor that supports
+/// account resizing, for use with [ProgramTest::add_program].
+///
+/// [ProgramTest::add_program]: solana_program_test::ProgramTest::add_program
+#[macro_export]
+macro_rules! native {
+    ($entry:path) => {{
+        fn process(
+            program_id: &solana_sdk::pubkey::Pubkey,
+            accounts: &[solana_sdk::account_info::AccountInfo],
+            data: &[u8],
+        ) -> solana_sdk::entrypoint::ProgramResult {
+            $crate::utils::native::process(program_id, accounts, data, $entry)
+        }
+
+        Some(
+            |first_instruction_account: usize,
+             input: &[u8],
+             invoke_context: &mut solana_program_test::InvokeContext| {
+                $crate::utils::native::set_invoke_context(invoke_context);
+                solana_program_test::builtin_process_instruction(
+                    process,
+                    first_instruction_account,
+                    input,
+                    invoke_context,
+                )
+            },
+        )
+    }};
+}
+
+/// Runs the entrypoint on padded copies of the account data, and writes the
+/// data back to the accounts, at its new size.
+pub fn process(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+    entry: ProcessInstruction,
+) -> ProgramResult {
+    // Accounts passed more than once share their data. The buffers are leaked
+    // so that they outlive the accounts, which may keep them once resized.
+    let mut padded_data: Vec<(Pubkey, Rc<RefCell<&mut [u8]>>)> = Vec::new();
+    for account in accounts {
+        if padded_data.iter().any(|(key, _)| key == account.key) {
+            continue;
+        }
+
+        let data = account.try_borrow_data()?;
+        let buffer =
+            Box::leak(vec![0; 8 + data.len() + MAX_PERMITTED_DATA_INCREASE].into_boxed_slice());
+        buffer[..8].copy_from_slice(&(data.len() as u64).to_le_bytes());
+        buffer[8..8 + data.len()].copy_from_slice(&data);
+        padded_data.push((
+            *account.key,
+            Rc::new(RefCell::new(&mut buffer[8..8 + data.len()])),
+        ));
+    }
+
+    let data_of = |key: &Pubkey| {
+        padded_data
+            .iter()
+            .find(|(padded_key, _)| padded_key == key)
+            .map(|(_, data)| data)
+            .unwrap()
+    };
+
+    let padded_accounts: Vec<AccountInfo> = accounts
+        .iter()
+        .map(|account| AccountInfo {
+            data: data_of(account.key).clone(),
+            ..account.clone()
+        })
+        .collect();
+    entry(program_id, &padded_accounts, data)?;
+    drop(padded_accounts);
+
+    for (key, data) in &padded_data {
+        let data = data.borrow();
+        let account = accounts.iter().find(|account| account.key == key).unwrap();
+        if account.data_len() == data.len() {
+            account.try_borrow_mut_data()?.copy_from_slice(&data);
+        } else {
+            *account.data.borrow_mut() = Box::leak(data.to_vec().into_boxed_slice());
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the syscall stubs of `solana-program-test`, once it has set them,
+/// with stubs that resize accounts after a CPI.
+pub fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let inner = program_stubs::set_syscall_stubs(Box::new(Unset));
+        program_stubs::set_syscall_stubs(Box::new(NativeStubs { inner }));
+    });
+}
+
+/// Stands in for the stubs while they are swapped.
+struct Unset;
+
+impl SyscallStubs for Unset {}
+
+struct NativeStubs {
+    inner: Box<dyn SyscallStubs>,
+}
+
+impl SyscallStubs for NativeStubs {
+    fn sol_log(&self, message: &str) {
+        self.inner.sol_log(message);
+    }
+
+    fn sol_log_compute_units(&self) {
+        self.inner.sol_log_compute_units();
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let invoke_context = invoke_context();
+        let log_collector = invoke_context.get_log_collector();
+
+        let caller = *invoke_context.get_caller().unwrap();
+        let message = Message::new(std::slice::from_ref(instruction), None);
+        let program_id = instruction.program_id;
+        // As in the BPF runtime, the changes of the caller are verified with
+        // its own privileges, which the callee may narrow.
+        let caller_privileges: Vec<bool> = message
+            .account_keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                account_infos
+                    .iter()
+                    .find(|account_info| account_info.key == key)
+                    .map_or(message.is_writable(i), |account_info| {
+                        account_info.is_writable
+                    })
+            })
+            .collect();
+
+        stable_log::program_invoke(
+            &log_collector,
+            &program_id,
+            invoke_context.get_stack_height(),
+        );
+
+        // Write the state of the caller's accounts to the callee's. As in the
+        // BPF runtime, the callee program needn't be passed.
+        let mut account_indices = Vec::with_capacity(message.account_keys.len());
+        let mut accounts = Vec::with_capacity(message.account_keys.len());
+        for (i, key) in message.account_keys.iter().enumerate() {
+            let (index, account) = invoke_context
+                .get_account(key)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            account_indices.push(index);
+
+            let account_info = account_infos
+                .iter()
+                .find(|account_info| account_info.key == key);
+            let account_info = match account_info {
+                Some(account_info) => account_info,
+                None if *key == program_id => continue,
+                None => return Err(ProgramError::NotEnoughAccountKeys),
+            };
+            {
+                let mut account = account.borrow_mut();
+                account.copy_into_owner_from_slice(account_info.owner.as_ref());
+                account.set_data_from_slice(&account_info.try_borrow_data()?);
+                account.set_lamports(account_info.lamports());
+                account.set_executable(account_info.executable);
+                account.set_rent_epoch(account_info.rent_epoch);
+            }
+
+            if message.is_writable(i) {
+                accounts.push((account, account_info));
+            }
+        }
+        let (program_index, _) = invoke_context
+            .get_account(&program_id)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+        // Only the signers of the caller and its PDAs may sign.
+        for meta in instruction.accounts.iter().filter(|meta| meta.is_signer) {
+            let account_info = account_infos
+                .iter()
+                .find(|account_info| *account_info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            let signed = account_info.is_signer
+                || signers_seeds.iter().any(|seeds| {
+                    Pubkey::create_program_address(seeds, &caller).as_ref() == Ok(&meta.pubkey)
+                });
+            if !signed {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+        }
+
+        invoke_context.record_instruction(invoke_context.get_stack_height(), instruction.clone());
+
+        let message = SanitizedMessage::Legacy(message);
+        invoke_context
+            .process_instruction(
+                &message,
+                &message.instructions()[0],
+                &[program_index],
+                &account_indices,
+                &caller_privileges,
+                &mut ExecuteTimings::default(),
+            )
+            .result
+            .map_err(|err| {
+                ProgramError::try_from(err).unwrap_or(ProgramError::InvalidAccountData)
+            })?;
+
+        // Write the state of the callee's writable accounts back to the
+        // caller's, resizing and reassigning them as needed.
+        for (account, account_info) in &accounts {
+            let account = account.borrow();
+            **account_info.try_borrow_mut_lamports()? = account.lamports();
+            if account_info.owner != account.owner() {
+                account_info.assign(account.owner());
+            }
+            if account_info.data_len() != account.data().len() {
+                account_info.realloc(account.data().len(), false)?;
+            }
+            account_info
+                .try_borrow_mut_data()?
+                .copy_from_slice(account.data());
+        }
+
+        stable_log::program_success(&log_collector, &program_id);
+        Ok(())
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_clock_sysvar(var_addr)
+    }
+
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_epoch_schedule_sysvar(var_addr)
+    }
+
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_fees_sysvar(var_addr)
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner.sol_get_rent_sysvar(var_addr)
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.inner.sol_get_return_data()
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.inner.sol_set_return_data(data);
+    }
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        self.inner.sol_log_data(fields);
+    }
+}
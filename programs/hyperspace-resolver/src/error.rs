@@ -16,4 +16,8 @@ pub enum ErrorCode {
     TimestampNotPassed,
     #[msg("Authority has already acknowledged the resolver")]
     AlreadyAcknowledged,
+    #[msg("Spawn authority does not match the escrow spawn authority address")]
+    IncorrectSpawnAuthority,
+    #[msg("Resolver account must be funded to be created without a payer")]
+    ResolverNotFunded,
 }
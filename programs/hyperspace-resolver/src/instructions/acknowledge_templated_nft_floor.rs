@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+use common::traits::KeyRef;
+use hh_escrow::program::HhEscrow;
+use hh_escrow::state::Market;
+
+use crate::error::ErrorCode;
+use crate::state::{NftFloor, NFT_FLOOR_SEED};
+
+/// Acknowledges the NFT floor resolver of a market spawned from an escrow
+/// market template, and acknowledges the market in the escrow program.
+///
+/// If the market requires a resolver bond, the bond accounts expected by the
+/// escrow program's `resolver_acknowledge` are passed in the remaining
+/// accounts, and forwarded to it.
+#[derive(Accounts)]
+pub struct AcknowledgeTemplatedNftFloor<'info> {
+    /// The metadata account for the resolver.
+    #[account(mut, seeds = [NFT_FLOOR_SEED, market.key_ref().as_ref()], bump)]
+    pub resolver: Account<'info, NftFloor>,
+    /// The market to acknowledge.
+    #[account(mut, address = resolver.market @ ErrorCode::IncorrectMarket)]
+    pub market: Account<'info, Market>,
+    /// The resolver authority.
+    #[account(address = resolver.authority @ ErrorCode::IncorrectAuthority)]
+    pub authority: Signer<'info>,
+
+    /// The escrow program.
+    pub escrow_program: Program<'info, HhEscrow>,
+}
+
+impl<'info> AcknowledgeTemplatedNftFloor<'info> {
+    /// Acknowledge the market, forwarding the bond accounts.
+    fn resolver_acknowledge(
+        &self,
+        signer_seeds: &[&[&[u8]]],
+        bond_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let accounts = hh_escrow::cpi::accounts::ResolverAcknowledge {
+            market: self.market.to_account_info(),
+            resolver: self.resolver.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            self.escrow_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(bond_accounts.to_vec());
+
+        hh_escrow::cpi::resolver_acknowledge(ctx)
+    }
+}
+
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AcknowledgeTemplatedNftFloor<'info>>,
+) -> Result<()> {
+    if ctx.accounts.resolver.acknowledged {
+        return Err(error!(ErrorCode::AlreadyAcknowledged));
+    }
+
+    ctx.accounts.resolver.acknowledged = true;
+
+    let bump = get_bump!(ctx, resolver)?;
+    let signer_seeds = &[
+        NFT_FLOOR_SEED,
+        ctx.accounts.market.key_ref().as_ref(),
+        &[bump],
+    ];
+
+    // Acknowledge the market.
+    ctx.accounts
+        .resolver_acknowledge(&[signer_seeds], ctx.remaining_accounts)?;
+
+    Ok(())
+}
@@ -20,6 +20,12 @@ pub struct InitializeNftFloorParams {
     pub project_id: String,
 }
 
+/// Initializes the NFT floor resolver of a market and acknowledges the
+/// market.
+///
+/// If the market requires a resolver bond, the bond accounts expected by the
+/// escrow program's `resolver_acknowledge` are passed in the remaining
+/// accounts, and forwarded to it.
 #[derive(Accounts)]
 pub struct InitializeNftFloor<'info> {
     /// The metadata account for the resolver.
@@ -41,88 +47,99 @@ pub struct InitializeNftFloor<'info> {
 }
 
 impl<'info> InitializeNftFloor<'info> {
-    /// Initializes the resolver account.
-    ///
-    /// This has to be done manually because the resolver account size varies
-    /// depending on the project ID.
-    fn init_resolver(
+    /// Acknowledge the market, forwarding the bond accounts.
+    fn resolver_acknowledge(
         &self,
         signer_seeds: &[&[&[u8]]],
-        project_id: &str,
-    ) -> Result<Account<'info, NftFloor>> {
-        let resolver = &*self.resolver;
-        let payer = &*self.creator;
-
-        let space = 8 + NftFloor::account_size(project_id);
+        bond_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let accounts = hh_escrow::cpi::accounts::ResolverAcknowledge {
+            market: self.market.to_account_info(),
+            resolver: self.resolver.to_account_info(),
+        };
+        let ctx = CpiContext::new_with_signer(
+            self.escrow_program.to_account_info(),
+            accounts,
+            signer_seeds,
+        )
+        .with_remaining_accounts(bond_accounts.to_vec());
 
-        let required_lamports = Rent::get()?.minimum_balance(space);
-        let lamports = resolver.lamports();
+        hh_escrow::cpi::resolver_acknowledge(ctx)
+    }
+}
 
-        if lamports == 0 {
-            // Create a new account.
-            solana_program::program::invoke_signed(
-                &solana_program::system_instruction::create_account(
+/// Initializes the resolver account.
+///
+/// This has to be done manually because the resolver account size varies
+/// depending on the project ID. Without a payer, the resolver account must
+/// already hold enough lamports to be rent exempt.
+pub(crate) fn init_resolver<'info>(
+    resolver: &AccountInfo<'info>,
+    payer: Option<&AccountInfo<'info>>,
+    signer_seeds: &[&[&[u8]]],
+    project_id: &str,
+) -> Result<Account<'info, NftFloor>> {
+    let space = 8 + NftFloor::account_size(project_id);
+
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let lamports = resolver.lamports();
+
+    if lamports == 0 {
+        let payer = payer.ok_or_else(|| error!(ErrorCode::ResolverNotFunded))?;
+
+        // Create a new account.
+        solana_program::program::invoke_signed(
+            &solana_program::system_instruction::create_account(
+                payer.key,
+                resolver.key,
+                required_lamports,
+                space as u64,
+                &crate::ID,
+            ),
+            &[payer.clone(), resolver.clone()],
+            signer_seeds,
+        )?;
+    } else {
+        let required_lamports = required_lamports.max(1).saturating_sub(lamports);
+        if required_lamports > 0 {
+            let payer = payer.ok_or_else(|| error!(ErrorCode::ResolverNotFunded))?;
+
+            // Top up lamports.
+            solana_program::program::invoke(
+                &solana_program::system_instruction::transfer(
                     payer.key,
                     resolver.key,
                     required_lamports,
-                    space as u64,
-                    &crate::ID,
                 ),
-                &[payer.to_account_info(), resolver.to_account_info()],
-                signer_seeds,
-            )?;
-        } else {
-            let required_lamports = required_lamports.max(1).saturating_sub(lamports);
-            if required_lamports > 0 {
-                // Top up lamports.
-                solana_program::program::invoke(
-                    &solana_program::system_instruction::transfer(
-                        payer.key,
-                        resolver.key,
-                        required_lamports,
-                    ),
-                    &[payer.to_account_info(), resolver.to_account_info()],
-                )?;
-            }
-
-            // Avoid cloning resolver account info again.
-            let resolver_info = slice::from_ref(resolver);
-
-            // Allocate space for the account.
-            solana_program::program::invoke_signed(
-                &solana_program::system_instruction::allocate(resolver.key, space as u64),
-                resolver_info,
-                signer_seeds,
-            )?;
-
-            // Assign this program as the account owner.
-            solana_program::program::invoke_signed(
-                &solana_program::system_instruction::assign(resolver.key, &crate::ID),
-                resolver_info,
-                signer_seeds,
+                &[payer.clone(), resolver.clone()],
             )?;
         }
 
-        Account::try_from_unchecked(resolver).map_err(|err| err.with_account_name("resolver"))
-    }
+        // Avoid cloning resolver account info again.
+        let resolver_info = slice::from_ref(resolver);
 
-    /// Acknowledge the market.
-    fn resolver_acknowledge(&self, signer_seeds: &[&[&[u8]]]) -> Result<()> {
-        let accounts = hh_escrow::cpi::accounts::ResolverAcknowledge {
-            market: self.market.to_account_info(),
-            resolver: self.resolver.to_account_info(),
-        };
-        let ctx = CpiContext::new_with_signer(
-            self.escrow_program.to_account_info(),
-            accounts,
+        // Allocate space for the account.
+        solana_program::program::invoke_signed(
+            &solana_program::system_instruction::allocate(resolver.key, space as u64),
+            resolver_info,
             signer_seeds,
-        );
+        )?;
 
-        hh_escrow::cpi::resolver_acknowledge(ctx)
+        // Assign this program as the account owner.
+        solana_program::program::invoke_signed(
+            &solana_program::system_instruction::assign(resolver.key, &crate::ID),
+            resolver_info,
+            signer_seeds,
+        )?;
     }
+
+    Account::try_from_unchecked(resolver).map_err(|err| err.with_account_name("resolver"))
 }
 
-pub fn handler(ctx: Context<InitializeNftFloor>, params: InitializeNftFloorParams) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, InitializeNftFloor<'info>>,
+    params: InitializeNftFloorParams,
+) -> Result<()> {
     let InitializeNftFloorParams {
         authority,
         floor_price,
@@ -142,7 +159,12 @@ pub fn handler(ctx: Context<InitializeNftFloor>, params: InitializeNftFloorParam
     ];
 
     // Create or allocate space for the resolver account.
-    let mut resolver = ctx.accounts.init_resolver(&[signer_seeds], &project_id)?;
+    let mut resolver = init_resolver(
+        &ctx.accounts.resolver,
+        Some(&*ctx.accounts.creator),
+        &[signer_seeds],
+        &project_id,
+    )?;
 
     resolver.authority = authority;
     resolver.market = ctx.accounts.market.key();
@@ -153,7 +175,8 @@ pub fn handler(ctx: Context<InitializeNftFloor>, params: InitializeNftFloorParam
     resolver.exit(&crate::ID)?;
 
     // Acknowledge the market.
-    ctx.accounts.resolver_acknowledge(&[signer_seeds])?;
+    ctx.accounts
+        .resolver_acknowledge(&[signer_seeds], ctx.remaining_accounts)?;
 
     Ok(())
 }
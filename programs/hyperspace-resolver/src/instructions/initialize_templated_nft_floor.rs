@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+
+use common::sys;
+use common::traits::KeyRef;
+use hh_escrow::state::Market;
+
+use crate::error::ErrorCode;
+use crate::instructions::{init_resolver, InitializeNftFloorParams};
+use crate::state::NFT_FLOOR_SEED;
+
+/// Initializes the resolver of a market spawned from an escrow market
+/// template, invoked by the escrow program.
+///
+/// The escrow program forwards no payer, so the resolver account must be
+/// funded before the market is spawned. The escrow program cannot be called
+/// back to acknowledge the market, which the resolver authority does with
+/// `acknowledge_templated_nft_floor`.
+#[derive(Accounts)]
+pub struct InitializeTemplatedNftFloor<'info> {
+    /// The metadata account for the resolver.
+    ///
+    /// CHECK: This account will be initialized in the handler.
+    #[account(mut, seeds = [NFT_FLOOR_SEED, market.key_ref().as_ref()], bump)]
+    pub resolver: UncheckedAccount<'info>,
+    /// The market to resolve.
+    #[account(constraint = market.resolver == *resolver.key_ref() @ ErrorCode::IncorrectResolver)]
+    pub market: Account<'info, Market>,
+    /// The escrow spawn authority of the market, proving that the escrow
+    /// program is spawning the market.
+    pub spawn_authority: Signer<'info>,
+
+    /// The Solana system program.
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeTemplatedNftFloor<'info> {
+    /// Checks that the spawn authority is the escrow PDA of the market.
+    fn check_spawn_authority(&self) -> Result<()> {
+        let (spawn_authority, _) = Pubkey::find_program_address(
+            &[b"spawn", self.market.key_ref().as_ref()],
+            &hh_escrow::ID,
+        );
+        if spawn_authority != *self.spawn_authority.key_ref() {
+            return Err(error!(ErrorCode::IncorrectSpawnAuthority));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn handler(
+    ctx: Context<InitializeTemplatedNftFloor>,
+    params: InitializeNftFloorParams,
+) -> Result<()> {
+    let InitializeNftFloorParams {
+        authority,
+        floor_price,
+        project_id,
+    } = params;
+
+    ctx.accounts.check_spawn_authority()?;
+
+    // Check that the timestamp has not already passed.
+    if ctx.accounts.market.expiry_ts <= sys::timestamp()? {
+        return Err(error!(ErrorCode::TimestampPassed));
+    }
+
+    let bump = get_bump!(ctx, resolver)?;
+    let signer_seeds = &[
+        NFT_FLOOR_SEED,
+        ctx.accounts.market.key_ref().as_ref(),
+        &[bump],
+    ];
+
+    // Create or allocate space for the resolver account.
+    let mut resolver = init_resolver(&ctx.accounts.resolver, None, &[signer_seeds], &project_id)?;
+
+    resolver.authority = authority;
+    resolver.market = ctx.accounts.market.key();
+    resolver.floor_price = floor_price;
+    resolver.project_id = project_id;
+
+    // Write the resolver account.
+    resolver.exit(&crate::ID)?;
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
 pub mod acknowledge_nft_floor;
+pub mod acknowledge_templated_nft_floor;
 pub mod initialize_nft_floor;
+pub mod initialize_templated_nft_floor;
 pub mod resolve_nft_floor;
 
 pub use self::acknowledge_nft_floor::*;
+pub use self::acknowledge_templated_nft_floor::*;
 pub use self::initialize_nft_floor::*;
+pub use self::initialize_templated_nft_floor::*;
 pub use self::resolve_nft_floor::*;
@@ -17,8 +17,8 @@ declare_id!("CyX3buQXyW939M5LReVhPGwcTDoPWMAdArehA2aqVRvP");
 pub mod hyperspace_resolver {
     use super::*;
 
-    pub fn initialize_nft_floor(
-        ctx: Context<InitializeNftFloor>,
+    pub fn initialize_nft_floor<'info>(
+        ctx: Context<'_, '_, '_, 'info, InitializeNftFloor<'info>>,
         params: InitializeNftFloorParams,
     ) -> Result<()> {
         instructions::initialize_nft_floor::handler(ctx, params)
@@ -34,4 +34,17 @@ pub mod hyperspace_resolver {
     pub fn acknowledge_nft_floor(ctx: Context<AcknowledgeNftFloor>) -> Result<()> {
         instructions::acknowledge_nft_floor::handler(ctx)
     }
+
+    pub fn initialize_templated_nft_floor(
+        ctx: Context<InitializeTemplatedNftFloor>,
+        params: InitializeNftFloorParams,
+    ) -> Result<()> {
+        instructions::initialize_templated_nft_floor::handler(ctx, params)
+    }
+
+    pub fn acknowledge_templated_nft_floor<'info>(
+        ctx: Context<'_, '_, '_, 'info, AcknowledgeTemplatedNftFloor<'info>>,
+    ) -> Result<()> {
+        instructions::acknowledge_templated_nft_floor::handler(ctx)
+    }
 }
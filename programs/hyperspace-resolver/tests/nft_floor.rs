@@ -0,0 +1,364 @@
+#[path = "../../hh-escrow/tests/utils/mod.rs"]
+mod utils;
+
+use solana_program_test::tokio;
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+use hh_client::escrow::{self, RegistryPages};
+use hh_client::{pda, resolver};
+use hh_escrow::instructions::{InitializeMarketTemplateParams, SetGlobalStateParams};
+use hh_escrow::state::{DepositLimits, Outcome};
+use hyperspace_resolver::error::ErrorCode;
+use hyperspace_resolver::instructions::{InitializeNftFloorParams, ResolveNftFloorParams};
+use hyperspace_resolver::state::NftFloor;
+
+use crate::utils::{
+    assert_error, TestEnv, FEE_CUT_BPS, NO_AMOUNT, RESOLUTION_DELAY, RESOLUTION_WINDOW, START_TS,
+    YES_AMOUNT,
+};
+
+const FLOOR_PRICE: u64 = 100;
+
+async fn start() -> TestEnv {
+    let mut test = utils::program_test();
+    test.add_program(
+        "hyperspace_resolver",
+        hyperspace_resolver::ID,
+        crate::native!(hyperspace_resolver::entry),
+    );
+
+    TestEnv::start(test).await
+}
+
+fn nft_floor_params(authority: &Pubkey) -> InitializeNftFloorParams {
+    InitializeNftFloorParams {
+        authority: *authority,
+        floor_price: FLOOR_PRICE,
+        project_id: "degods".to_string(),
+    }
+}
+
+/// Initializes a market resolved by its NFT floor resolver.
+async fn nft_floor_market(env: &mut TestEnv) -> Pubkey {
+    let market = Keypair::new();
+    let params = env.market_params(&pda::nft_floor(&market.pubkey()));
+    env.try_init_market(&market, params).await.unwrap();
+
+    market.pubkey()
+}
+
+// Test that the market creator initializes the resolver, acknowledging the
+// market.
+#[tokio::test]
+async fn test_initialize_nft_floor() {
+    let mut env = start().await;
+    let market = nft_floor_market(&mut env).await;
+    let authority = Keypair::new();
+
+    let other = Keypair::new();
+    let ix = resolver::initialize_nft_floor(
+        &market,
+        &other.pubkey(),
+        nft_floor_params(&authority.pubkey()),
+    );
+    assert_error(
+        env.process(&[ix], &[&other]).await,
+        ErrorCode::IncorrectCreator,
+    );
+
+    let ix = resolver::initialize_nft_floor(
+        &market,
+        &env.creator(),
+        nft_floor_params(&authority.pubkey()),
+    );
+    env.process(&[ix], &[]).await.unwrap();
+
+    let nft_floor: NftFloor = env.decode(&pda::nft_floor(&market)).await;
+    assert_eq!(nft_floor.authority, authority.pubkey());
+    assert_eq!(nft_floor.market, market);
+    assert_eq!(nft_floor.floor_price, FLOOR_PRICE);
+    assert!(!nft_floor.acknowledged);
+    assert!(env.market(&market).await.acknowledged);
+
+    // A resolver can't be created for a market that has expired.
+    let market = nft_floor_market(&mut env).await;
+    env.set_time(START_TS + 7_200).await;
+    let ix = resolver::initialize_nft_floor(
+        &market,
+        &env.creator(),
+        nft_floor_params(&authority.pubkey()),
+    );
+    assert_error(env.process(&[ix], &[]).await, ErrorCode::TimestampPassed);
+}
+
+// Test that the resolver bond accounts are forwarded to the escrow program
+// when the market requires a resolver bond.
+#[tokio::test]
+async fn test_initialize_nft_floor_resolver_bond() {
+    let mut env = start().await;
+    let market = Keypair::new();
+    let mut params = env.market_params(&pda::nft_floor(&market.pubkey()));
+    params.require_acknowledgement = true;
+    params.resolver_bond = 100;
+    env.try_init_market(&market, params).await.unwrap();
+    let market = market.pubkey();
+    let authority = Keypair::new();
+
+    let ix = resolver::initialize_nft_floor(
+        &market,
+        &env.creator(),
+        nft_floor_params(&authority.pubkey()),
+    );
+    assert_error(
+        env.process(&[ix], &[]).await,
+        hh_escrow::error::ErrorCode::InvalidResolverBondAccounts,
+    );
+
+    let creator = env.creator();
+    let creator_account = env.create_token_account(&creator).await;
+    env.mint_to(&creator_account, 100).await;
+    let mut ix =
+        resolver::initialize_nft_floor(&market, &creator, nft_floor_params(&authority.pubkey()));
+    ix.accounts.extend(escrow::resolver_bond_accounts(
+        &market,
+        &creator,
+        &env.mint.pubkey(),
+    ));
+    env.process(&[ix], &[]).await.unwrap();
+
+    let info = env.market(&market).await;
+    assert!(info.acknowledged);
+    assert_eq!(info.resolver_bond_payer, env.creator());
+    assert_eq!(
+        env.balance(&pda::resolver_bond_token_account(&market))
+            .await,
+        100
+    );
+}
+
+// Test that only the resolver authority acknowledges the resolver, once.
+#[tokio::test]
+async fn test_acknowledge_nft_floor() {
+    let mut env = start().await;
+    let market = nft_floor_market(&mut env).await;
+    let authority = Keypair::new();
+    let ix = resolver::initialize_nft_floor(
+        &market,
+        &env.creator(),
+        nft_floor_params(&authority.pubkey()),
+    );
+    env.process(&[ix], &[]).await.unwrap();
+
+    let other = Keypair::new();
+    let ix = resolver::acknowledge_nft_floor(&market, &other.pubkey());
+    assert_error(
+        env.process(&[ix], &[&other]).await,
+        ErrorCode::IncorrectAuthority,
+    );
+
+    let ix = resolver::acknowledge_nft_floor(&market, &authority.pubkey());
+    env.process(&[ix], &[&authority]).await.unwrap();
+    let nft_floor: NftFloor = env.decode(&pda::nft_floor(&market)).await;
+    assert!(nft_floor.acknowledged);
+
+    let ix = resolver::acknowledge_nft_floor(&market, &authority.pubkey());
+    assert_error(
+        env.process(&[ix], &[&authority]).await,
+        ErrorCode::AlreadyAcknowledged,
+    );
+}
+
+// Test that the market is resolved from the floor price once it expires.
+#[tokio::test]
+async fn test_resolve_nft_floor() {
+    let mut env = start().await;
+    let market = nft_floor_market(&mut env).await;
+    let authority = Keypair::new();
+    let ix = resolver::initialize_nft_floor(
+        &market,
+        &env.creator(),
+        nft_floor_params(&authority.pubkey()),
+    );
+    env.process(&[ix], &[]).await.unwrap();
+
+    // Fill the market, which would otherwise be invalid once closed.
+    let (yes_user, yes_account) = env.user(YES_AMOUNT).await;
+    let (no_user, no_account) = env.user(NO_AMOUNT).await;
+    env.deposit(&yes_user, &yes_account, &market, (YES_AMOUNT, 0))
+        .await;
+    env.deposit(&no_user, &no_account, &market, (0, NO_AMOUNT))
+        .await;
+
+    let resolve = |current_floor_price| {
+        resolver::resolve_nft_floor(
+            &market,
+            &authority.pubkey(),
+            ResolveNftFloorParams {
+                current_floor_price,
+            },
+        )
+    };
+
+    assert_error(
+        env.process(&[resolve(Some(FLOOR_PRICE))], &[&authority])
+            .await,
+        ErrorCode::TimestampNotPassed,
+    );
+
+    env.set_time(START_TS + 7_200).await;
+    env.process(&[resolve(Some(FLOOR_PRICE - 1))], &[&authority])
+        .await
+        .unwrap();
+    assert_eq!(env.market(&market).await.outcome, Outcome::No);
+
+    env.process(&[resolve(Some(FLOOR_PRICE))], &[&authority])
+        .await
+        .unwrap();
+    assert_eq!(env.market(&market).await.outcome, Outcome::Yes);
+}
+
+// Test that the escrow program initializes the funded resolver of spawned
+// markets from the allowed resolver program, that nobody else can, and that
+// the resolver authority acknowledges the market, posting its bond.
+#[tokio::test]
+async fn test_initialize_templated_nft_floor() {
+    let mut env = start().await;
+    let creator = env.creator();
+    let mint = env.mint.pubkey();
+    let owner = Keypair::from_bytes(&env.owner.to_bytes()).unwrap();
+    let authority = Keypair::new();
+    let template = Keypair::new();
+
+    let template_params = || InitializeMarketTemplateParams {
+        close_ts: START_TS + 3_600,
+        expiry_ts: START_TS + 7_200,
+        cadence: 86_400,
+        resolution_delay: RESOLUTION_DELAY,
+        yes_amount: YES_AMOUNT,
+        no_amount: NO_AMOUNT,
+        resolver: Pubkey::default(),
+        resolver_program: Some(hyperspace_resolver::ID),
+        resolver_data: resolver::templated_nft_floor_data(nft_floor_params(&authority.pubkey())),
+        uri_prefix: "https://example.com/daily/".to_string(),
+        title: "Daily floor".to_string(),
+        category: *b"nft\0\0\0\0\0\0\0\0\0\0\0\0\0",
+        yes_limits: DepositLimits::default(),
+        no_limits: DepositLimits::default(),
+        require_acknowledgement: true,
+        allow_early_resolution: false,
+        max_resolution_window: RESOLUTION_WINDOW,
+        resolver_bond: 100,
+    };
+
+    let ix =
+        escrow::initialize_market_template(&template.pubkey(), &creator, &mint, template_params());
+    assert_error(
+        env.process(&[ix], &[&template]).await,
+        hh_escrow::error::ErrorCode::ResolverProgramNotAllowed,
+    );
+
+    let ix = escrow::set_global_state(
+        &owner.pubkey(),
+        SetGlobalStateParams {
+            new_owner: owner.pubkey(),
+            new_fee_cut_bps: FEE_CUT_BPS,
+            new_fee_wallet: env.fee_wallet,
+            new_crank_tip_bps: None,
+            new_referral_bps: None,
+            new_min_resolution_window: None,
+            new_max_resolution_window: None,
+            new_creation_bond_lamports: None,
+            new_arbiter: None,
+            new_dispute_bond_lamports: None,
+            new_template_resolver_program: Some(hyperspace_resolver::ID),
+        },
+    );
+    env.process(&[ix], &[&owner]).await.unwrap();
+    let ix =
+        escrow::initialize_market_template(&template.pubkey(), &creator, &mint, template_params());
+    env.process(&[ix], &[&template]).await.unwrap();
+
+    // The resolver is initialized without a payer, so it must be funded.
+    let market = pda::market(&template.pubkey(), 0);
+    let spawn = || {
+        escrow::spawn_market_from_template(
+            &template.pubkey(),
+            0,
+            &creator,
+            &mint,
+            &creator,
+            RegistryPages::default(),
+            Some((pda::nft_floor(&market), hyperspace_resolver::ID)),
+        )
+    };
+    assert_error(
+        env.process(&[spawn()], &[]).await,
+        ErrorCode::ResolverNotFunded,
+    );
+
+    // The resolver must be created by the resolver program.
+    let ix = escrow::spawn_market_from_template(
+        &template.pubkey(),
+        0,
+        &creator,
+        &mint,
+        &creator,
+        RegistryPages::default(),
+        Some((mint, hyperspace_resolver::ID)),
+    );
+    assert_error(
+        env.process(&[ix], &[]).await,
+        hh_escrow::error::ErrorCode::ResolverNotInitialized,
+    );
+
+    let fund = resolver::fund_templated_nft_floor(&market, &creator, "degods");
+    env.process(&[fund, spawn()], &[]).await.unwrap();
+
+    let info = env.market(&market).await;
+    assert_eq!(info.resolver, pda::nft_floor(&market));
+    assert_eq!(info.resolver_bond, 100);
+    assert!(!info.acknowledged);
+    let nft_floor: NftFloor = env.decode(&pda::nft_floor(&market)).await;
+    assert_eq!(nft_floor.authority, authority.pubkey());
+    assert_eq!(nft_floor.market, market);
+
+    // Only the resolver authority acknowledges the market, posting the bond.
+    let other = Keypair::new();
+    let ix = resolver::acknowledge_templated_nft_floor(&market, &other.pubkey());
+    assert_error(
+        env.process(&[ix], &[&other]).await,
+        ErrorCode::IncorrectAuthority,
+    );
+
+    let creator_account = env.create_token_account(&creator).await;
+    env.mint_to(&creator_account, 100).await;
+    let mut ix = resolver::acknowledge_templated_nft_floor(&market, &authority.pubkey());
+    ix.accounts
+        .extend(escrow::resolver_bond_accounts(&market, &creator, &mint));
+    env.process(&[ix], &[&authority]).await.unwrap();
+
+    let info = env.market(&market).await;
+    assert!(info.acknowledged);
+    assert_eq!(info.resolver_bond_payer, creator);
+    assert_eq!(
+        env.balance(&pda::resolver_bond_token_account(&market))
+            .await,
+        100
+    );
+    let nft_floor: NftFloor = env.decode(&pda::nft_floor(&market)).await;
+    assert!(nft_floor.acknowledged);
+
+    // Calling the resolver program directly with another signer as the spawn
+    // authority fails.
+    let market = nft_floor_market(&mut env).await;
+    let fake_authority = Keypair::new();
+    let mut ix =
+        resolver::initialize_templated_nft_floor(&market, nft_floor_params(&authority.pubkey()));
+    ix.accounts[2] = AccountMeta::new_readonly(fake_authority.pubkey(), true);
+    assert_error(
+        env.process(&[ix], &[&fake_authority]).await,
+        ErrorCode::IncorrectSpawnAuthority,
+    );
+}
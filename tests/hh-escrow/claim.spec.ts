@@ -22,6 +22,12 @@ import {
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = 1_000_000n;
@@ -31,7 +37,7 @@ describe("claim", () => {
   const market = Keypair.generate();
   const mint = Keypair.generate();
   const user = Keypair.generate();
-  const userTokenAccount = Keypair.generate();
+  const userTokenAccount = getAssociatedTokenAddress(mint, user);
   const resolver = Keypair.generate();
 
   const authority = getAuthorityAddress(market);
@@ -39,15 +45,15 @@ describe("claim", () => {
   const [noTokenAccount] = getNoTokenAccountAddress(market);
   const userPosition = getUserPositionAddress(user, market);
 
-  let feeWallet: PublicKey, feeAccount: PublicKey;
-
   //////////////////////////////////////////////////////////////////////////////
 
   const claim = () =>
     program.methods.claim().accounts({
       globalState: globalState.address,
-      feeAccount,
-      userTokenAccount: userTokenAccount.publicKey,
+      destination: user.publicKey,
+      userTokenAccount,
+      referrerTokenAccount: userTokenAccount,
+      tokenMint: mint.publicKey,
       yesTokenAccount,
       noTokenAccount,
       userPosition,
@@ -62,27 +68,19 @@ describe("claim", () => {
     // Ensure global state is initialized and matches expected state.
     await globalState.initialize();
 
-    feeWallet = await globalState.getFeeWallet();
-    feeAccount = getAssociatedTokenAddress(mint, feeWallet, true);
-
     await sendTx(
       [
         ...(await createInitMintInstructions({
           mint,
           mintAuthority: program.provider.wallet.publicKey,
         })),
-        ...(await createInitAccountInstructions({
-          account: userTokenAccount,
-          mint,
-          user,
-        })),
         createAssociatedTokenAccountInstruction({
-          account: feeAccount,
-          owner: feeWallet,
+          account: userTokenAccount,
+          owner: user,
           mint,
         }),
       ],
-      [mint, userTokenAccount],
+      [mint],
     );
 
     const closeTs = unixTimestamp() + 3600n;
@@ -95,7 +93,17 @@ describe("claim", () => {
       yesAmount: intoU64BN(YES_AMOUNT),
       noAmount: intoU64BN(NO_AMOUNT),
       resolver: resolver.publicKey,
-      uri: "0".repeat(200),
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
     };
 
     await sendTx(
@@ -103,8 +111,19 @@ describe("claim", () => {
         await program.methods
           .initializeMarket(params)
           .accounts({
+            globalState: globalState.address,
+            ...(await getRegistryAccounts(
+              program.provider.wallet.publicKey,
+              mint.publicKey,
+            )),
             market: market.publicKey,
             tokenMint: mint.publicKey,
+            mintPolicy: getMintPolicyAddress(mint.publicKey),
+            bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+            creatorTokenAccount: getAssociatedTokenAddress(
+              mint.publicKey,
+              program.provider.wallet.publicKey,
+            ),
             authority,
             yesTokenAccount,
             noTokenAccount,
@@ -128,74 +147,20 @@ describe("claim", () => {
   it("fails if the global state address is incorrect", async () => {
     expect.assertions(1);
 
-    // This is painful because anchor attempts to deserialize the account before
-    // checking the seeds constraint.
+    // The account at the wrong address is a global state in the unversioned
+    // layout, which anchor fails to deserialize before checking the seeds.
 
     const [wrongGlobalState] = PublicKey.findProgramAddressSync(
       [Buffer.from("global2")],
       program.programId,
     );
-    const [feeWallet] = PublicKey.findProgramAddressSync(
-      [Buffer.from("global2"), Buffer.from("authority")],
-      program.programId,
-    );
-
-    const feeAccount = getAssociatedTokenAddress(mint, feeWallet, true);
 
     await expect(
       claim()
-        .accounts({ globalState: wrongGlobalState, feeAccount })
-        .preInstructions([
-          createAssociatedTokenAccountInstruction({
-            account: feeAccount,
-            mint,
-            owner: feeWallet,
-          }),
-        ])
+        .accounts({ globalState: wrongGlobalState })
         .signers([user])
         .rpc(),
-    ).rejects.toThrowProgramError(LangErrorCode.ConstraintSeeds);
-  });
-
-  it("fails if the fee account is has the wrong owner", async () => {
-    expect.assertions(1);
-
-    const wrongFeeAccount = Keypair.generate();
-    const wrongFeeWallet = Keypair.generate();
-
-    const preIxs = await createInitAccountInstructions({
-      account: wrongFeeAccount,
-      mint,
-      user: wrongFeeWallet,
-    });
-
-    await expect(
-      claim()
-        .accounts({ feeAccount: wrongFeeAccount.publicKey })
-        .preInstructions(preIxs)
-        .signers([user, wrongFeeAccount])
-        .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.AccountNotOwnedByFeeWallet);
-  });
-
-  it("fails if the fee account is not the associated token account for the fee wallet and token mint", async () => {
-    expect.assertions(1);
-
-    const wrongFeeAccount = Keypair.generate();
-
-    const preIxs = await createInitAccountInstructions({
-      account: wrongFeeAccount,
-      mint,
-      user: feeWallet,
-    });
-
-    await expect(
-      claim()
-        .accounts({ feeAccount: wrongFeeAccount.publicKey })
-        .preInstructions(preIxs)
-        .signers([user, wrongFeeAccount])
-        .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.AssociatedTokenAccountRequired);
+    ).rejects.toThrowProgramError(LangErrorCode.AccountDidNotDeserialize);
   });
 
   it("fails if the user provides the yes token account", async () => {
@@ -206,7 +171,7 @@ describe("claim", () => {
         .accounts({ userTokenAccount: yesTokenAccount })
         .signers([user])
         .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.UserAccountCannotBeMarketAccount);
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintTokenOwner);
   });
 
   it("fails if the user provides the no token account", async () => {
@@ -217,10 +182,10 @@ describe("claim", () => {
         .accounts({ userTokenAccount: noTokenAccount })
         .signers([user])
         .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.UserAccountCannotBeMarketAccount);
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintTokenOwner);
   });
 
-  it("fails if the user provides a token account they do not own", async () => {
+  it("fails if the user provides a token account the destination does not own", async () => {
     expect.assertions(1);
 
     const wrongUserTokenAccount = Keypair.generate();
@@ -238,7 +203,7 @@ describe("claim", () => {
         .preInstructions(preIxs)
         .signers([user, wrongUserTokenAccount])
         .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.UserAccountIncorrectOwner);
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintTokenOwner);
   });
 
   it("fails if the user position is incorrect", async () => {
@@ -26,6 +26,12 @@ import {
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = 1_000_000n;
@@ -46,7 +52,7 @@ describeFlaky("claim (clock-dependent)", () => {
 
   const mint = Keypair.generate();
   const user = Keypair.generate();
-  const userTokenAccount = Keypair.generate();
+  const userTokenAccount = getAssociatedTokenAddress(mint, user);
   const resolver = Keypair.generate();
 
   let feeWallet: PublicKey, feeAccount: PublicKey;
@@ -69,6 +75,16 @@ describeFlaky("claim (clock-dependent)", () => {
     noAmount,
     resolver: resolver_,
     uri,
+    title,
+    category,
+    contentHash,
+    yesLimits,
+    noLimits,
+    allowlistRoot,
+    requireAcknowledgement,
+    allowEarlyResolution,
+    maxResolutionWindow,
+    resolverBond,
   }: Partial<InitializeMarketParams>): InitializeMarketParams => {
     closeTs ??= intoU64BN(unixTimestamp() + 3600n);
     expiryTs ??= closeTs.addn(3600);
@@ -76,7 +92,17 @@ describeFlaky("claim (clock-dependent)", () => {
     yesAmount ??= intoU64BN(YES_AMOUNT);
     noAmount ??= intoU64BN(NO_AMOUNT);
     resolver_ ??= resolver.publicKey;
-    uri ??= "0".repeat(200);
+    uri ??= "https://example.com/market.json";
+    title ??= "Test market";
+    category ??= encodeCategory("test");
+    contentHash ??= new Array(32).fill(0);
+    yesLimits ??= NO_DEPOSIT_LIMITS;
+    noLimits ??= NO_DEPOSIT_LIMITS;
+    allowlistRoot ??= null;
+    requireAcknowledgement ??= false;
+    allowEarlyResolution ??= false;
+    maxResolutionWindow ??= RESOLUTION_WINDOW;
+    resolverBond ??= intoU64BN(0);
 
     return {
       closeTs,
@@ -86,14 +112,26 @@ describeFlaky("claim (clock-dependent)", () => {
       noAmount,
       resolver: resolver_,
       uri,
+      title,
+      category,
+      contentHash,
+      yesLimits,
+      noLimits,
+      allowlistRoot,
+      requireAcknowledgement,
+      allowEarlyResolution,
+      maxResolutionWindow,
+      resolverBond,
     };
   };
 
   const claim = () =>
     program.methods.claim().accounts({
       globalState: globalState.address,
-      feeAccount,
-      userTokenAccount: userTokenAccount.publicKey,
+      destination: user.publicKey,
+      userTokenAccount,
+      referrerTokenAccount: userTokenAccount,
+      tokenMint: mint.publicKey,
       yesTokenAccount,
       noTokenAccount,
       userPosition,
@@ -117,18 +155,18 @@ describeFlaky("claim (clock-dependent)", () => {
           mint,
           mintAuthority: program.provider.wallet.publicKey,
         })),
-        ...(await createInitAccountInstructions({
+        createAssociatedTokenAccountInstruction({
           account: userTokenAccount,
+          owner: user,
           mint,
-          user,
-        })),
+        }),
         createAssociatedTokenAccountInstruction({
           account: feeAccount,
           owner: feeWallet,
           mint,
         }),
       ],
-      [mint, userTokenAccount],
+      [mint],
     );
   });
 
@@ -148,7 +186,7 @@ describeFlaky("claim (clock-dependent)", () => {
         .accounts({
           mint: mint.publicKey,
           authority: program.provider.wallet.publicKey,
-          to: userTokenAccount.publicKey,
+          to: userTokenAccount,
         })
         .rpc();
     }
@@ -176,8 +214,19 @@ describeFlaky("claim (clock-dependent)", () => {
     await program.methods
       .initializeMarket(params)
       .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
         market: market.publicKey,
         tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
         authority,
         yesTokenAccount,
         noTokenAccount,
@@ -229,13 +278,15 @@ describeFlaky("claim (clock-dependent)", () => {
         yesAmount: intoU64BN(YES_AMOUNT),
         noAmount: intoU64BN(diff),
         allowPartial: true,
+        proof: [],
+        referrer: null,
       })
       .accounts({
         user: user.publicKey,
         market: market.publicKey,
         yesTokenAccount,
         noTokenAccount,
-        userTokenAccount: userTokenAccount.publicKey,
+        userTokenAccount,
         userPosition,
       })
       .instruction();
@@ -263,8 +314,19 @@ describeFlaky("claim (clock-dependent)", () => {
       const initMarketIx = await program.methods
         .initializeMarket(params)
         .accounts({
+          globalState: globalState.address,
+          ...(await getRegistryAccounts(
+            program.provider.wallet.publicKey,
+            mint.publicKey,
+          )),
           market: market.publicKey,
           tokenMint: mint.publicKey,
+          mintPolicy: getMintPolicyAddress(mint.publicKey),
+          bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+          creatorTokenAccount: getAssociatedTokenAddress(
+            mint.publicKey,
+            program.provider.wallet.publicKey,
+          ),
           authority,
           yesTokenAccount,
           noTokenAccount,
@@ -276,6 +338,8 @@ describeFlaky("claim (clock-dependent)", () => {
           yesAmount: intoU64BN(0),
           noAmount: intoU64BN(noAmount),
           allowPartial: true,
+          proof: [],
+          referrer: null,
         })
         .accounts({
           user: otherUser.publicKey,
@@ -316,9 +380,36 @@ describeFlaky("claim (clock-dependent)", () => {
     expect(yesPosition).toEqualBN(0);
     expect(noPosition).toEqualBN(0);
 
-    await expect(yesTokenAccount).toHaveBalance(999_942n);
+    // The fee is left in the escrow until it is collected.
+    await expect(yesTokenAccount).toHaveBalance(999_943n);
     await expect(noTokenAccount).toHaveBalance(noAmount);
-    await expect(feeAccount).toHaveBalance(1n);
     await expect(userTokenAccount).toHaveBalance(4_000_057n);
+
+    const { accruedFees } = await program.account.market.fetch(
+      market.publicKey,
+    );
+    expect(accruedFees).toEqualBN(1);
+
+    const feeBalance = intoU64(await getBalance(feeAccount));
+
+    await program.methods
+      .collectFees()
+      .accounts({
+        globalState: globalState.address,
+        feeWallet,
+        feeAccount,
+        tokenMint: mint.publicKey,
+        collector: globalState.authority.publicKey,
+      })
+      .remainingAccounts([
+        { pubkey: market.publicKey, isSigner: false, isWritable: true },
+        { pubkey: yesTokenAccount, isSigner: false, isWritable: true },
+        { pubkey: authority, isSigner: false, isWritable: false },
+      ])
+      .signers([globalState.authority])
+      .rpc();
+
+    await expect(yesTokenAccount).toHaveBalance(999_942n);
+    await expect(feeAccount).toHaveBalance(feeBalance + 1n);
   });
 });
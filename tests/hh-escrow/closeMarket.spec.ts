@@ -0,0 +1,236 @@
+import type { InitializeMarketParams } from "./utils";
+
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+
+import {
+  intoU64BN,
+  unixTimestamp,
+  createInitMintInstructions,
+  getAssociatedTokenAddress,
+} from "../utils";
+
+import {
+  ErrorCode,
+  program,
+  globalState,
+  getAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+  getResolverBondTokenAccountAddress,
+  getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+} from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+// NOTE: Tests in this block have a dependency order.
+describe("close market", () => {
+  const market = Keypair.generate();
+  const mint = Keypair.generate();
+  const resolver = Keypair.generate();
+
+  const authority = getAuthorityAddress(market);
+  const [yesTokenAccount] = getYesTokenAccountAddress(market);
+  const [noTokenAccount] = getNoTokenAccountAddress(market);
+
+  let registryAccounts: {
+    creatorRegistry: PublicKey;
+    creatorRegistryPage: PublicKey;
+    mintRegistry: PublicKey;
+    mintRegistryPage: PublicKey;
+  };
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const closeMarket = () =>
+    program.methods.closeMarket().accounts({
+      ...registryAccounts,
+      market: market.publicKey,
+      authority,
+      creator: program.provider.wallet.publicKey,
+      yesTokenAccount,
+      noTokenAccount,
+      bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+      resolverBondTokenAccount: getResolverBondTokenAccountAddress(
+        market.publicKey,
+      ),
+    });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    const params: InitializeMarketParams = {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      resolutionDelay: 0,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: resolver.publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+
+    registryAccounts = await getRegistryAccounts(
+      program.provider.wallet.publicKey,
+      mint.publicKey,
+    );
+
+    await program.methods
+      .initializeMarket(params)
+      .accounts({
+        globalState: globalState.address,
+        ...registryAccounts,
+        market: market.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
+        authority,
+        yesTokenAccount,
+        noTokenAccount,
+      })
+      .preInstructions(
+        await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        }),
+      )
+      .signers([mint, market])
+      .rpc();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("registers the market with its creator and token mint", async () => {
+    expect.assertions(3);
+
+    const mintRegistry = await program.account.marketRegistry.fetch(
+      registryAccounts.mintRegistry,
+    );
+
+    expect(mintRegistry.marketCount).toEqualBN(1);
+
+    const creatorPage = await program.account.marketRegistryPage.fetch(
+      registryAccounts.creatorRegistryPage,
+    );
+    const mintPage = await program.account.marketRegistryPage.fetch(
+      registryAccounts.mintRegistryPage,
+    );
+
+    expect(creatorPage.markets).toContainEqual(market.publicKey);
+    expect(mintPage.markets[0]).toEqualPubkey(market.publicKey);
+  });
+
+  it("fails if the market is not finalized", async () => {
+    expect.assertions(1);
+
+    await expect(closeMarket().rpc()).rejects.toThrowProgramError(
+      ErrorCode.NotFinalized,
+    );
+  });
+
+  it("fails if the creator is incorrect", async () => {
+    expect.assertions(1);
+
+    await program.methods
+      .updateState({ outcome: { Invalid: {} } })
+      .accounts({
+        market: market.publicKey,
+        resolver: resolver.publicKey,
+      })
+      .signers([resolver])
+      .rpc();
+
+    const wrongCreator = Keypair.generate();
+
+    await expect(
+      closeMarket()
+        .accounts({ creator: wrongCreator.publicKey })
+        .signers([wrongCreator])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.IncorrectCreator);
+  });
+
+  it("fails if a user position is open", async () => {
+    expect.assertions(1);
+
+    const user = Keypair.generate();
+    const userPosition = getUserPositionAddress(user, market);
+
+    await program.methods
+      .initializeUserPosition()
+      .accounts({
+        user: user.publicKey,
+        payer: program.provider.wallet.publicKey,
+        market: market.publicKey,
+        userPosition,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+
+    await expect(closeMarket().rpc()).rejects.toThrowProgramError(
+      ErrorCode.OpenPositions,
+    );
+
+    await program.methods
+      .closeUserPosition()
+      .accounts({
+        user: user.publicKey,
+        market: market.publicKey,
+        userPosition,
+      })
+      .signers([user])
+      .rpc();
+  });
+
+  it("successfully closes the market", async () => {
+    expect.assertions(4);
+
+    await closeMarket().rpc();
+
+    expect(
+      await program.account.market.fetchNullable(market.publicKey),
+    ).toBeNull();
+
+    const mintRegistry = await program.account.marketRegistry.fetch(
+      registryAccounts.mintRegistry,
+    );
+
+    expect(mintRegistry.marketCount).toEqualBN(0);
+
+    const creatorPage = await program.account.marketRegistryPage.fetch(
+      registryAccounts.creatorRegistryPage,
+    );
+    const mintPage = await program.account.marketRegistryPage.fetch(
+      registryAccounts.mintRegistryPage,
+    );
+
+    expect(creatorPage.markets).not.toContainEqual(market.publicKey);
+    expect(mintPage.markets[0]).toEqualPubkey(PublicKey.default);
+  });
+});
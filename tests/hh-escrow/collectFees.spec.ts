@@ -0,0 +1,182 @@
+import type { InitializeMarketParams } from "./utils";
+
+import type { AccountMeta } from "@solana/web3.js";
+import { Keypair, PublicKey } from "@solana/web3.js";
+
+import {
+  intoU64BN,
+  unixTimestamp,
+  getAssociatedTokenAddress,
+  createAssociatedTokenAccountInstruction,
+  createInitMintInstructions,
+  sendTx,
+} from "../utils";
+
+import {
+  ErrorCode,
+  program,
+  globalState,
+  getAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+describe("collect fees", () => {
+  const market = Keypair.generate();
+  const mint = Keypair.generate();
+  const resolver = Keypair.generate();
+
+  const authority = getAuthorityAddress(market);
+  const [yesTokenAccount] = getYesTokenAccountAddress(market);
+  const [noTokenAccount] = getNoTokenAccountAddress(market);
+
+  let feeWallet: PublicKey, feeAccount: PublicKey;
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const collectFees = (collector: PublicKey) =>
+    program.methods.collectFees().accounts({
+      globalState: globalState.address,
+      feeWallet,
+      feeAccount,
+      tokenMint: mint.publicKey,
+      collector,
+    });
+
+  const marketAccounts = (): AccountMeta[] => [
+    { pubkey: market.publicKey, isSigner: false, isWritable: true },
+    { pubkey: noTokenAccount, isSigner: false, isWritable: true },
+    { pubkey: authority, isSigner: false, isWritable: false },
+  ];
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    feeWallet = await globalState.getFeeWallet();
+    feeAccount = getAssociatedTokenAddress(mint, feeWallet, true);
+
+    await sendTx(
+      [
+        ...(await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        })),
+        createAssociatedTokenAccountInstruction({
+          account: feeAccount,
+          owner: feeWallet,
+          mint,
+        }),
+      ],
+      [mint],
+    );
+
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    const params: InitializeMarketParams = {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      resolutionDelay: 3600,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: resolver.publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+
+    await program.methods
+      .initializeMarket(params)
+      .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
+        market: market.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
+        authority,
+        yesTokenAccount,
+        noTokenAccount,
+      })
+      .signers([market])
+      .rpc();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the collector is neither the fee wallet nor the owner", async () => {
+    expect.assertions(1);
+
+    const collector = Keypair.generate();
+
+    await expect(
+      collectFees(collector.publicKey)
+        .remainingAccounts(marketAccounts())
+        .signers([collector])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.IncorrectFeeCollector);
+  });
+
+  it("fails if no market is given", async () => {
+    expect.assertions(1);
+
+    const collector = globalState.authority;
+
+    await expect(
+      collectFees(collector.publicKey).signers([collector]).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidFeeCollectionAccounts);
+  });
+
+  it("fails if a market is given without its escrow and authority", async () => {
+    expect.assertions(1);
+
+    const collector = globalState.authority;
+
+    await expect(
+      collectFees(collector.publicKey)
+        .remainingAccounts(marketAccounts().slice(0, 2))
+        .signers([collector])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidFeeCollectionAccounts);
+  });
+
+  it("skips markets without accrued fees", async () => {
+    expect.assertions(1);
+
+    const collector = globalState.authority;
+
+    await collectFees(collector.publicKey)
+      .remainingAccounts(marketAccounts())
+      .signers([collector])
+      .rpc();
+
+    await expect(feeAccount).toHaveBalance(0n);
+  });
+});
@@ -13,15 +13,23 @@ import {
   createInitMintInstructions,
   sendTx,
   __throw,
+  getAssociatedTokenAddress,
 } from "../utils";
 
 import {
   ErrorCode,
   program,
+  globalState,
   getAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = 1_000_000n;
@@ -36,6 +44,7 @@ describe("deposit", () => {
   const user = Keypair.generate();
   const userTokenAccount = Keypair.generate();
   const resolver = Keypair.generate();
+  const referrer = Keypair.generate();
 
   const authority = getAuthorityAddress(market);
   const [yesTokenAccount] = getYesTokenAccountAddress(market);
@@ -48,16 +57,22 @@ describe("deposit", () => {
     yesAmount,
     noAmount,
     allowPartial,
+    proof,
+    referrer: referrer_,
   }: Partial<DepositParams>) => {
     yesAmount ??= intoU64BN(YES_AMOUNT / 2n);
     noAmount ??= intoU64BN(NO_AMOUNT / 2n);
     allowPartial ??= false;
+    proof ??= [];
+    referrer_ ??= null;
 
     return program.methods
       .deposit({
         yesAmount,
         noAmount,
         allowPartial,
+        proof,
+        referrer: referrer_,
       })
       .accounts({
         market: market.publicKey,
@@ -72,6 +87,9 @@ describe("deposit", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     await sendTx(
       [
         ...(await createInitMintInstructions({
@@ -97,7 +115,17 @@ describe("deposit", () => {
       yesAmount: intoU64BN(YES_AMOUNT),
       noAmount: intoU64BN(NO_AMOUNT),
       resolver: resolver.publicKey,
-      uri: "0".repeat(200),
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
     };
 
     await sendTx(
@@ -105,8 +133,19 @@ describe("deposit", () => {
         await program.methods
           .initializeMarket(params)
           .accounts({
+            globalState: globalState.address,
+            ...(await getRegistryAccounts(
+              program.provider.wallet.publicKey,
+              mint.publicKey,
+            )),
             market: market.publicKey,
             tokenMint: mint.publicKey,
+            mintPolicy: getMintPolicyAddress(mint.publicKey),
+            bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+            creatorTokenAccount: getAssociatedTokenAddress(
+              mint.publicKey,
+              program.provider.wallet.publicKey,
+            ),
             authority,
             yesTokenAccount,
             noTokenAccount,
@@ -228,14 +267,24 @@ describe("deposit", () => {
     ).rejects.toThrowProgramError(ErrorCode.OverAllowedAmount);
   });
 
+  it("fails if the user refers themselves", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit({ referrer: user.publicKey }).signers([user]).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidReferrer);
+  });
+
   it("successfully deposits", async () => {
-    expect.assertions(4);
+    expect.assertions(5);
 
-    await deposit({}).signers([user]).rpc();
+    await deposit({ referrer: referrer.publicKey }).signers([user]).rpc();
 
-    const { yesAmount, noAmount } = await program.account.userPosition.fetch(
-      userPosition,
-    );
+    const {
+      yesAmount,
+      noAmount,
+      referrer: positionReferrer,
+    } = await program.account.userPosition.fetch(userPosition);
     const { yesFilled, noFilled } = await program.account.market.fetch(
       market.publicKey,
     );
@@ -244,6 +293,17 @@ describe("deposit", () => {
     expect(noAmount).toEqualBN(NO_AMOUNT / 2n);
     expect(yesFilled).toEqualBN(YES_AMOUNT / 2n);
     expect(noFilled).toEqualBN(NO_AMOUNT / 2n);
+    expect(positionReferrer).toEqualPubkey(referrer.publicKey);
+  });
+
+  it("fails if the referrer changes after the first deposit", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit({ referrer: Keypair.generate().publicKey })
+        .signers([user])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.ReferrerImmutable);
   });
 
   it("successfully deposits if the yes deposit exceeds the market amount (allow_partial = true)", async () => {
@@ -286,3 +346,196 @@ describe("deposit", () => {
     expect(noFilled).toEqualBN(NO_AMOUNT);
   });
 });
+
+// NOTE: Tests in this block have a dependency order.
+describe("deposit (limits)", () => {
+  const market = Keypair.generate();
+  const mint = Keypair.generate();
+  const user = Keypair.generate();
+  const userTokenAccount = Keypair.generate();
+  const resolver = Keypair.generate();
+
+  const authority = getAuthorityAddress(market);
+  const [yesTokenAccount] = getYesTokenAccountAddress(market);
+  const [noTokenAccount] = getNoTokenAccountAddress(market);
+  const userPosition = getUserPositionAddress(user, market);
+
+  const MIN_DEPOSIT = 1_000n;
+  const MAX_PER_USER = YES_AMOUNT / 2n;
+
+  ///////////////////////////////////////////////////////////////////////////////
+
+  const deposit = ({
+    yesAmount,
+    noAmount,
+    allowPartial,
+    proof,
+    referrer,
+  }: Partial<DepositParams>) => {
+    yesAmount ??= intoU64BN(0);
+    noAmount ??= intoU64BN(0);
+    allowPartial ??= false;
+    proof ??= [];
+    referrer ??= null;
+
+    return program.methods
+      .deposit({
+        yesAmount,
+        noAmount,
+        allowPartial,
+        proof,
+        referrer,
+      })
+      .accounts({
+        market: market.publicKey,
+        user: user.publicKey,
+        userPosition,
+        userTokenAccount: userTokenAccount.publicKey,
+        yesTokenAccount,
+        noTokenAccount,
+      });
+  };
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    await sendTx(
+      [
+        ...(await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        })),
+        ...(await createInitAccountInstructions({
+          account: userTokenAccount,
+          mint,
+          user,
+        })),
+      ],
+      [mint, userTokenAccount],
+    );
+
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    const params: InitializeMarketParams = {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      resolutionDelay: 3600,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: resolver.publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: {
+        minDeposit: intoU64BN(MIN_DEPOSIT),
+        maxPerUser: intoU64BN(MAX_PER_USER),
+      },
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+
+    await sendTx(
+      [
+        await program.methods
+          .initializeMarket(params)
+          .accounts({
+            globalState: globalState.address,
+            ...(await getRegistryAccounts(
+              program.provider.wallet.publicKey,
+              mint.publicKey,
+            )),
+            market: market.publicKey,
+            tokenMint: mint.publicKey,
+            mintPolicy: getMintPolicyAddress(mint.publicKey),
+            bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+            creatorTokenAccount: getAssociatedTokenAddress(
+              mint.publicKey,
+              program.provider.wallet.publicKey,
+            ),
+            authority,
+            yesTokenAccount,
+            noTokenAccount,
+          })
+          .instruction(),
+        await program.methods
+          .initializeUserPosition()
+          .accounts({
+            user: user.publicKey,
+            market: market.publicKey,
+            userPosition,
+          })
+          .instruction(),
+      ],
+      [market, user],
+    );
+
+    await spl.methods
+      .mintTo(intoU64BN(TOP_OFF))
+      .accounts({
+        mint: mint.publicKey,
+        authority: program.provider.wallet.publicKey,
+        to: userTokenAccount.publicKey,
+      })
+      .rpc();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the deposit is below the minimum deposit", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit({ yesAmount: intoU64BN(MIN_DEPOSIT - 1n) })
+        .signers([user])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.BelowMinimumDeposit);
+  });
+
+  it("fails if the deposit exceeds the maximum per user (allow_partial = false)", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit({ yesAmount: intoU64BN(MAX_PER_USER + 1n) })
+        .signers([user])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.OverUserLimit);
+  });
+
+  it("successfully deposits up to the maximum per user (allow_partial = true)", async () => {
+    expect.assertions(2);
+
+    await deposit({
+      yesAmount: intoU64BN(MAX_PER_USER + 1n),
+      allowPartial: true,
+    })
+      .signers([user])
+      .rpc();
+
+    const { yesAmount } = await program.account.userPosition.fetch(
+      userPosition,
+    );
+    const { yesFilled } = await program.account.market.fetch(market.publicKey);
+
+    expect(yesAmount).toEqualBN(MAX_PER_USER);
+    expect(yesFilled).toEqualBN(MAX_PER_USER);
+  });
+
+  it("fails if the user has already deposited the maximum per user", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit({ yesAmount: intoU64BN(MIN_DEPOSIT) })
+        .signers([user])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.OverUserLimit);
+  });
+});
@@ -15,6 +15,7 @@ describe.skip("initialize global state", () => {
       program.methods
         .initializeGlobalState({
           protocolFeeBps: 10,
+          crankTipBps: 0,
         })
         .accounts({
           globalState: globalState.address,
@@ -38,6 +39,7 @@ describe.skip("initialize global state", () => {
       program.methods
         .initializeGlobalState({
           protocolFeeBps: 10_001,
+          crankTipBps: 0,
         })
         .accounts({
           globalState: globalState.address,
@@ -54,11 +56,12 @@ describe.skip("initialize global state", () => {
   });
 
   it("initializes the global state", async () => {
-    expect.assertions(3);
+    expect.assertions(4);
 
     await program.methods
       .initializeGlobalState({
         protocolFeeBps: 10_000,
+        crankTipBps: 500,
       })
       .accounts({
         globalState: globalState.address,
@@ -77,5 +80,6 @@ describe.skip("initialize global state", () => {
     expect(state.owner).toEqualPubkey(authority.publicKey);
     expect(state.feeWallet).toEqualPubkey(feeWallet.publicKey);
     expect(state.feeCutBps.bps).toBe(10_000);
+    expect(state.crankTipBps.bps).toBe(500);
   });
 });
@@ -10,15 +10,24 @@ import {
   createInitMintInstructions,
   sendTx,
   __throw,
+  getAssociatedTokenAddress,
 } from "../utils";
 
 import {
   ErrorCode,
   program,
+  globalState,
   interpretMarketResource,
+  interpretMarketTitle,
   getAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = 1_000_000n;
@@ -45,6 +54,16 @@ describe("initialize market", () => {
     noAmount,
     resolver: resolver_,
     uri,
+    title,
+    category,
+    contentHash,
+    yesLimits,
+    noLimits,
+    allowlistRoot,
+    requireAcknowledgement,
+    allowEarlyResolution,
+    maxResolutionWindow,
+    resolverBond,
   }: Partial<InitializeMarketParams>): InitializeMarketParams => {
     closeTs ??= intoU64BN(unixTimestamp() + 3600n);
     expiryTs ??= closeTs.addn(3600);
@@ -52,7 +71,17 @@ describe("initialize market", () => {
     yesAmount ??= intoU64BN(YES_AMOUNT);
     noAmount ??= intoU64BN(NO_AMOUNT);
     resolver_ ??= resolver.publicKey;
-    uri ??= "0".repeat(200);
+    uri ??= "https://example.com/market.json";
+    title ??= "Test market";
+    category ??= encodeCategory("test");
+    contentHash ??= new Array(32).fill(0);
+    yesLimits ??= NO_DEPOSIT_LIMITS;
+    noLimits ??= NO_DEPOSIT_LIMITS;
+    allowlistRoot ??= null;
+    requireAcknowledgement ??= false;
+    allowEarlyResolution ??= false;
+    maxResolutionWindow ??= RESOLUTION_WINDOW;
+    resolverBond ??= intoU64BN(0);
 
     return {
       closeTs,
@@ -62,15 +91,36 @@ describe("initialize market", () => {
       noAmount,
       resolver: resolver_,
       uri,
+      title,
+      category,
+      contentHash,
+      yesLimits,
+      noLimits,
+      allowlistRoot,
+      requireAcknowledgement,
+      allowEarlyResolution,
+      maxResolutionWindow,
+      resolverBond,
     };
   };
 
-  const initMarket = (params: Partial<InitializeMarketParams>) =>
+  const initMarket = async (params: Partial<InitializeMarketParams>) =>
     program.methods.initializeMarket(initMarketParams(params)).accounts({
+      globalState: globalState.address,
+      ...(await getRegistryAccounts(
+        program.provider.wallet.publicKey,
+        mint.publicKey,
+      )),
       market: market.publicKey,
       authority,
       creator: program.provider.wallet.publicKey,
       tokenMint: mint.publicKey,
+      mintPolicy: getMintPolicyAddress(mint.publicKey),
+      bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+      creatorTokenAccount: getAssociatedTokenAddress(
+        mint.publicKey,
+        program.provider.wallet.publicKey,
+      ),
       yesTokenAccount,
       noTokenAccount,
       systemProgram: SystemProgram.programId,
@@ -80,6 +130,9 @@ describe("initialize market", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     await sendTx(
       await createInitMintInstructions({
         mint,
@@ -100,11 +153,11 @@ describe("initialize market", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   it("successfully initializes market", async () => {
-    expect.assertions(19);
+    expect.assertions(23);
 
     const params = initMarketParams({});
 
-    await initMarket(params).signers([market]).rpc();
+    await (await initMarket(params)).signers([market]).rpc();
 
     const info = await program.account.market.fetch(market.publicKey);
 
@@ -126,7 +179,11 @@ describe("initialize market", () => {
     expect(info.yesAccountBump).toBe(yesTokenAccountNonce);
     expect(info.noAccountBump).toBe(noTokenAccountNonce);
     expect(info.acknowledged).toBe(false);
-    expect(interpretMarketResource(info.uri)).toBe(params.uri);
+    expect(info.metadata.version).toBe(1);
+    expect(interpretMarketResource(info.metadata.uri)).toBe(params.uri);
+    expect(interpretMarketTitle(info.metadata.title)).toBe(params.title);
+    expect(info.metadata.category).toEqual(params.category);
+    expect(info.metadata.contentHash).toEqual(params.contentHash);
   });
 
   it("fails if the authority is incorrect", async () => {
@@ -135,7 +192,7 @@ describe("initialize market", () => {
     const wrongAuthority = Keypair.generate();
 
     await expect(
-      initMarket({})
+      (await initMarket({}))
         .accounts({ authority: wrongAuthority.publicKey })
         .signers([market])
         .rpc(),
@@ -155,7 +212,7 @@ describe("initialize market", () => {
     // ).rejects.toThrowProgramError(LangErrorCode.ConstraintSeeds);
 
     await expect(
-      initMarket({})
+      (await initMarket({}))
         .accounts({ yesTokenAccount: wrongTokenAccount.publicKey })
         .signers([market])
         .rpc(),
@@ -175,28 +232,56 @@ describe("initialize market", () => {
     // ).rejects.toThrowProgramError(LangErrorCode.ConstraintSeeds);
 
     await expect(
-      initMarket({})
+      (await initMarket({}))
         .accounts({ noTokenAccount: wrongTokenAccount.publicKey })
         .signers([market])
         .rpc(),
     ).rejects.toThrow();
   });
 
-  // it("fails if URI is too long", async () => {
-  //   expect.assertions(1);
+  it("fails if URI is too long", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket({ uri: "https://" + "0".repeat(193) }))
+        .signers([market])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidMarketResource);
+  });
+
+  it("fails if URI does not have a valid scheme", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket({ uri: "example.com/market.json" }))
+        .signers([market])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidMarketResource);
+  });
+
+  it("fails if the title is empty", async () => {
+    expect.assertions(1);
 
-  //   await expect(
-  //     initMarket({ uri: "0".repeat(201) })
-  //       .signers([market])
-  //       .rpc(),
-  //   ).rejects.toThrowProgramError(ErrorCode.InvalidMarketResource);
-  // });
+    await expect(
+      (await initMarket({ title: "" })).signers([market]).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidMarketTitle);
+  });
+
+  it("fails if the category is not ASCII", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket({ category: encodeCategory("café") }))
+        .signers([market])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidMarketCategory);
+  });
 
   it("fails if close timestamp is before the current time", async () => {
     expect.assertions(1);
 
     await expect(
-      initMarket({ closeTs: intoU64BN(0) })
+      (await initMarket({ closeTs: intoU64BN(0) }))
         .signers([market])
         .rpc(),
     ).rejects.toThrowProgramError(ErrorCode.InvalidCloseTimestamp);
@@ -206,17 +291,35 @@ describe("initialize market", () => {
     expect.assertions(1);
 
     await expect(
-      initMarket({ expiryTs: intoU64BN(0) })
+      (await initMarket({ expiryTs: intoU64BN(0) }))
         .signers([market])
         .rpc(),
     ).rejects.toThrowProgramError(ErrorCode.InvalidExpiryTimestamp);
   });
 
+  it("fails if the resolution window is zero", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket({ maxResolutionWindow: 0 })).signers([market]).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidResolutionWindow);
+  });
+
+  it("fails if the resolution delay exceeds the resolution window", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket({ resolutionDelay: RESOLUTION_WINDOW + 1 }))
+        .signers([market])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.ResolutionDelayTooLong);
+  });
+
   it("fails if the yes amount is zero", async () => {
     expect.assertions(1);
 
     await expect(
-      initMarket({ yesAmount: intoU64BN(0) })
+      (await initMarket({ yesAmount: intoU64BN(0) }))
         .signers([market])
         .rpc(),
     ).rejects.toThrowProgramError(ErrorCode.CannotHaveNonzeroAmounts);
@@ -226,9 +329,39 @@ describe("initialize market", () => {
     expect.assertions(1);
 
     await expect(
-      initMarket({ noAmount: intoU64BN(0) })
+      (await initMarket({ noAmount: intoU64BN(0) }))
         .signers([market])
         .rpc(),
     ).rejects.toThrowProgramError(ErrorCode.CannotHaveNonzeroAmounts);
   });
+
+  it("fails if the minimum deposit is greater than the side amount", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket({
+        yesLimits: {
+          minDeposit: intoU64BN(YES_AMOUNT + 1n),
+          maxPerUser: intoU64BN(0),
+        },
+      }))
+        .signers([market])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidDepositLimits);
+  });
+
+  it("fails if the minimum deposit is greater than the maximum per user", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket({
+        noLimits: {
+          minDeposit: intoU64BN(2),
+          maxPerUser: intoU64BN(1),
+        },
+      }))
+        .signers([market])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidDepositLimits);
+  });
 });
@@ -0,0 +1,156 @@
+import type { InitializeMarketParams } from "./utils";
+
+import { LangErrorCode } from "@project-serum/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+
+import {
+  intoU64BN,
+  unixTimestamp,
+  createInitMintInstructions,
+  sendTx,
+  getAssociatedTokenAddress,
+} from "../utils";
+
+import {
+  program,
+  globalState,
+  getAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  getMarketAddress,
+  getMarketCounterAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+// NOTE: Tests in this block have a dependency order.
+describe("initialize market pda", () => {
+  const mint = Keypair.generate();
+  const creator = Keypair.generate();
+  const resolver = Keypair.generate();
+
+  const marketCounter = getMarketCounterAddress(creator);
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const initMarketParams = (): InitializeMarketParams => {
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    return {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      resolutionDelay: 3600,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: resolver.publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+  };
+
+  const initMarketPda = async (market: PublicKey) =>
+    program.methods
+      .initializeMarketPda(initMarketParams())
+      .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(creator.publicKey, mint.publicKey)),
+        marketCounter,
+        market,
+        authority: getAuthorityAddress(market),
+        creator: creator.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          creator.publicKey,
+        ),
+        yesTokenAccount: getYesTokenAccountAddress(market)[0],
+        noTokenAccount: getNoTokenAccountAddress(market)[0],
+      })
+      .signers([creator]);
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    await sendTx(
+      [
+        ...(await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        })),
+        // Fund the creator, who pays for the market accounts.
+        SystemProgram.transfer({
+          fromPubkey: program.provider.wallet.publicKey,
+          toPubkey: creator.publicKey,
+          lamports: 1_000_000_000,
+        }),
+      ],
+      [mint],
+    );
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the market is not at the next nonce", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarketPda(getMarketAddress(creator, 1))).rpc(),
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintSeeds);
+  });
+
+  it("successfully initializes the first market", async () => {
+    expect.assertions(4);
+
+    const market = getMarketAddress(creator, 0);
+
+    await (await initMarketPda(market)).rpc();
+
+    const info = await program.account.market.fetch(market);
+
+    expect(info.creator).toEqualPubkey(creator.publicKey);
+    expect(info.resolver).toEqualPubkey(resolver.publicKey);
+
+    const counter = await program.account.marketCounter.fetch(marketCounter);
+
+    expect(counter.creator).toEqualPubkey(creator.publicKey);
+    expect(counter.nextNonce).toEqualBN(1n);
+  });
+
+  it("successfully initializes the next market", async () => {
+    expect.assertions(2);
+
+    const market = getMarketAddress(creator, 1);
+
+    await (await initMarketPda(market)).rpc();
+
+    const info = await program.account.market.fetch(market);
+
+    expect(info.creator).toEqualPubkey(creator.publicKey);
+
+    const counter = await program.account.marketCounter.fetch(marketCounter);
+
+    expect(counter.nextNonce).toEqualBN(2n);
+  });
+});
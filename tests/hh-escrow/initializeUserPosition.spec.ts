@@ -7,14 +7,22 @@ import {
   unixTimestamp,
   createInitMintInstructions,
   __throw,
+  getAssociatedTokenAddress,
 } from "../utils";
 
 import {
   program,
+  globalState,
   getAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = 1_000_000n;
@@ -32,6 +40,9 @@ describe("initialize user position", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     const closeTs = unixTimestamp() + 3600n;
     const expiryTs = closeTs + 3600n;
 
@@ -42,7 +53,17 @@ describe("initialize user position", () => {
       yesAmount: intoU64BN(YES_AMOUNT),
       noAmount: intoU64BN(NO_AMOUNT),
       resolver: resolver.publicKey,
-      uri: "0".repeat(200),
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
     };
 
     const preIxs = await createInitMintInstructions({
@@ -53,8 +74,19 @@ describe("initialize user position", () => {
     await program.methods
       .initializeMarket(params)
       .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
         market: market.publicKey,
         tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
         authority,
         yesTokenAccount,
         noTokenAccount,
@@ -0,0 +1,27 @@
+import { SystemProgram } from "@solana/web3.js";
+
+import { ErrorCode, program, globalState } from "./utils";
+
+describe("migrate global state", () => {
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the global state is already migrated", async () => {
+    expect.assertions(1);
+
+    await expect(
+      program.methods
+        .migrateGlobalState()
+        .accounts({
+          globalState: globalState.address,
+          payer: program.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.AlreadyMigrated);
+  });
+});
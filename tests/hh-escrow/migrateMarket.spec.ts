@@ -0,0 +1,62 @@
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+
+import { ErrorCode, interpretMarketResource, program } from "./utils";
+
+// Loaded into the validator with the unversioned layout.
+const MARKET_V0 = new PublicKey("HfowRTwGMS3Mpaoz6wRtbpSKDJo4F9KpHHMYUEdhFqR3");
+const USER_POSITION_V0 = new PublicKey(
+  "Cm3mfCsjYMLCViuY7ixuGb65tjk4Zv8fQTrdXrfu1idh",
+);
+
+// NOTE: Tests in this block have a dependency order.
+describe("migrate market", () => {
+  const migrateMarket = (market: PublicKey) =>
+    program.methods.migrateMarket().accounts({
+      market,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the account is not a market", async () => {
+    expect.assertions(1);
+
+    await expect(
+      migrateMarket(USER_POSITION_V0).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.UnknownAccountLayout);
+  });
+
+  it("fails if the account is not owned by the program", async () => {
+    expect.assertions(1);
+
+    await expect(
+      migrateMarket(Keypair.generate().publicKey).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.UnknownAccountLayout);
+  });
+
+  it("successfully migrates an unversioned market", async () => {
+    expect.assertions(6);
+
+    await migrateMarket(MARKET_V0).rpc();
+
+    const info = await program.account.market.fetch(MARKET_V0);
+
+    expect(info.version).toBe(1);
+    expect(info.creator).toEqualPubkey(new PublicKey(Buffer.alloc(32, 1)));
+    expect(info.resolver).toEqualPubkey(new PublicKey(Buffer.alloc(32, 2)));
+    expect(info.yesAmount).toEqualBN(1_000n);
+    expect(info.metadata.version).toBe(0);
+    expect(interpretMarketResource(info.metadata.uri)).toBe(
+      "https://example.com/market.json",
+    );
+  });
+
+  it("fails if the market is already migrated", async () => {
+    expect.assertions(1);
+
+    await expect(migrateMarket(MARKET_V0).rpc()).rejects.toThrowProgramError(
+      ErrorCode.AlreadyMigrated,
+    );
+  });
+});
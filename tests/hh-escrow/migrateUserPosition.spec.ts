@@ -0,0 +1,65 @@
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+
+import { ErrorCode, program } from "./utils";
+
+// Loaded into the validator with the unversioned layout.
+const MARKET_V0 = new PublicKey("HfowRTwGMS3Mpaoz6wRtbpSKDJo4F9KpHHMYUEdhFqR3");
+const USER_POSITION_V0 = new PublicKey(
+  "Cm3mfCsjYMLCViuY7ixuGb65tjk4Zv8fQTrdXrfu1idh",
+);
+
+// NOTE: Tests in this block have a dependency order.
+describe("migrate user position", () => {
+  const migrateUserPosition = () =>
+    program.methods.migrateUserPosition().accounts({
+      userPosition: USER_POSITION_V0,
+      market: MARKET_V0,
+      payer: program.provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+    });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // The market counts the position once migrated, so it must be migrated
+    // first, unless the migrate market tests already did.
+    const info = await program.provider.connection.getAccountInfo(MARKET_V0);
+    if (info && info.data.length < program.account.market.size) {
+      await program.methods
+        .migrateMarket()
+        .accounts({
+          market: MARKET_V0,
+          payer: program.provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("successfully migrates an unversioned user position", async () => {
+    expect.assertions(5);
+
+    await migrateUserPosition().rpc();
+
+    const info = await program.account.userPosition.fetch(USER_POSITION_V0);
+
+    expect(info.version).toBe(1);
+    expect(info.market).toEqualPubkey(MARKET_V0);
+    expect(info.yesAmount).toEqualBN(10n);
+    expect(info.noAmount).toEqualBN(20n);
+
+    const { openPositions } = await program.account.market.fetch(MARKET_V0);
+
+    expect(openPositions).toEqualBN(1n);
+  });
+
+  it("fails if the user position is already migrated", async () => {
+    expect.assertions(1);
+
+    await expect(migrateUserPosition().rpc()).rejects.toThrowProgramError(
+      ErrorCode.AlreadyMigrated,
+    );
+  });
+});
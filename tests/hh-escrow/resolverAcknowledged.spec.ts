@@ -3,24 +3,37 @@ import type { InitializeMarketParams } from "./utils";
 import { Keypair } from "@solana/web3.js";
 
 import {
+  spl,
   intoU64BN,
   unixTimestamp,
   createInitAccountInstructions,
   createInitMintInstructions,
   __throw,
+  getAssociatedTokenAddress,
 } from "../utils";
 
 import {
   ErrorCode,
   program,
+  globalState,
   getAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
+  getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = 1_000_000n;
 const NO_AMOUNT = 2_000_000n;
 
+const TOP_OFF = 5_000_000n;
+
+// NOTE: Tests in this block have a dependency order.
 describe("resolver acknowledged", () => {
   const market = Keypair.generate();
   const mint = Keypair.generate();
@@ -31,10 +44,34 @@ describe("resolver acknowledged", () => {
   const authority = getAuthorityAddress(market);
   const [yesTokenAccount] = getYesTokenAccountAddress(market);
   const [noTokenAccount] = getNoTokenAccountAddress(market);
+  const userPosition = getUserPositionAddress(user, market);
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const deposit = () =>
+    program.methods
+      .deposit({
+        yesAmount: intoU64BN(1n),
+        noAmount: intoU64BN(0n),
+        allowPartial: false,
+        proof: [],
+        referrer: null,
+      })
+      .accounts({
+        market: market.publicKey,
+        user: user.publicKey,
+        userPosition,
+        userTokenAccount: userTokenAccount.publicKey,
+        yesTokenAccount,
+        noTokenAccount,
+      });
 
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     const closeTs = unixTimestamp() + 3600n;
     const expiryTs = closeTs + 3600n;
 
@@ -45,7 +82,17 @@ describe("resolver acknowledged", () => {
       yesAmount: intoU64BN(YES_AMOUNT),
       noAmount: intoU64BN(NO_AMOUNT),
       resolver: resolver.publicKey,
-      uri: "0".repeat(200),
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: true,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
     };
 
     const preIxs = [
@@ -63,14 +110,44 @@ describe("resolver acknowledged", () => {
     await program.methods
       .initializeMarket(params)
       .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
         market: market.publicKey,
         tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
         authority,
         yesTokenAccount,
         noTokenAccount,
       })
       .preInstructions(preIxs)
-      .signers([mint, userTokenAccount, market])
+      .postInstructions([
+        await program.methods
+          .initializeUserPosition()
+          .accounts({
+            user: user.publicKey,
+            market: market.publicKey,
+            userPosition,
+          })
+          .instruction(),
+      ])
+      .signers([mint, userTokenAccount, market, user])
+      .rpc();
+
+    await spl.methods
+      .mintTo(intoU64BN(TOP_OFF))
+      .accounts({
+        mint: mint.publicKey,
+        authority: program.provider.wallet.publicKey,
+        to: userTokenAccount.publicKey,
+      })
       .rpc();
   });
 
@@ -93,6 +170,14 @@ describe("resolver acknowledged", () => {
     ).rejects.toThrowProgramError(ErrorCode.IncorrectResolver);
   });
 
+  it("fails to deposit before the market is acknowledged", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit().signers([user]).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.NotAcknowledged);
+  });
+
   it("successfully acknowledges the market", async () => {
     expect.assertions(1);
 
@@ -109,4 +194,16 @@ describe("resolver acknowledged", () => {
 
     expect(info.acknowledged).toBe(true);
   });
+
+  it("successfully deposits after the market is acknowledged", async () => {
+    expect.assertions(1);
+
+    await deposit().signers([user]).rpc();
+
+    const { yesAmount } = await program.account.userPosition.fetch(
+      userPosition,
+    );
+
+    expect(yesAmount).toEqualBN(1n);
+  });
 });
@@ -0,0 +1,243 @@
+import type { DepositParams, InitializeMarketParams } from "./utils";
+
+import { Keypair } from "@solana/web3.js";
+
+import {
+  spl,
+  intoU64BN,
+  unixTimestamp,
+  createInitAccountInstructions,
+  createInitMintInstructions,
+  sendTx,
+  getAssociatedTokenAddress,
+} from "../utils";
+
+import {
+  Allowlist,
+  ErrorCode,
+  program,
+  globalState,
+  getAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+const TOP_OFF = 5_000_000n;
+
+// NOTE: Tests in this block have a dependency order.
+describe("set allowlist root", () => {
+  const market = Keypair.generate();
+  const mint = Keypair.generate();
+  const user = Keypair.generate();
+  const userTokenAccount = Keypair.generate();
+  const resolver = Keypair.generate();
+
+  const authority = getAuthorityAddress(market);
+  const [yesTokenAccount] = getYesTokenAccountAddress(market);
+  const [noTokenAccount] = getNoTokenAccountAddress(market);
+  const userPosition = getUserPositionAddress(user, market);
+
+  const others = [Keypair.generate(), Keypair.generate()];
+  const allowlist = new Allowlist([...others, user]);
+
+  ///////////////////////////////////////////////////////////////////////////////
+
+  const setAllowlistRoot = (allowlistRoot: number[] | null) =>
+    program.methods.setAllowlistRoot({ allowlistRoot }).accounts({
+      market: market.publicKey,
+      creator: program.provider.wallet.publicKey,
+    });
+
+  const deposit = ({ proof }: Partial<DepositParams>) =>
+    program.methods
+      .deposit({
+        yesAmount: intoU64BN(1n),
+        noAmount: intoU64BN(0n),
+        allowPartial: false,
+        proof: proof ?? [],
+        referrer: null,
+      })
+      .accounts({
+        market: market.publicKey,
+        user: user.publicKey,
+        userPosition,
+        userTokenAccount: userTokenAccount.publicKey,
+        yesTokenAccount,
+        noTokenAccount,
+      });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    await sendTx(
+      [
+        ...(await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        })),
+        ...(await createInitAccountInstructions({
+          account: userTokenAccount,
+          mint,
+          user,
+        })),
+      ],
+      [mint, userTokenAccount],
+    );
+
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    // The user is not on the initial allowlist.
+    const params: InitializeMarketParams = {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      resolutionDelay: 3600,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: resolver.publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: new Allowlist(others).root,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+
+    await sendTx(
+      [
+        await program.methods
+          .initializeMarket(params)
+          .accounts({
+            globalState: globalState.address,
+            ...(await getRegistryAccounts(
+              program.provider.wallet.publicKey,
+              mint.publicKey,
+            )),
+            market: market.publicKey,
+            tokenMint: mint.publicKey,
+            mintPolicy: getMintPolicyAddress(mint.publicKey),
+            bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+            creatorTokenAccount: getAssociatedTokenAddress(
+              mint.publicKey,
+              program.provider.wallet.publicKey,
+            ),
+            authority,
+            yesTokenAccount,
+            noTokenAccount,
+          })
+          .instruction(),
+        await program.methods
+          .initializeUserPosition()
+          .accounts({
+            user: user.publicKey,
+            market: market.publicKey,
+            userPosition,
+          })
+          .instruction(),
+      ],
+      [market, user],
+    );
+
+    await spl.methods
+      .mintTo(intoU64BN(TOP_OFF))
+      .accounts({
+        mint: mint.publicKey,
+        authority: program.provider.wallet.publicKey,
+        to: userTokenAccount.publicKey,
+      })
+      .rpc();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the signer is not the market creator", async () => {
+    expect.assertions(1);
+
+    const wrongCreator = Keypair.generate();
+
+    await expect(
+      setAllowlistRoot(allowlist.root)
+        .accounts({ creator: wrongCreator.publicKey })
+        .signers([wrongCreator])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.IncorrectCreator);
+  });
+
+  it("fails to deposit if the user is not on the allowlist", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit({ proof: allowlist.getProof(0) }).signers([user]).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.NotAllowlisted);
+  });
+
+  it("successfully rotates the allowlist root", async () => {
+    expect.assertions(1);
+
+    await setAllowlistRoot(allowlist.root).rpc();
+
+    const { allowlistRoot } = await program.account.market.fetch(
+      market.publicKey,
+    );
+
+    expect(allowlistRoot).toEqual(allowlist.root);
+  });
+
+  it("fails to deposit without a valid proof", async () => {
+    expect.assertions(1);
+
+    await expect(
+      deposit({ proof: allowlist.getProof(0) }).signers([user]).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.NotAllowlisted);
+  });
+
+  it("successfully deposits with a valid proof", async () => {
+    expect.assertions(1);
+
+    await deposit({ proof: allowlist.getProof(2) }).signers([user]).rpc();
+
+    const { yesAmount } = await program.account.userPosition.fetch(
+      userPosition,
+    );
+
+    expect(yesAmount).toEqualBN(1n);
+  });
+
+  it("successfully makes the market public", async () => {
+    expect.assertions(2);
+
+    await setAllowlistRoot(null).rpc();
+
+    const { allowlistRoot } = await program.account.market.fetch(
+      market.publicKey,
+    );
+
+    expect(allowlistRoot).toEqual(new Array(32).fill(0));
+
+    await deposit({}).signers([user]).rpc();
+
+    const { yesAmount } = await program.account.userPosition.fetch(
+      userPosition,
+    );
+
+    expect(yesAmount).toEqualBN(2n);
+  });
+});
@@ -1,6 +1,18 @@
 import { Keypair } from "@solana/web3.js";
 
-import { ErrorCode, program, globalState } from "./utils";
+import { ErrorCode, program, globalState, RESOLUTION_WINDOW } from "./utils";
+
+// Leaves the optional settings of the global state unchanged.
+const UNCHANGED = {
+  newCrankTipBps: null,
+  newReferralBps: null,
+  newMinResolutionWindow: null,
+  newMaxResolutionWindow: null,
+  newCreationBondLamports: null,
+  newArbiter: null,
+  newDisputeBondLamports: null,
+  newTemplateResolverProgram: null,
+};
 
 describe("set global state", () => {
   const authority = globalState.authority;
@@ -23,6 +35,7 @@ describe("set global state", () => {
           newOwner: authority.publicKey,
           newFeeWallet: feeWallet,
           newFeeCutBps: protocolFeeBps.bps,
+          ...UNCHANGED,
         })
         .accounts({
           globalState: globalState.address,
@@ -44,6 +57,7 @@ describe("set global state", () => {
           newOwner: authority.publicKey,
           newFeeWallet: feeWallet,
           newFeeCutBps: 10_001,
+          ...UNCHANGED,
         })
         .accounts({
           globalState: globalState.address,
@@ -54,20 +68,113 @@ describe("set global state", () => {
     ).rejects.toThrowProgramError(ErrorCode.FeeTooHigh);
   });
 
-  it("successfully changes the global state", async () => {
-    expect.assertions(3);
+  it("fails if the crank tip is too high", async () => {
+    expect.assertions(1);
+
+    const { feeWallet, protocolFeeBps } = await globalState.fetch();
+
+    await expect(
+      program.methods
+        .setGlobalState({
+          newOwner: authority.publicKey,
+          newFeeWallet: feeWallet,
+          newFeeCutBps: protocolFeeBps.bps,
+          ...UNCHANGED,
+          newCrankTipBps: 10_001,
+        })
+        .accounts({
+          globalState: globalState.address,
+          owner: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.FeeTooHigh);
+  });
+
+  it("fails if the referral share is too high", async () => {
+    expect.assertions(1);
 
     const { feeWallet, protocolFeeBps } = await globalState.fetch();
 
+    await expect(
+      program.methods
+        .setGlobalState({
+          newOwner: authority.publicKey,
+          newFeeWallet: feeWallet,
+          newFeeCutBps: protocolFeeBps.bps,
+          ...UNCHANGED,
+          newReferralBps: 10_001,
+        })
+        .accounts({
+          globalState: globalState.address,
+          owner: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.FeeTooHigh);
+  });
+
+  it("fails if the minimum resolution window exceeds the maximum", async () => {
+    expect.assertions(1);
+
+    const { feeWallet, protocolFeeBps } = await globalState.fetch();
+
+    await expect(
+      program.methods
+        .setGlobalState({
+          newOwner: authority.publicKey,
+          newFeeWallet: feeWallet,
+          newFeeCutBps: protocolFeeBps.bps,
+          ...UNCHANGED,
+          newMinResolutionWindow: RESOLUTION_WINDOW + 1,
+          newMaxResolutionWindow: RESOLUTION_WINDOW,
+        })
+        .accounts({
+          globalState: globalState.address,
+          owner: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidResolutionWindowBounds);
+  });
+
+  it("successfully changes the global state", async () => {
+    expect.assertions(9);
+
+    const {
+      feeWallet,
+      protocolFeeBps,
+      crankTipBps,
+      referralBps,
+      minResolutionWindow,
+      maxResolutionWindow,
+      creationBondLamports,
+      arbiter,
+      disputeBondLamports,
+    } = await globalState.fetch();
+
     const newAuthority = Keypair.generate();
     const newFeeWallet = Keypair.generate();
     const newProtocolFeeBps = protocolFeeBps.bps === 1000 ? 2000 : 1000;
+    const newCrankTipBps = crankTipBps.bps === 1000 ? 2000 : 1000;
+    const newReferralBps = referralBps.bps === 1000 ? 2000 : 1000;
+    const newMinResolutionWindow = minResolutionWindow === 3600 ? 7200 : 3600;
+    const newMaxResolutionWindow = RESOLUTION_WINDOW * 2;
+    const newArbiter = Keypair.generate();
 
     await program.methods
       .setGlobalState({
         newOwner: newAuthority.publicKey,
         newFeeWallet: newFeeWallet.publicKey,
         newFeeCutBps: newProtocolFeeBps,
+        newCrankTipBps,
+        newReferralBps,
+        newMinResolutionWindow,
+        newMaxResolutionWindow,
+        newCreationBondLamports: creationBondLamports,
+        newArbiter: newArbiter.publicKey,
+        newDisputeBondLamports: disputeBondLamports,
+        newTemplateResolverProgram: null,
       })
       .accounts({
         globalState: globalState.address,
@@ -82,6 +189,13 @@ describe("set global state", () => {
       expect(state.authority).toEqualPubkey(newAuthority.publicKey);
       expect(state.feeWallet).toEqualPubkey(newFeeWallet.publicKey);
       expect(state.protocolFeeBps.bps).toBe(newProtocolFeeBps);
+      expect(state.crankTipBps.bps).toBe(newCrankTipBps);
+      expect(state.referralBps.bps).toBe(newReferralBps);
+      expect(state.minResolutionWindow).toBe(newMinResolutionWindow);
+      expect(state.maxResolutionWindow).toBe(newMaxResolutionWindow);
+      expect(state.creationBondLamports).toEqualBN(creationBondLamports);
+      expect(state.arbiter).toEqualPubkey(newArbiter.publicKey);
+      expect(state.disputeBondLamports).toEqualBN(disputeBondLamports);
     } finally {
       // Restore the previous global state, to have minimal impact on other tests.
       await program.methods
@@ -89,6 +203,14 @@ describe("set global state", () => {
           newOwner: authority.publicKey,
           newFeeWallet: feeWallet,
           newFeeCutBps: protocolFeeBps.bps,
+          newCrankTipBps: crankTipBps.bps,
+          newReferralBps: referralBps.bps,
+          newMinResolutionWindow: minResolutionWindow,
+          newMaxResolutionWindow: maxResolutionWindow,
+          newCreationBondLamports: creationBondLamports,
+          newArbiter: arbiter,
+          newDisputeBondLamports: disputeBondLamports,
+          newTemplateResolverProgram: null,
         })
         .accounts({
           globalState: globalState.address,
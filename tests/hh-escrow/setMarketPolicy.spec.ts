@@ -0,0 +1,97 @@
+import type { MarketPolicy } from "./utils";
+
+import { Keypair } from "@solana/web3.js";
+
+import { ErrorCode, program, globalState, RESOLUTION_WINDOW } from "./utils";
+
+// A policy loose enough not to affect the markets of other tests.
+const LOOSE_POLICY: MarketPolicy = {
+  minCloseDelay: 0,
+  maxCloseDelay: 86_400 * 365 * 10,
+  maxExpiryGap: 86_400 * 365 * 10,
+  minResolutionDelay: 0,
+  maxResolutionDelay: RESOLUTION_WINDOW,
+  maxUriLen: 0,
+};
+
+describe("set market policy", () => {
+  const authority = globalState.authority;
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+  });
+
+  it("fails if the authority is incorrect", async () => {
+    expect.assertions(1);
+
+    const wrongAuthority = Keypair.generate();
+
+    await expect(
+      program.methods
+        .setMarketPolicy({ newMarketPolicy: LOOSE_POLICY })
+        .accounts({
+          globalState: globalState.address,
+          owner: wrongAuthority.publicKey,
+        })
+        .signers([wrongAuthority])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.IncorrectGlobalStateOwner);
+  });
+
+  it("fails if a minimum exceeds its maximum", async () => {
+    expect.assertions(1);
+
+    await expect(
+      program.methods
+        .setMarketPolicy({
+          newMarketPolicy: {
+            ...LOOSE_POLICY,
+            minResolutionDelay: 3601,
+            maxResolutionDelay: 3600,
+          },
+        })
+        .accounts({
+          globalState: globalState.address,
+          owner: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidMarketPolicy);
+  });
+
+  it("successfully changes the market policy", async () => {
+    expect.assertions(1);
+
+    const { marketPolicy } = await program.account.globalState.fetch(
+      globalState.address,
+    );
+
+    await program.methods
+      .setMarketPolicy({ newMarketPolicy: LOOSE_POLICY })
+      .accounts({
+        globalState: globalState.address,
+        owner: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+
+    try {
+      const state = await program.account.globalState.fetch(
+        globalState.address,
+      );
+
+      expect(state.marketPolicy).toStrictEqual(LOOSE_POLICY);
+    } finally {
+      // Restore the previous policy, to have minimal impact on other tests.
+      await program.methods
+        .setMarketPolicy({ newMarketPolicy: marketPolicy })
+        .accounts({
+          globalState: globalState.address,
+          owner: authority.publicKey,
+        })
+        .signers([authority])
+        .rpc();
+    }
+  });
+});
@@ -0,0 +1,187 @@
+import type { InitializeMarketParams } from "./utils";
+
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+
+import {
+  intoU64BN,
+  unixTimestamp,
+  createInitMintInstructions,
+  sendTx,
+  spl,
+  getBalance,
+  getAssociatedTokenAddress,
+  createAssociatedTokenAccountInstruction,
+} from "../utils";
+
+import {
+  ErrorCode,
+  program,
+  globalState,
+  getAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "./utils";
+
+const MIN_FILL = 1_000_000n;
+const CREATION_BOND = 500_000n;
+
+describe("set mint policy", () => {
+  const authority = globalState.authority;
+  const mint = Keypair.generate();
+  const mintPolicy = getMintPolicyAddress(mint.publicKey);
+  const creatorTokenAccount = getAssociatedTokenAddress(
+    mint,
+    program.provider.wallet.publicKey,
+  );
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const setMintPolicy = (owner: Keypair, minFill: bigint) =>
+    program.methods
+      .setMintPolicy({
+        newMinFill: intoU64BN(minFill),
+        newCreationBond: intoU64BN(CREATION_BOND),
+      })
+      .accounts({
+        globalState: globalState.address,
+        owner: owner.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy,
+        payer: program.provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([owner]);
+
+  const initMarket = async (market: Keypair, yesAmount: bigint) => {
+    const closeTs = intoU64BN(unixTimestamp() + 3600n);
+    const params: InitializeMarketParams = {
+      closeTs,
+      expiryTs: closeTs.addn(3600),
+      resolutionDelay: 3600,
+      yesAmount: intoU64BN(yesAmount),
+      noAmount: intoU64BN(MIN_FILL),
+      resolver: Keypair.generate().publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+
+    return program.methods
+      .initializeMarket(params)
+      .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
+        market: market.publicKey,
+        authority: getAuthorityAddress(market),
+        creator: program.provider.wallet.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy,
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount,
+        yesTokenAccount: getYesTokenAccountAddress(market)[0],
+        noTokenAccount: getNoTokenAccountAddress(market)[0],
+        systemProgram: SystemProgram.programId,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([market]);
+  };
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    await sendTx(
+      await createInitMintInstructions({
+        mint,
+        mintAuthority: program.provider.wallet.publicKey,
+      }),
+      [mint],
+    );
+
+    // Fund the creator to post creation bonds in the mint.
+    await sendTx(
+      [
+        createAssociatedTokenAccountInstruction({
+          account: creatorTokenAccount,
+          owner: program.provider.wallet.publicKey,
+          mint,
+        }),
+      ],
+      [],
+    );
+    await spl.methods
+      .mintTo(intoU64BN(CREATION_BOND))
+      .accounts({
+        mint: mint.publicKey,
+        authority: program.provider.wallet.publicKey,
+        to: creatorTokenAccount,
+      })
+      .rpc();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the authority is incorrect", async () => {
+    expect.assertions(1);
+
+    await expect(
+      setMintPolicy(Keypair.generate(), MIN_FILL).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.IncorrectGlobalStateOwner);
+  });
+
+  it("successfully sets the mint policy", async () => {
+    expect.assertions(3);
+
+    await setMintPolicy(authority, MIN_FILL).rpc();
+
+    const policy = await program.account.mintPolicy.fetch(mintPolicy);
+
+    expect(policy.tokenMint).toEqualPubkey(mint.publicKey);
+    expect(policy.minFill).toEqualBN(intoU64BN(MIN_FILL));
+    expect(policy.creationBond).toEqualBN(intoU64BN(CREATION_BOND));
+  });
+
+  it("fails to initialize a market below the minimum fill", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await initMarket(Keypair.generate(), MIN_FILL - 1n)).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.FillTooSmall);
+  });
+
+  it("successfully initializes a market at the minimum fill, escrowing the creation bond", async () => {
+    expect.assertions(5);
+
+    const market = Keypair.generate();
+    await (await initMarket(market, MIN_FILL)).rpc();
+
+    const info = await program.account.market.fetch(market.publicKey);
+    expect(info.yesAmount).toEqualBN(intoU64BN(MIN_FILL));
+    expect(info.creationBond).toEqualBN(intoU64BN(CREATION_BOND));
+    expect(info.creationBondInMint).toBe(true);
+    expect(
+      await getBalance(getBondTokenAccountAddress(market.publicKey)),
+    ).toEqualBN(intoU64BN(CREATION_BOND));
+    expect(await getBalance(creatorTokenAccount)).toEqualBN(0);
+  });
+});
@@ -0,0 +1,225 @@
+import type { InitializeMarketParams } from "./utils";
+
+import { Keypair } from "@solana/web3.js";
+
+import {
+  spl,
+  intoU64BN,
+  unixTimestamp,
+  createInitAccountInstructions,
+  createInitMintInstructions,
+  sendTx,
+  getAssociatedTokenAddress,
+} from "../utils";
+
+import {
+  ErrorCode,
+  program,
+  globalState,
+  getAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+const TOP_OFF = 5_000_000n;
+
+// NOTE: Tests in this block have a dependency order.
+describe("set resolver", () => {
+  const market = Keypair.generate();
+  const mint = Keypair.generate();
+  const user = Keypair.generate();
+  const userTokenAccount = Keypair.generate();
+  const resolver = Keypair.generate();
+  const newResolver = Keypair.generate();
+
+  const authority = getAuthorityAddress(market);
+  const [yesTokenAccount] = getYesTokenAccountAddress(market);
+  const [noTokenAccount] = getNoTokenAccountAddress(market);
+  const userPosition = getUserPositionAddress(user, market);
+  const creatorPosition = getUserPositionAddress(
+    program.provider.wallet.publicKey,
+    market,
+  );
+
+  ///////////////////////////////////////////////////////////////////////////////
+
+  const setResolver = () =>
+    program.methods
+      .setResolver({ resolver: newResolver.publicKey })
+      .accounts({
+        market: market.publicKey,
+        creatorPosition,
+        creator: program.provider.wallet.publicKey,
+      });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    await sendTx(
+      [
+        ...(await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        })),
+        ...(await createInitAccountInstructions({
+          account: userTokenAccount,
+          mint,
+          user,
+        })),
+      ],
+      [mint, userTokenAccount],
+    );
+
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    const params: InitializeMarketParams = {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      resolutionDelay: 3600,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: resolver.publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+
+    await sendTx(
+      [
+        await program.methods
+          .initializeMarket(params)
+          .accounts({
+            globalState: globalState.address,
+            ...(await getRegistryAccounts(
+              program.provider.wallet.publicKey,
+              mint.publicKey,
+            )),
+            market: market.publicKey,
+            tokenMint: mint.publicKey,
+            mintPolicy: getMintPolicyAddress(mint.publicKey),
+            bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+            creatorTokenAccount: getAssociatedTokenAddress(
+              mint.publicKey,
+              program.provider.wallet.publicKey,
+            ),
+            authority,
+            yesTokenAccount,
+            noTokenAccount,
+          })
+          .instruction(),
+        await program.methods
+          .initializeUserPosition()
+          .accounts({
+            user: user.publicKey,
+            market: market.publicKey,
+            userPosition,
+          })
+          .instruction(),
+      ],
+      [market, user],
+    );
+
+    await spl.methods
+      .mintTo(intoU64BN(TOP_OFF))
+      .accounts({
+        mint: mint.publicKey,
+        authority: program.provider.wallet.publicKey,
+        to: userTokenAccount.publicKey,
+      })
+      .rpc();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the signer is not the market creator", async () => {
+    expect.assertions(1);
+
+    const wrongCreator = Keypair.generate();
+
+    await expect(
+      setResolver()
+        .accounts({
+          creatorPosition: getUserPositionAddress(wrongCreator, market),
+          creator: wrongCreator.publicKey,
+        })
+        .signers([wrongCreator])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.IncorrectCreator);
+  });
+
+  it("successfully reassigns the resolver", async () => {
+    expect.assertions(1);
+
+    await setResolver().rpc();
+
+    const info = await program.account.market.fetch(market.publicKey);
+
+    expect(info.resolver).toEqual(newResolver.publicKey);
+  });
+
+  it("fails if another user has deposited", async () => {
+    expect.assertions(1);
+
+    await program.methods
+      .deposit({
+        yesAmount: intoU64BN(1n),
+        noAmount: intoU64BN(0n),
+        allowPartial: false,
+        proof: [],
+        referrer: null,
+      })
+      .accounts({
+        market: market.publicKey,
+        user: user.publicKey,
+        userPosition,
+        userTokenAccount: userTokenAccount.publicKey,
+        yesTokenAccount,
+        noTokenAccount,
+      })
+      .signers([user])
+      .rpc();
+
+    await expect(setResolver().rpc()).rejects.toThrowProgramError(
+      ErrorCode.DepositsExist,
+    );
+  });
+
+  it("fails if the resolver has acknowledged the market", async () => {
+    expect.assertions(1);
+
+    await program.methods
+      .resolverAcknowledge()
+      .accounts({
+        market: market.publicKey,
+        resolver: newResolver.publicKey,
+      })
+      .signers([newResolver])
+      .rpc();
+
+    await expect(setResolver().rpc()).rejects.toThrowProgramError(
+      ErrorCode.AlreadyAcknowledged,
+    );
+  });
+});
@@ -0,0 +1,324 @@
+import type { InitializeMarketParams } from "./utils";
+
+import { LangErrorCode } from "@project-serum/anchor";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+
+import {
+  spl,
+  intoU64,
+  intoU64BN,
+  unixTimestamp,
+  getBalance,
+  getAssociatedTokenAddress,
+  createAssociatedTokenAccountInstruction,
+  createInitAccountInstructions,
+  createInitMintInstructions,
+  sendTx,
+} from "../utils";
+
+import {
+  ErrorCode,
+  program,
+  globalState,
+  getAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+const TOP_OFF = 5_000_000n;
+
+describe("settle position", () => {
+  const mint = Keypair.generate();
+  const user = Keypair.generate();
+  const cranker = Keypair.generate();
+  const crankerTokenAccount = Keypair.generate();
+  const resolver = Keypair.generate();
+
+  const userTokenAccount = getAssociatedTokenAddress(mint, user);
+
+  let market: Keypair,
+    authority: PublicKey,
+    yesTokenAccount: PublicKey,
+    noTokenAccount: PublicKey,
+    userPosition: PublicKey;
+
+  let initMarketIx: TransactionInstruction,
+    userPositionIx: TransactionInstruction;
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const initMarketParams = ({
+    resolutionDelay,
+  }: Partial<InitializeMarketParams>): InitializeMarketParams => {
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    resolutionDelay ??= 3600;
+
+    return {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      resolutionDelay,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: resolver.publicKey,
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+  };
+
+  const initMarket = async (params: Partial<InitializeMarketParams>) =>
+    program.methods.initializeMarket(initMarketParams(params)).accounts({
+      globalState: globalState.address,
+      ...(await getRegistryAccounts(
+        program.provider.wallet.publicKey,
+        mint.publicKey,
+      )),
+      market: market.publicKey,
+      authority,
+      creator: program.provider.wallet.publicKey,
+      tokenMint: mint.publicKey,
+      mintPolicy: getMintPolicyAddress(mint.publicKey),
+      bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+      creatorTokenAccount: getAssociatedTokenAddress(
+        mint.publicKey,
+        program.provider.wallet.publicKey,
+      ),
+      yesTokenAccount,
+      noTokenAccount,
+      systemProgram: SystemProgram.programId,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    });
+
+  const settlePosition = () =>
+    program.methods.settlePosition().accounts({
+      globalState: globalState.address,
+      user: user.publicKey,
+      userTokenAccount,
+      crankerTokenAccount: crankerTokenAccount.publicKey,
+      referrerTokenAccount: userTokenAccount,
+      yesTokenAccount,
+      noTokenAccount,
+      userPosition,
+      market: market.publicKey,
+      authority,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      cranker: cranker.publicKey,
+    });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    await sendTx(
+      [
+        ...(await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        })),
+        ...(await createInitAccountInstructions({
+          account: crankerTokenAccount,
+          mint,
+          user: cranker,
+        })),
+        createAssociatedTokenAccountInstruction({
+          account: userTokenAccount,
+          owner: user,
+          mint,
+        }),
+      ],
+      [mint, crankerTokenAccount],
+    );
+  });
+
+  beforeEach(async () => {
+    market = Keypair.generate();
+
+    authority = getAuthorityAddress(market);
+    [yesTokenAccount] = getYesTokenAccountAddress(market);
+    [noTokenAccount] = getNoTokenAccountAddress(market);
+    userPosition = getUserPositionAddress(user, market);
+
+    // Top off the user's token account before each test.
+    const topOff = TOP_OFF - intoU64(await getBalance(userTokenAccount));
+    if (topOff > 0n) {
+      await spl.methods
+        .mintTo(intoU64BN(topOff))
+        .accounts({
+          mint: mint.publicKey,
+          authority: program.provider.wallet.publicKey,
+          to: userTokenAccount,
+        })
+        .rpc();
+    }
+
+    initMarketIx = await (await initMarket({})).instruction();
+
+    userPositionIx = await program.methods
+      .initializeUserPosition()
+      .accounts({
+        user: user.publicKey,
+        market: market.publicKey,
+        userPosition,
+      })
+      .instruction();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the user token account is not the associated token account of the user", async () => {
+    expect.assertions(1);
+
+    const wrongUserTokenAccount = Keypair.generate();
+
+    const preIxs = await createInitAccountInstructions({
+      account: wrongUserTokenAccount,
+      mint,
+      user,
+    });
+
+    await expect(
+      settlePosition()
+        .accounts({ userTokenAccount: wrongUserTokenAccount.publicKey })
+        .preInstructions([initMarketIx, userPositionIx, ...preIxs])
+        .signers([market, user, cranker, wrongUserTokenAccount])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.UserAccountNotAssociated);
+  });
+
+  it("fails if the cranker token account is not owned by the cranker", async () => {
+    expect.assertions(1);
+
+    await expect(
+      settlePosition()
+        .accounts({ crankerTokenAccount: userTokenAccount })
+        .preInstructions([initMarketIx, userPositionIx])
+        .signers([market, user, cranker])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.CrankerAccountIncorrectOwner);
+  });
+
+  it("fails if the user position does not belong to the user", async () => {
+    expect.assertions(1);
+
+    const wrongUser = Keypair.generate();
+    const wrongUserPosition = getUserPositionAddress(wrongUser, market);
+
+    const preIxs = [
+      await program.methods
+        .initializeUserPosition()
+        .accounts({
+          user: wrongUser.publicKey,
+          market: market.publicKey,
+          userPosition: wrongUserPosition,
+        })
+        .instruction(),
+    ];
+
+    await expect(
+      settlePosition()
+        .accounts({ userPosition: wrongUserPosition })
+        .preInstructions([initMarketIx, userPositionIx, ...preIxs])
+        .signers([market, user, wrongUser, cranker])
+        .rpc(),
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintSeeds);
+  });
+
+  it("fails if the market is not finalized", async () => {
+    expect.assertions(1);
+
+    await expect(
+      settlePosition()
+        .preInstructions([initMarketIx, userPositionIx])
+        .signers([market, user, cranker])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.NotFinalized);
+  });
+
+  it("successfully refunds a position in an invalid market", async () => {
+    expect.assertions(6);
+
+    // Instantly finalize.
+    const initMarketIx = await (
+      await initMarket({ resolutionDelay: 0 })
+    ).instruction();
+
+    const yesDeposit = 1n;
+    const noDeposit = 2n;
+
+    await program.methods
+      .deposit({
+        yesAmount: intoU64BN(yesDeposit),
+        noAmount: intoU64BN(noDeposit),
+        allowPartial: true,
+        proof: [],
+        referrer: null,
+      })
+      .accounts({
+        user: user.publicKey,
+        market: market.publicKey,
+        yesTokenAccount,
+        noTokenAccount,
+        userTokenAccount,
+        userPosition,
+      })
+      .preInstructions([initMarketIx, userPositionIx])
+      .signers([market, user])
+      .rpc();
+
+    await program.methods
+      .updateState({ outcome: { Invalid: {} } })
+      .accounts({
+        market: market.publicKey,
+        resolver: resolver.publicKey,
+      })
+      .signers([resolver])
+      .rpc();
+
+    await settlePosition().signers([cranker]).rpc();
+
+    await expect(yesTokenAccount).toHaveBalance(0n);
+    await expect(noTokenAccount).toHaveBalance(0n);
+    await expect(userTokenAccount).toHaveBalance(TOP_OFF);
+
+    const { yesAmount, noAmount } = await program.account.userPosition.fetch(
+      userPosition,
+    );
+
+    expect(yesAmount).toEqualBN(0n);
+    expect(noAmount).toEqualBN(0n);
+
+    const { finalized } = await program.account.market.fetch(
+      market.publicKey,
+    );
+
+    expect(finalized).toBe(true);
+  });
+});
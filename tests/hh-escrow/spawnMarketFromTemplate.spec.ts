@@ -0,0 +1,263 @@
+import type { InitializeMarketTemplateParams } from "./utils";
+
+import { LangErrorCode } from "@project-serum/anchor";
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+
+import {
+  intoU64BN,
+  unixTimestamp,
+  createInitMintInstructions,
+  sendTx,
+  getAssociatedTokenAddress,
+} from "../utils";
+
+import {
+  ErrorCode,
+  program,
+  globalState,
+  interpretMarketResource,
+  interpretMarketTitle,
+  getAuthorityAddress,
+  getSpawnAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  getMarketAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+const CADENCE = 7n * 86_400n;
+
+// NOTE: Tests in this block have a dependency order.
+describe("spawn market from template", () => {
+  const mint = Keypair.generate();
+  const template = Keypair.generate();
+  const resolver = Keypair.generate();
+  const cranker = Keypair.generate();
+
+  const closeTs = unixTimestamp() + 3600n;
+  const expiryTs = closeTs + 3600n;
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const initTemplateParams = ({
+    closeTs: closeTs_,
+    expiryTs: expiryTs_,
+    cadence,
+    resolutionDelay,
+    yesAmount,
+    noAmount,
+    resolver: resolver_,
+    resolverProgram,
+    resolverData,
+    uriPrefix,
+    title,
+    category,
+    yesLimits,
+    noLimits,
+    requireAcknowledgement,
+    allowEarlyResolution,
+    maxResolutionWindow,
+    resolverBond,
+  }: Partial<InitializeMarketTemplateParams>): InitializeMarketTemplateParams => {
+    closeTs_ ??= intoU64BN(closeTs);
+    expiryTs_ ??= intoU64BN(expiryTs);
+    cadence ??= intoU64BN(CADENCE);
+    resolutionDelay ??= 3600;
+    yesAmount ??= intoU64BN(YES_AMOUNT);
+    noAmount ??= intoU64BN(NO_AMOUNT);
+    resolver_ ??= resolver.publicKey;
+    resolverProgram ??= null;
+    resolverData ??= Buffer.alloc(0);
+    uriPrefix ??= "https://example.com/weekly/";
+    title ??= "Weekly market";
+    category ??= encodeCategory("test");
+    yesLimits ??= NO_DEPOSIT_LIMITS;
+    noLimits ??= NO_DEPOSIT_LIMITS;
+    requireAcknowledgement ??= false;
+    allowEarlyResolution ??= false;
+    maxResolutionWindow ??= RESOLUTION_WINDOW;
+    resolverBond ??= intoU64BN(0);
+
+    return {
+      closeTs: closeTs_,
+      expiryTs: expiryTs_,
+      cadence,
+      resolutionDelay,
+      yesAmount,
+      noAmount,
+      resolver: resolver_,
+      resolverProgram,
+      resolverData,
+      uriPrefix,
+      title,
+      category,
+      yesLimits,
+      noLimits,
+      requireAcknowledgement,
+      allowEarlyResolution,
+      maxResolutionWindow,
+      resolverBond,
+    };
+  };
+
+  const initTemplate = (
+    template: Keypair,
+    params: Partial<InitializeMarketTemplateParams>,
+  ) =>
+    program.methods
+      .initializeMarketTemplate(initTemplateParams(params))
+      .accounts({
+        globalState: globalState.address,
+        template: template.publicKey,
+        creator: program.provider.wallet.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+      })
+      .signers([template]);
+
+  const spawnMarket = async (market: PublicKey) =>
+    program.methods
+      .spawnMarketFromTemplate()
+      .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
+        template: template.publicKey,
+        market,
+        authority: getAuthorityAddress(market),
+        spawnAuthority: getSpawnAuthorityAddress(market),
+        payer: cranker.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market),
+        payerTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          cranker.publicKey,
+        ),
+        yesTokenAccount: getYesTokenAccountAddress(market)[0],
+        noTokenAccount: getNoTokenAccountAddress(market)[0],
+      })
+      .signers([cranker]);
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
+    await sendTx(
+      [
+        ...(await createInitMintInstructions({
+          mint,
+          mintAuthority: program.provider.wallet.publicKey,
+        })),
+        // Fund the cranker, who pays for the spawned market accounts.
+        SystemProgram.transfer({
+          fromPubkey: program.provider.wallet.publicKey,
+          toPubkey: cranker.publicKey,
+          lamports: 1_000_000_000,
+        }),
+      ],
+      [mint],
+    );
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the cadence is zero", async () => {
+    expect.assertions(1);
+
+    await expect(
+      initTemplate(Keypair.generate(), { cadence: intoU64BN(0) }).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidTemplateCadence);
+  });
+
+  it("fails if the URI prefix is invalid", async () => {
+    expect.assertions(1);
+
+    await expect(
+      initTemplate(Keypair.generate(), {
+        uriPrefix: "example.com/weekly/",
+      }).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidMarketResource);
+  });
+
+  it("fails if the resolver program is not allowed", async () => {
+    expect.assertions(1);
+
+    await expect(
+      initTemplate(Keypair.generate(), {
+        resolverProgram: Keypair.generate().publicKey,
+      }).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.ResolverProgramNotAllowed);
+  });
+
+  it("successfully initializes the template", async () => {
+    expect.assertions(5);
+
+    await initTemplate(template, {}).rpc();
+
+    const info = await program.account.marketTemplate.fetch(
+      template.publicKey,
+    );
+
+    expect(info.creator).toEqualPubkey(program.provider.wallet.publicKey);
+    expect(info.tokenMint).toEqualPubkey(mint.publicKey);
+    expect(info.closeTs).toEqualBN(closeTs);
+    expect(info.cadence).toEqualBN(CADENCE);
+    expect(info.spawned).toEqualBN(0);
+  });
+
+  it("fails if the market is not at the next index", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await spawnMarket(getMarketAddress(template.publicKey, 1))).rpc(),
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintSeeds);
+  });
+
+  it("successfully spawns the first market", async () => {
+    expect.assertions(9);
+
+    const market = getMarketAddress(template.publicKey, 0);
+
+    await (await spawnMarket(market)).rpc();
+
+    const info = await program.account.market.fetch(market);
+
+    expect(info.creator).toEqualPubkey(program.provider.wallet.publicKey);
+    expect(info.resolver).toEqualPubkey(resolver.publicKey);
+    expect(info.closeTs).toEqualBN(closeTs);
+    expect(info.expiryTs).toEqualBN(expiryTs);
+    expect(interpretMarketResource(info.metadata.uri)).toBe(
+      "https://example.com/weekly/0",
+    );
+    expect(interpretMarketTitle(info.metadata.title)).toBe("Weekly market");
+
+    const templateInfo = await program.account.marketTemplate.fetch(
+      template.publicKey,
+    );
+
+    expect(templateInfo.closeTs).toEqualBN(closeTs + CADENCE);
+    expect(templateInfo.expiryTs).toEqualBN(expiryTs + CADENCE);
+    expect(templateInfo.spawned).toEqualBN(1);
+  });
+
+  it("fails to spawn the next market before the first market closes", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await spawnMarket(getMarketAddress(template.publicKey, 1))).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.TemplateNotDue);
+  });
+});
@@ -20,15 +20,23 @@ import {
   sendTx,
   chain,
   __throw,
+  getAssociatedTokenAddress,
 } from "../utils";
 
 import {
   ErrorCode,
   program,
+  globalState,
   getAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = intoU64BN(100n);
@@ -70,6 +78,16 @@ describeFlaky("update outcome (clock-dependent)", () => {
     noAmount,
     resolver: resolver_,
     uri,
+    title,
+    category,
+    contentHash,
+    yesLimits,
+    noLimits,
+    allowlistRoot,
+    requireAcknowledgement,
+    allowEarlyResolution,
+    maxResolutionWindow,
+    resolverBond,
   }: Partial<InitializeMarketParams>) => {
     closeTs ??= intoU64BN(unixTimestamp() + 3600n);
     expiryTs ??= closeTs.addn(3600);
@@ -77,7 +95,17 @@ describeFlaky("update outcome (clock-dependent)", () => {
     yesAmount ??= YES_AMOUNT;
     noAmount ??= NO_AMOUNT;
     resolver_ ??= resolver.publicKey;
-    uri ??= "0".repeat(200);
+    uri ??= "https://example.com/market.json";
+    title ??= "Test market";
+    category ??= encodeCategory("test");
+    contentHash ??= new Array(32).fill(0);
+    yesLimits ??= NO_DEPOSIT_LIMITS;
+    noLimits ??= NO_DEPOSIT_LIMITS;
+    allowlistRoot ??= null;
+    requireAcknowledgement ??= false;
+    allowEarlyResolution ??= false;
+    maxResolutionWindow ??= RESOLUTION_WINDOW;
+    resolverBond ??= intoU64BN(0);
 
     return program.methods
       .initializeMarket({
@@ -88,12 +116,33 @@ describeFlaky("update outcome (clock-dependent)", () => {
         noAmount,
         resolver: resolver_,
         uri,
+        title,
+        category,
+        contentHash,
+        yesLimits,
+        noLimits,
+        allowlistRoot,
+        requireAcknowledgement,
+        allowEarlyResolution,
+        maxResolutionWindow,
+        resolverBond,
       })
       .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
         market: market.publicKey,
         authority,
         creator: program.provider.wallet.publicKey,
         tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
         yesTokenAccount,
         noTokenAccount,
         systemProgram: SystemProgram.programId,
@@ -104,6 +153,9 @@ describeFlaky("update outcome (clock-dependent)", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     await sendTx(
       [
         ...(await createInitMintInstructions({
@@ -157,6 +209,8 @@ describeFlaky("update outcome (clock-dependent)", () => {
         yesAmount: YES_AMOUNT,
         noAmount: NO_AMOUNT,
         allowPartial: true,
+        proof: [],
+        referrer: null,
       })
       .accounts({
         market: market.publicKey,
@@ -251,6 +305,69 @@ describeFlaky("update outcome (clock-dependent)", () => {
     expect(info.outcome).toStrictEqual<Outcome>({ Invalid: {} });
   });
 
+  it("successfully updates to yes before market has expired if early resolution is allowed", async () => {
+    expect.assertions(3);
+
+    const time = await chain.blockTimestamp();
+
+    const preIxs = [
+      await initMarket({
+        closeTs: intoU64BN(time + 3600),
+        allowEarlyResolution: true,
+        maxResolutionWindow: RESOLUTION_WINDOW,
+      }).instruction(),
+    ];
+
+    await program.methods
+      .updateState({ outcome: { Yes: {} } })
+      .accounts({
+        market: market.publicKey,
+        resolver: resolver.publicKey,
+      })
+      .preInstructions(preIxs)
+      .signers([market, resolver])
+      .rpc();
+
+    const info = await program.account.market.fetch(market.publicKey);
+
+    expect(info.outcomeTs).toEqualBN(time);
+    expect(info.closeTs).toEqualBN(time);
+    expect(info.outcome).toStrictEqual<Outcome>({ Yes: {} });
+  });
+
+  it("fails to change an early resolution before market has expired", async () => {
+    expect.assertions(1);
+
+    const time = await chain.blockTimestamp();
+
+    const preIxs = [
+      await initMarket({
+        closeTs: intoU64BN(time + 3600),
+        allowEarlyResolution: true,
+        maxResolutionWindow: RESOLUTION_WINDOW,
+      }).instruction(),
+      await program.methods
+        .updateState({ outcome: { Yes: {} } })
+        .accounts({
+          market: market.publicKey,
+          resolver: resolver.publicKey,
+        })
+        .instruction(),
+    ];
+
+    await expect(
+      program.methods
+        .updateState({ outcome: { No: {} } })
+        .accounts({
+          market: market.publicKey,
+          resolver: resolver.publicKey,
+        })
+        .preInstructions(preIxs)
+        .signers([market, resolver])
+        .rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.InvalidTransition);
+  });
+
   it("successfully updates to open before market has expired", async () => {
     expect.assertions(2);
 
@@ -4,6 +4,7 @@ import type { Address } from "../utils";
 
 import { Program } from "@project-serum/anchor";
 import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+import { keccak_256 } from "js-sha3";
 
 import {
   ProgramError,
@@ -15,19 +16,44 @@ import {
   translateAddress,
   getAssociatedTokenAddress,
   sendTx,
+  intoU64BN,
 } from "../utils";
 
 type EscrowTypes = IdlTypes<HhEscrow>;
 
 export type InitializeMarketParams = EscrowTypes["InitializeMarketParams"];
+export type InitializeMarketTemplateParams =
+  EscrowTypes["InitializeMarketTemplateParams"];
 export type UriResource = EscrowTypes["UriResource"];
+export type MarketTitle = EscrowTypes["MarketTitle"];
+export type DepositLimits = EscrowTypes["DepositLimits"];
 export type DepositParams = EscrowTypes["DepositParams"];
 export type UpdateStateParams = EscrowTypes["UpdateStateParams"];
 export type Outcome = EscrowTypes["Outcome"];
+export type MarketPolicy = EscrowTypes["MarketPolicy"];
 
 export const program = new Program(ESCROW_PROGRAM_IDL, ESCROW_PROGRAM_ID);
 export const ErrorCode = parseErrorCodes(program.idl.errors);
 
+/**
+ * Deposit limits that do not restrict deposits.
+ */
+export const NO_DEPOSIT_LIMITS: DepositLimits = {
+  minDeposit: intoU64BN(0),
+  maxPerUser: intoU64BN(0),
+};
+
+/**
+ * The maximum resolution window of a new market when the global state does
+ * not configure it.
+ */
+export const DEFAULT_MAX_RESOLUTION_WINDOW = 86_400 * 30;
+
+/**
+ * The resolution window of test markets.
+ */
+export const RESOLUTION_WINDOW = DEFAULT_MAX_RESOLUTION_WINDOW;
+
 export const [programData] = PublicKey.findProgramAddressSync(
   [program.programId.toBytes()],
   BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
@@ -59,9 +85,33 @@ export const globalState = (() => {
     },
 
     async fetch() {
-      const { feeWallet, owner, feeCutBps } =
-        await program.account.globalState.fetch(address);
-      return { authority: owner, feeWallet, protocolFeeBps: feeCutBps };
+      const {
+        feeWallet,
+        owner,
+        feeCutBps,
+        crankTipBps,
+        referralBps,
+        minResolutionWindow,
+        maxResolutionWindow,
+        creationBondLamports,
+        arbiter,
+        disputeBondLamports,
+      } = await program.account.globalState.fetch(address);
+      return {
+        authority: owner,
+        feeWallet,
+        protocolFeeBps: feeCutBps,
+        crankTipBps,
+        referralBps,
+        minResolutionWindow,
+        maxResolutionWindow:
+          maxResolutionWindow === 0
+            ? DEFAULT_MAX_RESOLUTION_WINDOW
+            : maxResolutionWindow,
+        creationBondLamports,
+        arbiter,
+        disputeBondLamports,
+      };
     },
 
     async getFeeWallet(): Promise<PublicKey> {
@@ -70,6 +120,9 @@ export const globalState = (() => {
     async getProtocolFeeBps(): Promise<number> {
       return (await this.fetch()).protocolFeeBps.bps;
     },
+    async getCrankTipBps(): Promise<number> {
+      return (await this.fetch()).crankTipBps.bps;
+    },
 
     async initialize(): Promise<void> {
       const state = await program.account.globalState.fetchNullable(address);
@@ -87,12 +140,14 @@ export const globalState = (() => {
         program.programId,
       );
       const protocolFeeBps = 100; // 1%.
+      const crankTipBps = 1000; // 10% of the protocol fee.
 
       try {
         await sendTx(
           await program.methods
             .initializeGlobalState({
               protocolFeeBps,
+              crankTipBps,
             })
             .accounts({
               globalState: address,
@@ -118,6 +173,27 @@ export const globalState = (() => {
       }
     },
 
+    async allowTemplateResolverProgram(resolverProgram: Address) {
+      const { feeWallet, protocolFeeBps } = await this.fetch();
+      await program.methods
+        .setGlobalState({
+          newOwner: authority.publicKey,
+          newFeeCutBps: protocolFeeBps.bps,
+          newFeeWallet: feeWallet,
+          newCrankTipBps: null,
+          newReferralBps: null,
+          newMinResolutionWindow: null,
+          newMaxResolutionWindow: null,
+          newCreationBondLamports: null,
+          newArbiter: null,
+          newDisputeBondLamports: null,
+          newTemplateResolverProgram: translateAddress(resolverProgram),
+        })
+        .accounts({ globalState: address, owner: authority.publicKey })
+        .signers([authority])
+        .rpc();
+    },
+
     async getFeeAccountFor(mint: Address): Promise<PublicKey> {
       return getAssociatedTokenAddress(mint, await this.getFeeWallet(), true);
     },
@@ -132,6 +208,146 @@ export function interpretMarketResource({ len, uri }: UriResource): string {
   return buf.toString("utf8");
 }
 
+/**
+ * Interpret the on-chain representation of a `MarketTitle` as a string.
+ */
+export function interpretMarketTitle({ len, title }: MarketTitle): string {
+  const buf = Buffer.from(title).subarray(0, len);
+  return buf.toString("utf8");
+}
+
+/**
+ * Encodes a category tag as its on-chain representation, padded with zeroes.
+ */
+export function encodeCategory(category: string): number[] {
+  const buf = Buffer.alloc(16);
+  buf.write(category, "utf8");
+  return [...buf];
+}
+
+/**
+ * Gets the address of the policy of a given token mint.
+ */
+export function getMintPolicyAddress(mint: Address): PublicKey {
+  const [policy] = PublicKey.findProgramAddressSync(
+    [Buffer.from("mint_policy"), translateAddress(mint).toBuffer()],
+    program.programId,
+  );
+  return policy;
+}
+
+/**
+ * Gets the address of the market counter for a given creator.
+ */
+export function getMarketCounterAddress(creator: Address): PublicKey {
+  const [counter] = PublicKey.findProgramAddressSync(
+    [Buffer.from("counter"), translateAddress(creator).toBuffer()],
+    program.programId,
+  );
+  return counter;
+}
+
+/**
+ * Gets the address of the market created by a given creator with a given
+ * nonce, or spawned from a given template at a given index.
+ */
+export function getMarketAddress(
+  creator: Address,
+  nonce: number | bigint,
+): PublicKey {
+  const nonceBuf = Buffer.alloc(8);
+  nonceBuf.writeBigUInt64LE(BigInt(nonce));
+  const [market] = PublicKey.findProgramAddressSync(
+    [Buffer.from("market"), translateAddress(creator).toBuffer(), nonceBuf],
+    program.programId,
+  );
+  return market;
+}
+
+/**
+ * Gets the address of the market registry of a given creator or token mint.
+ */
+export function getRegistryAddress(
+  kind: "creator" | "mint",
+  key: Address,
+): PublicKey {
+  const [registry] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("registry"),
+      Buffer.from(kind),
+      translateAddress(key).toBuffer(),
+    ],
+    program.programId,
+  );
+  return registry;
+}
+
+/**
+ * Gets the address of a given page of a market registry.
+ */
+export function getRegistryPageAddress(
+  registry: Address,
+  page: number,
+): PublicKey {
+  const pageBuf = Buffer.alloc(4);
+  pageBuf.writeUInt32LE(page);
+  const [registryPage] = PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("registry_page"),
+      translateAddress(registry).toBuffer(),
+      pageBuf,
+    ],
+    program.programId,
+  );
+  return registryPage;
+}
+
+/**
+ * Gets the registry accounts a new market of a given creator and token mint
+ * is appended to.
+ */
+export async function getRegistryAccounts(
+  creator: Address,
+  mint: Address,
+): Promise<{
+  creatorRegistry: PublicKey;
+  creatorRegistryPage: PublicKey;
+  mintRegistry: PublicKey;
+  mintRegistryPage: PublicKey;
+}> {
+  const tailPage = async (registry: PublicKey) =>
+    (await program.account.marketRegistry.fetchNullable(registry))
+      ?.tailPage ?? 0;
+
+  const creatorRegistry = getRegistryAddress("creator", creator);
+  const mintRegistry = getRegistryAddress("mint", mint);
+
+  return {
+    creatorRegistry,
+    creatorRegistryPage: getRegistryPageAddress(
+      creatorRegistry,
+      await tailPage(creatorRegistry),
+    ),
+    mintRegistry,
+    mintRegistryPage: getRegistryPageAddress(
+      mintRegistry,
+      await tailPage(mintRegistry),
+    ),
+  };
+}
+
+/**
+ * Gets the address of the signer proving to a resolver program that a given
+ * market is spawned from a template.
+ */
+export function getSpawnAuthorityAddress(market: Address): PublicKey {
+  const [spawnAuthority] = PublicKey.findProgramAddressSync(
+    [Buffer.from("spawn"), translateAddress(market).toBuffer()],
+    program.programId,
+  );
+  return spawnAuthority;
+}
+
 /**
  * Gets the address of the authority account for a given market.
  */
@@ -155,6 +371,28 @@ export function getYesTokenAccountAddress(
   );
 }
 
+/**
+ * Gets the address of the creation bond escrow for a given market.
+ */
+export function getBondTokenAccountAddress(market: Address): PublicKey {
+  const [bond] = PublicKey.findProgramAddressSync(
+    [Buffer.from("bond"), translateAddress(market).toBuffer()],
+    program.programId,
+  );
+  return bond;
+}
+
+/**
+ * Gets the address of the resolver bond escrow for a given market.
+ */
+export function getResolverBondTokenAccountAddress(market: Address): PublicKey {
+  const [bond] = PublicKey.findProgramAddressSync(
+    [Buffer.from("resolver_bond"), translateAddress(market).toBuffer()],
+    program.programId,
+  );
+  return bond;
+}
+
 /**
  * Gets the address of the no token account for a given market.
  */
@@ -182,3 +420,55 @@ export function getUserPositionAddress(
   );
   return userPosition;
 }
+
+/**
+ * A Merkle tree over an allowlist of users, matching the on-chain
+ * verification in `common::merkle`.
+ */
+export class Allowlist {
+  private readonly layers: Buffer[][];
+
+  constructor(users: Address[]) {
+    let layer = users.map((user) =>
+      Buffer.from(keccak_256.arrayBuffer(translateAddress(user).toBuffer())),
+    );
+    this.layers = [layer];
+    while (layer.length > 1) {
+      const next = [];
+      for (let i = 0; i < layer.length; i += 2) {
+        next.push(
+          i + 1 < layer.length ? hashPair(layer[i], layer[i + 1]) : layer[i],
+        );
+      }
+      this.layers.push(next);
+      layer = next;
+    }
+  }
+
+  /**
+   * The Merkle root of the allowlist.
+   */
+  get root(): number[] {
+    return [...this.layers[this.layers.length - 1][0]];
+  }
+
+  /**
+   * Gets the proof for the user at the given index.
+   */
+  getProof(index: number): number[][] {
+    const proof = [];
+    for (const layer of this.layers.slice(0, -1)) {
+      const sibling = index ^ 1;
+      if (sibling < layer.length) {
+        proof.push([...layer[sibling]]);
+      }
+      index >>= 1;
+    }
+    return proof;
+  }
+}
+
+function hashPair(a: Buffer, b: Buffer): Buffer {
+  const [first, second] = Buffer.compare(a, b) <= 0 ? [a, b] : [b, a];
+  return Buffer.from(keccak_256.arrayBuffer(Buffer.concat([first, second])));
+}
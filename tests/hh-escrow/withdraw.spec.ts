@@ -15,6 +15,8 @@ import {
   intoU64BN,
   unixTimestamp,
   getBalance,
+  getAssociatedTokenAddress,
+  createAssociatedTokenAccountInstruction,
   createInitAccountInstructions,
   createInitMintInstructions,
   sendTx,
@@ -24,10 +26,17 @@ import {
 import {
   ErrorCode,
   program,
+  globalState,
   getAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = 1_000_000n;
@@ -38,7 +47,7 @@ const TOP_OFF = 5_000_000n;
 describe("withdraw", () => {
   const mint = Keypair.generate();
   const user = Keypair.generate();
-  const userTokenAccount = Keypair.generate();
+  const userTokenAccount = getAssociatedTokenAddress(mint, user);
   const resolver = Keypair.generate();
 
   let market: Keypair,
@@ -57,7 +66,9 @@ describe("withdraw", () => {
       user: user.publicKey,
       yesTokenAccount,
       noTokenAccount,
-      userTokenAccount: userTokenAccount.publicKey,
+      destination: user.publicKey,
+      userTokenAccount,
+      tokenMint: mint.publicKey,
       authority,
       market: market.publicKey,
       userPosition,
@@ -67,19 +78,22 @@ describe("withdraw", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     await sendTx(
       [
         ...(await createInitMintInstructions({
           mint,
           mintAuthority: program.provider.wallet.publicKey,
         })),
-        ...(await createInitAccountInstructions({
+        createAssociatedTokenAccountInstruction({
           account: userTokenAccount,
+          owner: user,
           mint,
-          user,
-        })),
+        }),
       ],
-      [mint, userTokenAccount],
+      [mint],
     );
   });
 
@@ -99,7 +113,7 @@ describe("withdraw", () => {
         .accounts({
           mint: mint.publicKey,
           authority: program.provider.wallet.publicKey,
-          to: userTokenAccount.publicKey,
+          to: userTokenAccount,
         })
         .rpc();
     }
@@ -114,16 +128,37 @@ describe("withdraw", () => {
       yesAmount: intoU64BN(YES_AMOUNT),
       noAmount: intoU64BN(NO_AMOUNT),
       resolver: resolver.publicKey,
-      uri: "0".repeat(200),
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
     };
 
     initMarketIx = await program.methods
       .initializeMarket(params)
       .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
         market: market.publicKey,
         authority,
         creator: program.provider.wallet.publicKey,
         tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
         yesTokenAccount,
         noTokenAccount,
         systemProgram: SystemProgram.programId,
@@ -229,7 +264,7 @@ describe("withdraw", () => {
         .preInstructions([initMarketIx, userPositionIx])
         .signers([market, user])
         .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.UserAccountCannotBeMarketAccount);
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintTokenOwner);
   });
 
   it("fails if the user token account provided is the no token account", async () => {
@@ -241,10 +276,10 @@ describe("withdraw", () => {
         .preInstructions([initMarketIx, userPositionIx])
         .signers([market, user])
         .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.UserAccountCannotBeMarketAccount);
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintTokenOwner);
   });
 
-  it("fails if the user token account is not owned by the user", async () => {
+  it("fails if the user token account is not owned by the destination", async () => {
     expect.assertions(1);
 
     const otherUser = Keypair.generate();
@@ -262,7 +297,7 @@ describe("withdraw", () => {
         .preInstructions([initMarketIx, userPositionIx, ...newTokenAccountIxs])
         .signers([market, user, otherTokenAccount])
         .rpc(),
-    ).rejects.toThrowProgramError(ErrorCode.UserAccountIncorrectOwner);
+    ).rejects.toThrowProgramError(LangErrorCode.ConstraintTokenOwner);
   });
 
   it("successfully withdraws tokens for a user", async () => {
@@ -278,16 +313,37 @@ describe("withdraw", () => {
       yesAmount: intoU64BN(YES_AMOUNT),
       noAmount: intoU64BN(NO_AMOUNT),
       resolver: resolver.publicKey,
-      uri: "0".repeat(200),
+      uri: "https://example.com/market.json",
+      title: "Test market",
+      category: encodeCategory("test"),
+      contentHash: new Array(32).fill(0),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      allowlistRoot: null,
+      requireAcknowledgement: false,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
     };
 
     const initMarketIx = await program.methods
       .initializeMarket(params)
       .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
         market: market.publicKey,
         authority,
         creator: program.provider.wallet.publicKey,
         tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
         yesTokenAccount,
         noTokenAccount,
         systemProgram: SystemProgram.programId,
@@ -303,13 +359,15 @@ describe("withdraw", () => {
         yesAmount: intoU64BN(yesDeposit),
         noAmount: intoU64BN(noDeposit),
         allowPartial: true,
+        proof: [],
+        referrer: null,
       })
       .accounts({
         user: user.publicKey,
         market: market.publicKey,
         yesTokenAccount,
         noTokenAccount,
-        userTokenAccount: userTokenAccount.publicKey,
+        userTokenAccount,
         userPosition,
       })
       .preInstructions([initMarketIx, userPositionIx])
@@ -15,7 +15,8 @@ import {
   intoU64BN,
   unixTimestamp,
   getBalance,
-  createInitAccountInstructions,
+  getAssociatedTokenAddress,
+  createAssociatedTokenAccountInstruction,
   createInitMintInstructions,
   sendTx,
   chain,
@@ -25,10 +26,17 @@ import {
 import {
   ErrorCode,
   program,
+  globalState,
   getAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "./utils";
 
 const YES_AMOUNT = intoU64BN(1_000_000n);
@@ -47,7 +55,7 @@ const describeFlaky = SKIP_FLAKY ? describe.skip : describe;
 describeFlaky("withdraw (clock-dependent)", () => {
   const mint = Keypair.generate();
   const user = Keypair.generate();
-  const userTokenAccount = Keypair.generate();
+  const userTokenAccount = getAssociatedTokenAddress(mint, user);
   const resolver = Keypair.generate();
 
   let market: Keypair,
@@ -68,6 +76,16 @@ describeFlaky("withdraw (clock-dependent)", () => {
     noAmount,
     resolver: resolver_,
     uri,
+    title,
+    category,
+    contentHash,
+    yesLimits,
+    noLimits,
+    allowlistRoot,
+    requireAcknowledgement,
+    allowEarlyResolution,
+    maxResolutionWindow,
+    resolverBond,
   }: Partial<InitializeMarketParams>) => {
     closeTs ??= intoU64BN(unixTimestamp() + 3600n);
     expiryTs ??= closeTs.addn(3600);
@@ -75,7 +93,17 @@ describeFlaky("withdraw (clock-dependent)", () => {
     yesAmount ??= YES_AMOUNT;
     noAmount ??= NO_AMOUNT;
     resolver_ ??= resolver.publicKey;
-    uri ??= "0".repeat(200);
+    uri ??= "https://example.com/market.json";
+    title ??= "Test market";
+    category ??= encodeCategory("test");
+    contentHash ??= new Array(32).fill(0);
+    yesLimits ??= NO_DEPOSIT_LIMITS;
+    noLimits ??= NO_DEPOSIT_LIMITS;
+    allowlistRoot ??= null;
+    requireAcknowledgement ??= false;
+    allowEarlyResolution ??= false;
+    maxResolutionWindow ??= RESOLUTION_WINDOW;
+    resolverBond ??= intoU64BN(0);
 
     return program.methods
       .initializeMarket({
@@ -86,12 +114,33 @@ describeFlaky("withdraw (clock-dependent)", () => {
         noAmount,
         resolver: resolver_,
         uri,
+        title,
+        category,
+        contentHash,
+        yesLimits,
+        noLimits,
+        allowlistRoot,
+        requireAcknowledgement,
+        allowEarlyResolution,
+        maxResolutionWindow,
+        resolverBond,
       })
       .accounts({
+        globalState: globalState.address,
+        ...(await getRegistryAccounts(
+          program.provider.wallet.publicKey,
+          mint.publicKey,
+        )),
         market: market.publicKey,
         authority,
         creator: program.provider.wallet.publicKey,
         tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+        bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+        creatorTokenAccount: getAssociatedTokenAddress(
+          mint.publicKey,
+          program.provider.wallet.publicKey,
+        ),
         yesTokenAccount,
         noTokenAccount,
         systemProgram: SystemProgram.programId,
@@ -104,7 +153,9 @@ describeFlaky("withdraw (clock-dependent)", () => {
       user: user.publicKey,
       yesTokenAccount,
       noTokenAccount,
-      userTokenAccount: userTokenAccount.publicKey,
+      destination: user.publicKey,
+      userTokenAccount,
+      tokenMint: mint.publicKey,
       authority,
       market: market.publicKey,
       userPosition,
@@ -114,19 +165,22 @@ describeFlaky("withdraw (clock-dependent)", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     await sendTx(
       [
         ...(await createInitMintInstructions({
           mint,
           mintAuthority: program.provider.wallet.publicKey,
         })),
-        ...(await createInitAccountInstructions({
+        createAssociatedTokenAccountInstruction({
           account: userTokenAccount,
+          owner: user,
           mint,
-          user,
-        })),
+        }),
       ],
-      [mint, userTokenAccount],
+      [mint],
     );
   });
 
@@ -146,7 +200,7 @@ describeFlaky("withdraw (clock-dependent)", () => {
         .accounts({
           mint: mint.publicKey,
           authority: program.provider.wallet.publicKey,
-          to: userTokenAccount.publicKey,
+          to: userTokenAccount,
         })
         .rpc();
     }
@@ -165,12 +219,14 @@ describeFlaky("withdraw (clock-dependent)", () => {
         yesAmount: YES_AMOUNT,
         noAmount: NO_AMOUNT,
         allowPartial: true,
+        proof: [],
+        referrer: null,
       })
       .accounts({
         market: market.publicKey,
         user: user.publicKey,
         userPosition,
-        userTokenAccount: userTokenAccount.publicKey,
+        userTokenAccount,
         yesTokenAccount,
         noTokenAccount,
         tokenProgram: TOKEN_PROGRAM_ID,
@@ -11,12 +11,20 @@ import {
   createInitMintInstructions,
   sendTx,
   chain,
+  getAssociatedTokenAddress,
 } from "../utils";
 import {
   program as escrowProgram,
+  globalState,
   getAuthorityAddress as getMarketAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "../hh-escrow/utils";
 
 import { ErrorCode, program, getNftFloorAddress } from "./utils";
@@ -46,6 +54,16 @@ describeFlaky("initialize nft floor resolver", () => {
     noAmount,
     resolver: resolver_,
     uri,
+    title,
+    category,
+    contentHash,
+    yesLimits,
+    noLimits,
+    allowlistRoot,
+    requireAcknowledgement,
+    allowEarlyResolution,
+    maxResolutionWindow,
+    resolverBond,
   }: Partial<InitializeMarketParams>): InitializeMarketParams => {
     closeTs ??= intoU64BN(unixTimestamp() + 3600n);
     expiryTs ??= closeTs.addn(3600);
@@ -53,7 +71,17 @@ describeFlaky("initialize nft floor resolver", () => {
     yesAmount ??= intoU64BN(YES_AMOUNT);
     noAmount ??= intoU64BN(NO_AMOUNT);
     resolver_ ??= resolver;
-    uri ??= "0".repeat(200);
+    uri ??= "https://example.com/market.json";
+    title ??= "Test market";
+    category ??= encodeCategory("test");
+    contentHash ??= new Array(32).fill(0);
+    yesLimits ??= NO_DEPOSIT_LIMITS;
+    noLimits ??= NO_DEPOSIT_LIMITS;
+    allowlistRoot ??= null;
+    requireAcknowledgement ??= false;
+    allowEarlyResolution ??= false;
+    maxResolutionWindow ??= RESOLUTION_WINDOW;
+    resolverBond ??= intoU64BN(0);
 
     return {
       closeTs,
@@ -63,15 +91,36 @@ describeFlaky("initialize nft floor resolver", () => {
       noAmount,
       resolver: resolver_,
       uri,
+      title,
+      category,
+      contentHash,
+      yesLimits,
+      noLimits,
+      allowlistRoot,
+      requireAcknowledgement,
+      allowEarlyResolution,
+      maxResolutionWindow,
+      resolverBond,
     };
   };
 
-  const initMarket = (params: Partial<InitializeMarketParams>) =>
+  const initMarket = async (params: Partial<InitializeMarketParams>) =>
     escrowProgram.methods.initializeMarket(initMarketParams(params)).accounts({
+      globalState: globalState.address,
+      ...(await getRegistryAccounts(
+        program.provider.wallet.publicKey,
+        mint.publicKey,
+      )),
       market: market.publicKey,
       authority: marketAuthority,
       creator: program.provider.wallet.publicKey,
       tokenMint: mint.publicKey,
+      mintPolicy: getMintPolicyAddress(mint.publicKey),
+      bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+      creatorTokenAccount: getAssociatedTokenAddress(
+        mint.publicKey,
+        program.provider.wallet.publicKey,
+      ),
       yesTokenAccount,
       noTokenAccount,
       systemProgram: SystemProgram.programId,
@@ -106,6 +155,9 @@ describeFlaky("initialize nft floor resolver", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     await sendTx(
       await createInitMintInstructions({
         mint,
@@ -133,10 +185,10 @@ describeFlaky("initialize nft floor resolver", () => {
     const time = await chain.blockTimestamp();
     const expiryTs = time + 2;
 
-    await initMarket({
+    await (await initMarket({
       closeTs: intoU64BN(expiryTs),
       expiryTs: intoU64BN(expiryTs),
-    })
+    }))
       .signers([market])
       .rpc();
 
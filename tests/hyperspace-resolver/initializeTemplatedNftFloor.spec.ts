@@ -0,0 +1,221 @@
+import type { InitializeMarketTemplateParams } from "../hh-escrow/utils";
+
+import { Keypair, SystemProgram } from "@solana/web3.js";
+
+import {
+  intoU64BN,
+  unixTimestamp,
+  createInitMintInstructions,
+  sendTx,
+  getAssociatedTokenAddress,
+} from "../utils";
+import {
+  ErrorCode as EscrowErrorCode,
+  program as escrowProgram,
+  globalState,
+  getAuthorityAddress as getMarketAuthorityAddress,
+  getSpawnAuthorityAddress,
+  getYesTokenAccountAddress,
+  getNoTokenAccountAddress,
+  getMarketAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
+} from "../hh-escrow/utils";
+
+import { ErrorCode, program, getNftFloorAddress } from "./utils";
+
+const YES_AMOUNT = 1_000_000n;
+const NO_AMOUNT = 2_000_000n;
+
+describe("initialize templated nft floor resolver", () => {
+  const mint = Keypair.generate();
+  const template = Keypair.generate();
+  const authority = Keypair.generate();
+
+  const market = getMarketAddress(template.publicKey, 0);
+  const resolver = getNftFloorAddress(market);
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  const spawnMarket = async () =>
+    escrowProgram.methods.spawnMarketFromTemplate().accounts({
+      globalState: globalState.address,
+      ...(await getRegistryAccounts(
+        program.provider.wallet.publicKey,
+        mint.publicKey,
+      )),
+      template: template.publicKey,
+      market,
+      authority: getMarketAuthorityAddress(market),
+      spawnAuthority: getSpawnAuthorityAddress(market),
+      payer: program.provider.wallet.publicKey,
+      tokenMint: mint.publicKey,
+      mintPolicy: getMintPolicyAddress(mint.publicKey),
+      bondTokenAccount: getBondTokenAccountAddress(market),
+      payerTokenAccount: getAssociatedTokenAddress(
+        mint.publicKey,
+        program.provider.wallet.publicKey,
+      ),
+      yesTokenAccount: getYesTokenAccountAddress(market)[0],
+      noTokenAccount: getNoTokenAccountAddress(market)[0],
+    });
+
+  const resolverAccounts = [
+    { pubkey: resolver, isSigner: false, isWritable: true },
+    { pubkey: program.programId, isSigner: false, isWritable: false },
+  ];
+
+  // The resolver is initialized without a payer, so it is funded beforehand.
+  const fundResolver = async () =>
+    SystemProgram.transfer({
+      fromPubkey: program.provider.wallet.publicKey,
+      toPubkey: resolver,
+      lamports:
+        await program.provider.connection.getMinimumBalanceForRentExemption(
+          8 + 32 + 32 + 1 + 8 + 4 + "foobar".length,
+        ),
+    });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+    await globalState.allowTemplateResolverProgram(program.programId);
+
+    await sendTx(
+      await createInitMintInstructions({
+        mint,
+        mintAuthority: program.provider.wallet.publicKey,
+      }),
+      [mint],
+    );
+
+    const closeTs = unixTimestamp() + 3600n;
+    const expiryTs = closeTs + 3600n;
+
+    const resolverData = program.coder.instruction.encode(
+      "initializeTemplatedNftFloor",
+      {
+        params: {
+          authority: authority.publicKey,
+          floorPrice: intoU64BN(42),
+          projectId: "foobar",
+        },
+      },
+    );
+
+    const params: InitializeMarketTemplateParams = {
+      closeTs: intoU64BN(closeTs),
+      expiryTs: intoU64BN(expiryTs),
+      cadence: intoU64BN(7n * 86_400n),
+      resolutionDelay: 3600,
+      yesAmount: intoU64BN(YES_AMOUNT),
+      noAmount: intoU64BN(NO_AMOUNT),
+      resolver: SystemProgram.programId,
+      resolverProgram: program.programId,
+      resolverData,
+      uriPrefix: "https://example.com/weekly/",
+      title: "Weekly floor market",
+      category: encodeCategory("nft"),
+      yesLimits: NO_DEPOSIT_LIMITS,
+      noLimits: NO_DEPOSIT_LIMITS,
+      requireAcknowledgement: true,
+      allowEarlyResolution: false,
+      maxResolutionWindow: RESOLUTION_WINDOW,
+      resolverBond: intoU64BN(0),
+    };
+
+    await escrowProgram.methods
+      .initializeMarketTemplate(params)
+      .accounts({
+        globalState: globalState.address,
+        template: template.publicKey,
+        creator: program.provider.wallet.publicKey,
+        tokenMint: mint.publicKey,
+        mintPolicy: getMintPolicyAddress(mint.publicKey),
+      })
+      .signers([template])
+      .rpc();
+  });
+
+  //////////////////////////////////////////////////////////////////////////////
+
+  it("fails if the resolver accounts are missing", async () => {
+    expect.assertions(1);
+
+    await expect((await spawnMarket()).rpc()).rejects.toThrowProgramError(
+      EscrowErrorCode.MissingResolverAccount,
+    );
+  });
+
+  it("fails if the resolver program is incorrect", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await spawnMarket())
+        .remainingAccounts([
+          { pubkey: resolver, isSigner: false, isWritable: true },
+          {
+            pubkey: escrowProgram.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+        ])
+        .rpc(),
+    ).rejects.toThrowProgramError(EscrowErrorCode.IncorrectResolverProgram);
+  });
+
+  it("fails if the resolver is not funded", async () => {
+    expect.assertions(1);
+
+    await expect(
+      (await spawnMarket()).remainingAccounts(resolverAccounts).rpc(),
+    ).rejects.toThrowProgramError(ErrorCode.ResolverNotFunded);
+  });
+
+  it("successfully initializes the resolver of a spawned market", async () => {
+    expect.assertions(5);
+
+    await (await spawnMarket())
+      .remainingAccounts(resolverAccounts)
+      .preInstructions([await fundResolver()])
+      .rpc();
+
+    const marketInfo = await escrowProgram.account.market.fetch(market);
+
+    expect(marketInfo.resolver).toEqualPubkey(resolver);
+    expect(marketInfo.acknowledged).toBe(false);
+
+    const info = await program.account.nftFloor.fetch(resolver);
+
+    expect(info.authority).toEqualPubkey(authority.publicKey);
+    expect(info.market).toEqualPubkey(market);
+    expect(info.floorPrice).toEqualBN(42);
+  });
+
+  it("successfully acknowledges the spawned market", async () => {
+    expect.assertions(2);
+
+    await program.methods
+      .acknowledgeTemplatedNftFloor()
+      .accounts({
+        resolver,
+        market,
+        authority: authority.publicKey,
+        escrowProgram: escrowProgram.programId,
+      })
+      .signers([authority])
+      .rpc();
+
+    const marketInfo = await escrowProgram.account.market.fetch(market);
+    expect(marketInfo.acknowledged).toBe(true);
+
+    const info = await program.account.nftFloor.fetch(resolver);
+    expect(info.acknowledged).toBe(true);
+  });
+});
@@ -5,12 +5,24 @@ import type { InitializeNftFloorParams } from "./utils";
 import { Keypair, SystemProgram } from "@solana/web3.js";
 import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
 
-import { intoU64BN, unixTimestamp, createInitMintInstructions } from "../utils";
+import {
+  intoU64BN,
+  unixTimestamp,
+  createInitMintInstructions,
+  getAssociatedTokenAddress,
+} from "../utils";
 import {
   program as escrowProgram,
+  globalState,
   getAuthorityAddress as getMarketAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "../hh-escrow/utils";
 
 import { ErrorCode, program, getNftFloorAddress } from "./utils";
@@ -44,6 +56,9 @@ describe("resolve nft floor resolver", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     const preIxs = await createInitMintInstructions({
       mint,
       mintAuthority: program.provider.wallet.publicKey,
@@ -60,17 +75,38 @@ describe("resolve nft floor resolver", () => {
         yesAmount: intoU64BN(YES_AMOUNT),
         noAmount: intoU64BN(NO_AMOUNT),
         resolver,
-        uri: "0".repeat(200),
+        uri: "https://example.com/market.json",
+        title: "Test market",
+        category: encodeCategory("test"),
+        contentHash: new Array(32).fill(0),
+        yesLimits: NO_DEPOSIT_LIMITS,
+        noLimits: NO_DEPOSIT_LIMITS,
+        allowlistRoot: null,
+        requireAcknowledgement: false,
+        allowEarlyResolution: false,
+        maxResolutionWindow: RESOLUTION_WINDOW,
+        resolverBond: intoU64BN(0),
       };
 
       preIxs.push(
         await escrowProgram.methods
           .initializeMarket(params)
           .accounts({
+            globalState: globalState.address,
+            ...(await getRegistryAccounts(
+              program.provider.wallet.publicKey,
+              mint.publicKey,
+            )),
             market: market.publicKey,
             authority: marketAuthority,
             creator: program.provider.wallet.publicKey,
             tokenMint: mint.publicKey,
+            mintPolicy: getMintPolicyAddress(mint.publicKey),
+            bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+            creatorTokenAccount: getAssociatedTokenAddress(
+              mint.publicKey,
+              program.provider.wallet.publicKey,
+            ),
             yesTokenAccount,
             noTokenAccount,
             systemProgram: SystemProgram.programId,
@@ -126,17 +162,38 @@ describe("resolve nft floor resolver", () => {
         yesAmount: intoU64BN(YES_AMOUNT),
         noAmount: intoU64BN(NO_AMOUNT),
         resolver: marketAuthority,
-        uri: "0".repeat(200),
+        uri: "https://example.com/market.json",
+        title: "Test market",
+        category: encodeCategory("test"),
+        contentHash: new Array(32).fill(0),
+        yesLimits: NO_DEPOSIT_LIMITS,
+        noLimits: NO_DEPOSIT_LIMITS,
+        allowlistRoot: null,
+        requireAcknowledgement: false,
+        allowEarlyResolution: false,
+        maxResolutionWindow: RESOLUTION_WINDOW,
+        resolverBond: intoU64BN(0),
       };
 
       preIxs.push(
         await escrowProgram.methods
           .initializeMarket(params)
           .accounts({
+            globalState: globalState.address,
+            ...(await getRegistryAccounts(
+              program.provider.wallet.publicKey,
+              mint.publicKey,
+            )),
             market: market.publicKey,
             authority: marketAuthority,
             creator: program.provider.wallet.publicKey,
             tokenMint: mint.publicKey,
+            mintPolicy: getMintPolicyAddress(mint.publicKey),
+            bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+            creatorTokenAccount: getAssociatedTokenAddress(
+              mint.publicKey,
+              program.provider.wallet.publicKey,
+            ),
             yesTokenAccount,
             noTokenAccount,
             systemProgram: SystemProgram.programId,
@@ -21,13 +21,21 @@ import {
   createInitMintInstructions,
   sendTx,
   chain,
+  getAssociatedTokenAddress,
 } from "../utils";
 import {
   program as escrowProgram,
+  globalState,
   getAuthorityAddress as getMarketAuthorityAddress,
   getYesTokenAccountAddress,
   getNoTokenAccountAddress,
   getUserPositionAddress,
+  NO_DEPOSIT_LIMITS,
+  RESOLUTION_WINDOW,
+  encodeCategory,
+  getRegistryAccounts,
+  getMintPolicyAddress,
+  getBondTokenAccountAddress,
 } from "../hh-escrow/utils";
 
 import { program, getNftFloorAddress } from "./utils";
@@ -64,6 +72,16 @@ describeFlaky("initialize nft floor resolver", () => {
     noAmount,
     resolver: resolver_,
     uri,
+    title,
+    category,
+    contentHash,
+    yesLimits,
+    noLimits,
+    allowlistRoot,
+    requireAcknowledgement,
+    allowEarlyResolution,
+    maxResolutionWindow,
+    resolverBond,
   }: Partial<InitializeMarketParams>): InitializeMarketParams => {
     closeTs ??= intoU64BN(unixTimestamp() + 3600n);
     expiryTs ??= closeTs.addn(3600);
@@ -71,7 +89,17 @@ describeFlaky("initialize nft floor resolver", () => {
     yesAmount ??= YES_AMOUNT;
     noAmount ??= NO_AMOUNT;
     resolver_ ??= resolver;
-    uri ??= "0".repeat(200);
+    uri ??= "https://example.com/market.json";
+    title ??= "Test market";
+    category ??= encodeCategory("test");
+    contentHash ??= new Array(32).fill(0);
+    yesLimits ??= NO_DEPOSIT_LIMITS;
+    noLimits ??= NO_DEPOSIT_LIMITS;
+    allowlistRoot ??= null;
+    requireAcknowledgement ??= false;
+    allowEarlyResolution ??= false;
+    maxResolutionWindow ??= RESOLUTION_WINDOW;
+    resolverBond ??= intoU64BN(0);
 
     return {
       closeTs,
@@ -81,15 +109,36 @@ describeFlaky("initialize nft floor resolver", () => {
       noAmount,
       resolver: resolver_,
       uri,
+      title,
+      category,
+      contentHash,
+      yesLimits,
+      noLimits,
+      allowlistRoot,
+      requireAcknowledgement,
+      allowEarlyResolution,
+      maxResolutionWindow,
+      resolverBond,
     };
   };
 
-  const initMarket = (params: Partial<InitializeMarketParams>) =>
+  const initMarket = async (params: Partial<InitializeMarketParams>) =>
     escrowProgram.methods.initializeMarket(initMarketParams(params)).accounts({
+      globalState: globalState.address,
+      ...(await getRegistryAccounts(
+        program.provider.wallet.publicKey,
+        mint.publicKey,
+      )),
       market: market.publicKey,
       authority: marketAuthority,
       creator: program.provider.wallet.publicKey,
       tokenMint: mint.publicKey,
+      mintPolicy: getMintPolicyAddress(mint.publicKey),
+      bondTokenAccount: getBondTokenAccountAddress(market.publicKey),
+      creatorTokenAccount: getAssociatedTokenAddress(
+        mint.publicKey,
+        program.provider.wallet.publicKey,
+      ),
       yesTokenAccount,
       noTokenAccount,
       systemProgram: SystemProgram.programId,
@@ -137,6 +186,9 @@ describeFlaky("initialize nft floor resolver", () => {
   //////////////////////////////////////////////////////////////////////////////
 
   beforeAll(async () => {
+    // Ensure global state is initialized and matches expected state.
+    await globalState.initialize();
+
     await sendTx(
       [
         ...(await createInitMintInstructions({
@@ -192,6 +244,8 @@ describeFlaky("initialize nft floor resolver", () => {
         yesAmount: YES_AMOUNT,
         noAmount: NO_AMOUNT,
         allowPartial: true,
+        proof: [],
+        referrer: null,
       })
       .accounts({
         market: market.publicKey,
@@ -221,10 +275,12 @@ describeFlaky("initialize nft floor resolver", () => {
 
       const floorPrice = 100n;
 
-      const initMarketIx = await initMarket({
-        closeTs: intoU64BN(expiryTs),
-        expiryTs: intoU64BN(expiryTs),
-      }).instruction();
+      const initMarketIx = await (
+        await initMarket({
+          closeTs: intoU64BN(expiryTs),
+          expiryTs: intoU64BN(expiryTs),
+        })
+      ).instruction();
 
       await initNftFloor({ floorPrice: intoU64BN(floorPrice) })
         .preInstructions([initMarketIx, userPositionIx, depositIx])